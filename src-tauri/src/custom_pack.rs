@@ -1,10 +1,41 @@
-use crate::sound_pack::{CategoryOverride, KeySound, SoundDefaults, SoundPack};
-use std::path::Path;
+use crate::keyboard::{canonical_key_name, ASSIGNABLE_KEYS};
+use crate::sound_pack::{
+    clamp_volume_jitter, discover_packs, fingerprint_dir, hash_file, is_contained_path,
+    is_current_checksum, mapping_paths, validate_pack, CategoryOverride, IssueSeverity, KeySound,
+    PackIssue, SoundDefaults, SoundPack, TriggerMode, MANIFEST_VERSION, MAX_KEY_DELAY_MS,
+    QUARANTINE_DIR,
+};
+use kira::sound::{static_sound::StaticSoundData, FromFileError};
+use kira::Frame;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 pub const DATA_VERSION: u32 = 1;
 pub const ALLOWED_EXTENSIONS: &[&str] = &["mp3", "wav", "ogg"];
 pub const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024; // 5MB
 
+/// Samples quieter than this (either channel) count as silence when trimming imports.
+const SILENCE_THRESHOLD: f32 = 0.01;
+
+/// Silence at least this long separates two sounds when splitting a recording (ms).
+const SPLIT_MIN_GAP_MS: u64 = 50;
+
+/// Import constraints, sent to the frontend so the file dialog filter stays in sync.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SupportedFormats {
+    pub extensions: Vec<String>,
+    /// Maximum import size in bytes
+    pub max_file_size: u64,
+}
+
+pub fn supported_formats() -> SupportedFormats {
+    SupportedFormats {
+        extensions: ALLOWED_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+        max_file_size: MAX_FILE_SIZE,
+    }
+}
+
 // --- Data Versioning ---
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -49,6 +80,7 @@ pub struct SlotInfo {
     pub slot: String,
     pub label: String,
     pub file_name: Option<String>,
+    pub trigger: TriggerMode,
 }
 
 pub fn get_all_slots(pack: &SoundPack) -> Vec<SlotInfo> {
@@ -75,6 +107,13 @@ pub fn get_all_slots(pack: &SoundPack) -> Vec<SlotInfo> {
                 .get("modifiers")
                 .and_then(|c| c.keydown.clone()),
         ),
+        (
+            "modifier_up",
+            "Modifiers (release)",
+            pack.category_overrides
+                .get("modifiers")
+                .and_then(|c| c.keyup.clone()),
+        ),
         (
             "backspace",
             "Backspace / Delete",
@@ -107,8 +146,9 @@ pub fn get_all_slots(pack: &SoundPack) -> Vec<SlotInfo> {
             };
             SlotInfo {
                 slot: slot.to_string(),
-                label: label.to_string(),
+                label: slot_label(pack, slot, label),
                 file_name,
+                trigger: slot_trigger(pack, slot),
             }
         })
         .collect();
@@ -119,7 +159,7 @@ pub fn get_all_slots(pack: &SoundPack) -> Vec<SlotInfo> {
         .iter()
         .filter(|(key, _)| key.as_str() != "Space" && key.as_str() != "Return")
         .collect();
-    per_key.sort_by_key(|(key, _)| (*key).clone());
+    per_key.sort_by(|(a, _), (b, _)| natural_key_cmp(a, b));
 
     for (key_name, key_sound) in per_key {
         let slot_id = format!("key:{}", key_name);
@@ -136,8 +176,9 @@ pub fn get_all_slots(pack: &SoundPack) -> Vec<SlotInfo> {
                 })
             });
         result.push(SlotInfo {
+            label: slot_label(pack, &slot_id, key_name),
+            trigger: slot_trigger(pack, &slot_id),
             slot: slot_id,
-            label: key_name.clone(),
             file_name,
         });
     }
@@ -145,6 +186,28 @@ pub fn get_all_slots(pack: &SoundPack) -> Vec<SlotInfo> {
     result
 }
 
+/// Order key names so trailing numbers compare numerically ("F2" before "F10").
+fn natural_key_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    fn split(key: &str) -> (&str, Option<u64>) {
+        let prefix = key.trim_end_matches(|c: char| c.is_ascii_digit());
+        (prefix, key[prefix.len()..].parse().ok())
+    }
+    split(a).cmp(&split(b)).then_with(|| a.cmp(b))
+}
+
+/// The user's custom label for a slot, or `default` if none is set.
+fn slot_label(pack: &SoundPack, slot: &str, default: &str) -> String {
+    pack.slot_labels
+        .get(slot)
+        .cloned()
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// When a slot's sound plays (on press unless set otherwise).
+fn slot_trigger(pack: &SoundPack, slot: &str) -> TriggerMode {
+    pack.slot_triggers.get(slot).copied().unwrap_or_default()
+}
+
 pub fn get_slot_path(pack: &SoundPack, slot: &str) -> Option<String> {
     match slot {
         "default" => Some(pack.defaults.keydown.clone()),
@@ -160,6 +223,10 @@ pub fn get_slot_path(pack: &SoundPack, slot: &str) -> Option<String> {
             .category_overrides
             .get("modifiers")
             .and_then(|c| c.keydown.clone()),
+        "modifier_up" => pack
+            .category_overrides
+            .get("modifiers")
+            .and_then(|c| c.keyup.clone()),
         "backspace" => pack
             .category_overrides
             .get("delete")
@@ -192,6 +259,7 @@ pub fn apply_slot_to_pack(pack: &mut SoundPack, slot: &str, path: Option<String>
                         keydown: None,
                         keyup: None,
                         volume: Some(1.0),
+                        ..Default::default()
                     })
                     .keydown = Some(p);
             } else {
@@ -206,46 +274,41 @@ pub fn apply_slot_to_pack(pack: &mut SoundPack, slot: &str, path: Option<String>
                         keydown: None,
                         keyup: None,
                         volume: Some(1.0),
+                        ..Default::default()
                     })
                     .keydown = Some(p);
             } else {
                 pack.key_overrides.remove("Return");
             }
         }
-        "modifier" => {
+        "modifier" | "modifier_up" => {
             if let Some(p) = path {
-                pack.category_overrides
+                let cat = pack
+                    .category_overrides
                     .entry("modifiers".into())
-                    .or_insert_with(|| CategoryOverride {
-                        keys: vec![
-                            "ShiftLeft".into(),
-                            "ShiftRight".into(),
-                            "ControlLeft".into(),
-                            "ControlRight".into(),
-                            "Alt".into(),
-                            "AltGr".into(),
-                            "MetaLeft".into(),
-                            "MetaRight".into(),
-                        ],
-                        keydown: None,
-                        keyup: None,
-                        volume: Some(0.6),
-                    })
-                    .keydown = Some(p);
-            } else {
-                pack.category_overrides.remove("modifiers");
+                    .or_insert_with(|| modifiers_category(Some(0.6)));
+                if slot == "modifier" {
+                    cat.keydown = Some(p);
+                } else {
+                    cat.keyup = Some(p);
+                }
+            } else if let Some(cat) = pack.category_overrides.get_mut("modifiers") {
+                if slot == "modifier" {
+                    cat.keydown = None;
+                } else {
+                    cat.keyup = None;
+                }
+                // Drop the category once neither channel has a sound
+                if cat.keydown.is_none() && cat.keyup.is_none() {
+                    pack.category_overrides.remove("modifiers");
+                }
             }
         }
         "backspace" => {
             if let Some(p) = path {
                 pack.category_overrides
                     .entry("delete".into())
-                    .or_insert_with(|| CategoryOverride {
-                        keys: vec!["Backspace".into(), "Delete".into()],
-                        keydown: None,
-                        keyup: None,
-                        volume: None,
-                    })
+                    .or_insert_with(|| delete_category(None))
                     .keydown = Some(p);
             } else {
                 pack.category_overrides.remove("delete");
@@ -261,6 +324,7 @@ pub fn apply_slot_to_pack(pack: &mut SoundPack, slot: &str, path: Option<String>
                             keydown: None,
                             keyup: None,
                             volume: Some(1.0),
+                            ..Default::default()
                         })
                         .keydown = Some(p);
                 } else {
@@ -271,52 +335,310 @@ pub fn apply_slot_to_pack(pack: &mut SoundPack, slot: &str, path: Option<String>
     }
 }
 
-pub fn write_pack_json(pack: &SoundPack) -> Result<(), String> {
+/// Empty "modifiers" category override, as created by the modifier slots.
+fn modifiers_category(volume: Option<f64>) -> CategoryOverride {
+    CategoryOverride {
+        keys: vec![
+            "ShiftLeft".into(),
+            "ShiftRight".into(),
+            "ControlLeft".into(),
+            "ControlRight".into(),
+            "Alt".into(),
+            "AltGr".into(),
+            "MetaLeft".into(),
+            "MetaRight".into(),
+        ],
+        keydown: None,
+        keyup: None,
+        volume,
+        volume_jitter: None,
+        enabled: None,
+    }
+}
+
+/// Empty "delete" category override, as created by the backspace slot.
+fn delete_category(volume: Option<f64>) -> CategoryOverride {
+    CategoryOverride {
+        keys: vec!["Backspace".into(), "Delete".into()],
+        keydown: None,
+        keyup: None,
+        volume,
+        volume_jitter: None,
+        enabled: None,
+    }
+}
+
+// --- Presets ---
+
+/// Starting layouts for a new custom pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresetKind {
+    /// Softer modifiers and a slightly quieter backspace/delete
+    Typewriter,
+}
+
+impl PresetKind {
+    /// Category overrides the preset sets up, without sounds.
+    fn categories(self) -> Vec<(&'static str, CategoryOverride)> {
+        match self {
+            PresetKind::Typewriter => vec![
+                ("modifiers", modifiers_category(Some(0.6))),
+                ("delete", delete_category(Some(0.8))),
+            ],
+        }
+    }
+}
+
+/// Pre-create a preset's category overrides so the user only has to import
+/// sounds. Existing categories keep their sounds and keys; only the volume
+/// is set from the preset.
+pub fn apply_preset_to_pack(pack_dir: &Path, preset: PresetKind) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    for (name, category) in preset.categories() {
+        let volume = category.volume;
+        pack.category_overrides
+            .entry(name.to_string())
+            .or_insert(category)
+            .volume = volume;
+    }
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Current Unix time in seconds.
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Write pack.json, stamping `modified_at` with the current time.
+pub fn write_pack_json(pack: &mut SoundPack) -> Result<(), String> {
+    pack.check_sound_paths()?;
+    pack.modified_at = Some(unix_now());
+    if !pack.checksums.is_empty() {
+        // A sealed pack stays sealed over the app's own edits, so
+        // checksums follow the sounds the pack uses now
+        pack.checksums = sound_checksums(pack);
+    }
     let json = serde_json::to_string_pretty(pack)
         .map_err(|e| format!("Failed to serialize pack: {}", e))?;
     let path = pack.base_path.join("pack.json");
     std::fs::write(&path, json).map_err(|e| format!("Failed to write pack.json: {}", e))
 }
 
+/// Longest slug (in characters) `slugify` produces, keeping pack directory
+/// paths well inside Windows path limits even after `unique_id` adds a suffix.
+const MAX_SLUG_LEN: usize = 64;
+
+/// Longest display name (in characters) a pack may have; longer names are cut.
+pub const MAX_PACK_NAME_LEN: usize = 100;
+
 pub fn slugify(name: &str) -> String {
-    name.to_lowercase()
+    let slug = name
+        .to_lowercase()
         .chars()
         .map(|c| if c.is_alphanumeric() { c } else { '-' })
         .collect::<String>()
         .split('-')
         .filter(|s| !s.is_empty())
         .collect::<Vec<_>>()
-        .join("-")
+        .join("-");
+    let truncated: String = slug.chars().take(MAX_SLUG_LEN).collect();
+    truncated.trim_end_matches('-').to_string()
+}
+
+/// Trim a pack name and cut it to MAX_PACK_NAME_LEN characters.
+fn clean_pack_name(name: &str) -> Result<String, String> {
+    let name: String = name.trim().chars().take(MAX_PACK_NAME_LEN).collect();
+    let name = name.trim_end().to_string();
+    if name.is_empty() {
+        return Err("Pack name cannot be empty".into());
+    }
+    Ok(name)
+}
+
+/// Ids a custom pack may not take: app-data entries and bundled pack names
+/// that a user pack must never shadow.
+pub const RESERVED_IDS: &[&str] = &[
+    "default",
+    "data-version",
+    "soundpacks",
+    "user-soundpacks",
+    "resources",
+    "settings",
+    TRASH_DIR,
+    "bundled-backups",
+    QUARANTINE_DIR,
+];
+
+pub fn is_reserved_id(id: &str) -> bool {
+    RESERVED_IDS.contains(&id)
+}
+
+/// Whether `id` can be used for a new pack directory under `dir`.
+/// Any existing entry blocks it, including stray files and dangling symlinks
+/// (which `exists()` misses but `create_dir_all` would still trip over);
+/// `unique_id` then moves on to the next candidate.
+fn is_id_available(id: &str, dir: &Path) -> bool {
+    !is_reserved_id(id) && std::fs::symlink_metadata(dir.join(id)).is_err()
 }
 
 pub fn unique_id(base: &str, dir: &Path) -> String {
-    if !dir.join(base).exists() {
+    if is_id_available(base, dir) {
         return base.to_string();
     }
     for i in 2..1000 {
         let candidate = format!("{}-{}", base, i);
-        if !dir.join(&candidate).exists() {
+        if is_id_available(&candidate, dir) {
             return candidate;
         }
     }
-    format!(
-        "{}-{}",
-        base,
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs()
-    )
+    format!("{}-{}", base, unix_now())
 }
 
-pub fn generate_silence_wav(path: &Path) -> Result<(), std::io::Error> {
-    // Minimal WAV: 44-byte header + 882 bytes silence (10ms @ 44100Hz mono 16-bit)
-    let sample_rate: u32 = 44100;
-    let bits_per_sample: u16 = 16;
-    let num_channels: u16 = 1;
-    let num_samples: u32 = 441; // ~10ms
-    let data_size = num_samples * u32::from(num_channels) * u32::from(bits_per_sample / 8);
+/// Write 10ms of 16-bit silence with `num_channels` interleaved channels.
+pub fn generate_silence_wav(path: &Path, num_channels: u16) -> Result<(), std::io::Error> {
+    // Minimal WAV: 44-byte header + 10ms of silence @ 44100Hz 16-bit
+    let num_frames: u32 = 441; // ~10ms
+    let data_size = num_frames * u32::from(num_channels) * 2;
+
+    let mut buf = wav_header(44100, num_channels, data_size);
+    buf.resize(44 + data_size as usize, 0); // silence
+
+    std::fs::write(path, buf)
+}
+
+/// Sample rate of generated click sounds
+const SYNTH_SAMPLE_RATE: u32 = 44100;
+
+/// Mono 16-bit click: a sine at `frequency` Hz fading out exponentially with
+/// time constant `decay_ms`, cut off once it is inaudible.
+fn generate_click_wav(frequency: f32, decay_ms: f32) -> Vec<u8> {
+    let decay_frames = decay_ms / 1000.0 * SYNTH_SAMPLE_RATE as f32;
+    // After 7 time constants the envelope is below 0.1%
+    let num_frames = (decay_frames * 7.0).ceil() as u32;
+    let mut buf = wav_header(SYNTH_SAMPLE_RATE, 1, num_frames * 2);
+    for i in 0..num_frames {
+        let t = i as f32 / SYNTH_SAMPLE_RATE as f32;
+        let envelope = 0.6 * (-(i as f32) / decay_frames).exp();
+        let sample = envelope * (std::f32::consts::TAU * frequency * t).sin();
+        buf.extend_from_slice(&((sample * f32::from(i16::MAX)).round() as i16).to_le_bytes());
+    }
+    buf
+}
+
+/// Basic facts about an audio file.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct AudioInfo {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub duration_ms: u64,
+}
+
+/// Length of one slot's sound, for `slot_durations`
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SlotDuration {
+    /// 0 when the file is missing or can't be decoded
+    pub duration_ms: u64,
+    pub readable: bool,
+}
+
+/// Length of every configured slot's sound, keyed by slot id.
+pub fn slot_durations(pack: &SoundPack) -> BTreeMap<String, SlotDuration> {
+    get_all_slots(pack)
+        .into_iter()
+        .filter_map(|info| {
+            let path = get_slot_path(pack, &info.slot)?;
+            let duration = match audio_info(&pack.base_path.join(path)) {
+                Ok(audio) => SlotDuration {
+                    duration_ms: audio.duration_ms,
+                    readable: true,
+                },
+                Err(_) => SlotDuration {
+                    duration_ms: 0,
+                    readable: false,
+                },
+            };
+            Some((info.slot, duration))
+        })
+        .collect()
+}
+
+/// Sample rate, channel count, and length of an audio file. PCM WAV is read
+/// from its header; other formats are decoded (mono sources decode to two
+/// identical channels, so those report 1).
+pub fn audio_info(path: &Path) -> Result<AudioInfo, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    if let Some(info) = wav_info(&data) {
+        return Ok(info);
+    }
+    decode_audio(&data).map(|(_, info)| info)
+}
+
+/// Decode audio along with its `AudioInfo`, for edits that need the samples.
+fn decode_audio(data: &[u8]) -> Result<(StaticSoundData, AudioInfo), String> {
+    let sound = StaticSoundData::from_cursor(std::io::Cursor::new(data.to_vec()))
+        .map_err(|e| describe_decode_error(&e, data))?;
+    let info = wav_info(data).unwrap_or_else(|| {
+        let mono = sound.frames.iter().all(|f| f.left == f.right);
+        AudioInfo {
+            sample_rate: sound.sample_rate,
+            channels: if mono { 1 } else { 2 },
+            duration_ms: sound.duration().as_millis() as u64,
+        }
+    });
+    Ok((sound, info))
+}
+
+/// Read `AudioInfo` from a WAV file's fmt and data chunks.
+fn wav_info(data: &[u8]) -> Option<AudioInfo> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return None;
+    }
+    let u16_at = |i: usize| Some(u16::from_le_bytes(data.get(i..i + 2)?.try_into().ok()?));
+    let u32_at = |i: usize| Some(u32::from_le_bytes(data.get(i..i + 4)?.try_into().ok()?));
+
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let size = u32_at(pos + 4)? as usize;
+        let body = pos + 8;
+        match &data[pos..pos + 4] {
+            // (channels, sample rate, bytes per frame)
+            b"fmt " => format = Some((u16_at(body + 2)?, u32_at(body + 4)?, u16_at(body + 12)?)),
+            b"data" => {
+                let (channels, sample_rate, block_align) = format?;
+                if sample_rate == 0 || block_align == 0 {
+                    return None;
+                }
+                // Tolerate a data size past the end of a truncated file
+                let bytes = size.min(data.len() - body) as u64;
+                let frames = bytes / u64::from(block_align);
+                return Some(AudioInfo {
+                    sample_rate,
+                    channels,
+                    duration_ms: frames * 1000 / u64::from(sample_rate),
+                });
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even size
+        pos = body + size + size % 2;
+    }
+    None
+}
 
+/// 44-byte header of a 16-bit PCM WAV file holding `data_size` bytes of samples.
+fn wav_header(sample_rate: u32, num_channels: u16, data_size: u32) -> Vec<u8> {
+    let bits_per_sample: u16 = 16;
     let mut buf = Vec::with_capacity(44 + data_size as usize);
     // RIFF header
     buf.extend_from_slice(b"RIFF");
@@ -336,9 +658,179 @@ pub fn generate_silence_wav(path: &Path) -> Result<(), std::io::Error> {
     // data chunk
     buf.extend_from_slice(b"data");
     buf.extend_from_slice(&data_size.to_le_bytes());
-    buf.resize(44 + data_size as usize, 0); // silence
+    buf
+}
 
-    std::fs::write(path, buf)
+/// Encode decoded audio as 16-bit PCM WAV at its own sample rate.
+/// Sounds whose channels are identical are written as mono.
+fn encode_wav(sound: &StaticSoundData) -> Vec<u8> {
+    let frames = match sound.slice {
+        Some((start, end)) => &sound.frames[start..end],
+        None => &sound.frames[..],
+    };
+    let mono = frames.iter().all(|f| f.left == f.right);
+    encode_wav_channels(sound, if mono { 1 } else { 2 })
+}
+
+/// Encode decoded audio as 16-bit PCM WAV with `num_channels` channels (a
+/// mono file keeps the left channel; more than two are written as stereo).
+fn encode_wav_channels(sound: &StaticSoundData, num_channels: u16) -> Vec<u8> {
+    let frames = match sound.slice {
+        Some((start, end)) => &sound.frames[start..end],
+        None => &sound.frames[..],
+    };
+    let mono = num_channels == 1;
+    let num_channels = num_channels.clamp(1, 2);
+    let data_size = (frames.len() * usize::from(num_channels) * 2) as u32;
+    let to_i16 = |s: f32| (s.clamp(-1.0, 1.0) * f32::from(i16::MAX)).round() as i16;
+
+    let mut buf = wav_header(sound.sample_rate, num_channels, data_size);
+    for frame in frames {
+        buf.extend_from_slice(&to_i16(frame.left).to_le_bytes());
+        if !mono {
+            buf.extend_from_slice(&to_i16(frame.right).to_le_bytes());
+        }
+    }
+    buf
+}
+
+/// Sample rate `optimize_pack_dir` converts every sound to
+const OPTIMIZE_SAMPLE_RATE: u32 = 44100;
+
+/// Linearly resample decoded audio to `sample_rate`, keeping only its slice.
+fn resample(sound: &StaticSoundData, sample_rate: u32) -> StaticSoundData {
+    let frames = match sound.slice {
+        Some((start, end)) => &sound.frames[start..end],
+        None => &sound.frames[..],
+    };
+    if sound.sample_rate == sample_rate || frames.is_empty() {
+        return StaticSoundData {
+            frames: frames.into(),
+            slice: None,
+            ..sound.clone()
+        };
+    }
+
+    let step = f64::from(sound.sample_rate) / f64::from(sample_rate);
+    let len = ((frames.len() as f64 / step).round() as usize).max(1);
+    let last = frames.len() - 1;
+    let resampled: Vec<Frame> = (0..len)
+        .map(|i| {
+            let pos = i as f64 * step;
+            let index = (pos as usize).min(last);
+            let next = frames[(index + 1).min(last)];
+            let t = (pos - index as f64) as f32;
+            frames[index] + (next - frames[index]) * t
+        })
+        .collect();
+    StaticSoundData {
+        sample_rate,
+        frames: resampled.into(),
+        slice: None,
+        ..sound.clone()
+    }
+}
+
+/// Phrase typed by `render_pack_demo_file`
+const DEMO_PHRASE: &str = "the quick brown fox jumps over the lazy dog";
+
+/// Sample rate of rendered demos
+const DEMO_SAMPLE_RATE: u32 = 44100;
+
+/// Time between a demo key's press and its release (ms)
+const DEMO_HOLD_MS: u64 = 70;
+
+/// Key typed for a demo character (letters and space only)
+fn demo_key(c: char) -> Option<String> {
+    match c {
+        ' ' => Some("Space".into()),
+        c if c.is_ascii_alphabetic() => Some(format!("Key{}", c.to_ascii_uppercase())),
+        _ => None,
+    }
+}
+
+/// Mix the pack typing DEMO_PHRASE into a 16-bit stereo wav at `dst`, offline
+/// (nothing goes through the output device). Presses are 110-150ms apart in a
+/// fixed pattern, so the same pack always renders the same file.
+pub fn render_pack_demo_file(pack: &SoundPack, dst: &Path) -> Result<(), String> {
+    // (start ms, sound, volume) of every sound the phrase triggers
+    let mut events: Vec<(u64, PathBuf, f64)> = Vec::new();
+    let mut at_ms = 200;
+    for (i, c) in DEMO_PHRASE.chars().enumerate() {
+        let Some(key) = demo_key(c) else {
+            continue;
+        };
+        let volume = pack.resolve_volume(&key);
+        if let Some(path) = pack.resolve_keydown(&key) {
+            events.push((at_ms, path, volume));
+        }
+        if let Some(path) = pack.resolve_keyup(&key).filter(|_| pack.keyup_enabled) {
+            events.push((at_ms + DEMO_HOLD_MS, path, volume));
+        }
+        at_ms += 110 + (i as u64 * 37) % 40;
+    }
+
+    let mut decoded: BTreeMap<PathBuf, Option<StaticSoundData>> = BTreeMap::new();
+    let mut mix: Vec<Frame> = Vec::new();
+    for (at_ms, path, volume) in events {
+        let sound = decoded
+            .entry(path)
+            .or_insert_with_key(|path| StaticSoundData::from_file(path).ok());
+        let Some(sound) = sound else {
+            continue;
+        };
+        let frames = match sound.slice {
+            Some((start, end)) => &sound.frames[start..end],
+            None => &sound.frames[..],
+        };
+
+        // Nearest-sample resampling is plenty for short clicks
+        let step = f64::from(sound.sample_rate) / f64::from(DEMO_SAMPLE_RATE);
+        let len = (frames.len() as f64 / step) as usize;
+        let start = (at_ms * u64::from(DEMO_SAMPLE_RATE) / 1000) as usize;
+        if mix.len() < start + len {
+            mix.resize(start + len, Frame::ZERO);
+        }
+        for (i, out) in mix[start..start + len].iter_mut().enumerate() {
+            *out += frames[(i as f64 * step) as usize] * volume as f32;
+        }
+    }
+    if mix.is_empty() {
+        return Err("None of the pack's sounds could be decoded".into());
+    }
+    // Let the last sound ring out
+    mix.resize(mix.len() + DEMO_SAMPLE_RATE as usize / 5, Frame::ZERO);
+
+    let to_i16 = |s: f32| (s.clamp(-1.0, 1.0) * f32::from(i16::MAX)).round() as i16;
+    let mut buf = wav_header(DEMO_SAMPLE_RATE, 2, (mix.len() * 4) as u32);
+    for frame in &mix {
+        buf.extend_from_slice(&to_i16(frame.left).to_le_bytes());
+        buf.extend_from_slice(&to_i16(frame.right).to_le_bytes());
+    }
+    std::fs::write(dst, buf).map_err(|e| format!("Failed to write demo: {}", e))
+}
+
+/// Put a silent wav with `num_channels` channels at `dst`, copying the bundled
+/// silence.wav when it is usable and has that many channels. Otherwise (missing,
+/// truncated, corrupt, or another layout) one is generated.
+fn write_silence(resource_dir: &Path, dst: &Path, num_channels: u16) -> Result<(), std::io::Error> {
+    let silence_src = resource_dir.join("resources").join("silence.wav");
+    let bundled_channels = audio_info(&silence_src).ok().map(|info| info.channels);
+    if bundled_channels.is_some_and(|c| c != num_channels) {
+        return generate_silence_wav(dst, num_channels);
+    }
+    if silence_src.exists() {
+        let copied =
+            std::fs::copy(&silence_src, dst).is_ok() && StaticSoundData::from_file(dst).is_ok();
+        if copied {
+            return Ok(());
+        }
+        log::warn!(
+            "Bundled silence.wav is unusable ({}), generating one",
+            silence_src.display()
+        );
+    }
+    generate_silence_wav(dst, num_channels)
 }
 
 pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
@@ -361,15 +853,172 @@ pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), std::io::Error>
     Ok(())
 }
 
+/// What `sync_bundled_packs_dir` did with each bundled pack (by id)
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct BundledSync {
+    /// Not in app data yet, copied over
+    pub installed: Vec<String>,
+    /// Bundled copy is newer; the old copy was backed up and replaced
+    pub updated: Vec<String>,
+    /// App data copy is as new or newer, left alone
+    pub kept: Vec<String>,
+}
+
+/// Compare dotted version strings numerically ("1.10.0" > "1.9"); missing or
+/// non-numeric parts count as 0.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parse = |v: &str| -> Vec<u64> {
+        v.trim()
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (a, b) = (parse(a), parse(b));
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|o| o.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// Copy bundled packs from `src` into the app data copy at `dst`, replacing a
+/// copy only when the bundled pack is newer (`manifest_version`, then `version`).
+/// Replaced copies are moved to `backup_dir/<id>` (latest backup only), so edits
+/// to a bundled pack aren't lost on update. Copies that fail to load count as old.
+pub fn sync_bundled_packs_dir(
+    src: &Path,
+    dst: &Path,
+    backup_dir: &Path,
+) -> Result<BundledSync, String> {
+    std::fs::create_dir_all(dst)
+        .map_err(|e| format!("Failed to create {}: {}", dst.display(), e))?;
+    let mut report = BundledSync::default();
+
+    let mut bundled: Vec<PathBuf> = std::fs::read_dir(src)
+        .map_err(|e| format!("Failed to read {}: {}", src.display(), e))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.join("pack.json").exists())
+        .collect();
+    bundled.sort();
+
+    for src_pack in bundled {
+        let Some(dir_name) = src_pack.file_name() else {
+            continue;
+        };
+        let id = dir_name.to_string_lossy().into_owned();
+        let dst_pack = dst.join(dir_name);
+        let copy = |report: &mut Vec<String>| {
+            copy_dir_recursive(&src_pack, &dst_pack)
+                .map_err(|e| format!("Failed to copy pack '{}': {}", id, e))?;
+            report.push(id.clone());
+            Ok::<_, String>(())
+        };
+
+        if !dst_pack.exists() {
+            copy(&mut report.installed)?;
+            continue;
+        }
+
+        let is_newer = match (SoundPack::load(&src_pack), SoundPack::load(&dst_pack)) {
+            (Ok(new), Ok(old)) => new
+                .manifest_version
+                .cmp(&old.manifest_version)
+                .then_with(|| compare_versions(&new.version, &old.version))
+                .is_gt(),
+            (Ok(_), Err(_)) => true,
+            (Err(e), _) => {
+                log::warn!("Skipping bundled pack '{}': {}", id, e);
+                false
+            }
+        };
+        if !is_newer {
+            report.kept.push(id);
+            continue;
+        }
+
+        // The old copy waits under a temporary name until the new one is in
+        // place, so a failed copy can put it back and keep the previous backup
+        let backup = backup_dir.join(dir_name);
+        let pending = backup_dir.join(format!("{}.pending", id));
+        std::fs::create_dir_all(backup_dir)
+            .map_err(|e| format!("Failed to create backup folder: {}", e))?;
+        if pending.exists() {
+            std::fs::remove_dir_all(&pending).ok();
+        }
+        move_dir(&dst_pack, &pending)
+            .map_err(|e| format!("Failed to back up pack '{}': {}", id, e))?;
+        if let Err(e) = copy(&mut report.updated) {
+            std::fs::remove_dir_all(&dst_pack).ok();
+            if let Err(restore) = move_dir(&pending, &dst_pack) {
+                log::warn!(
+                    "Failed to restore pack '{}' from {}: {}",
+                    id,
+                    pending.display(),
+                    restore
+                );
+            }
+            return Err(e);
+        }
+        if backup.exists() {
+            std::fs::remove_dir_all(&backup).ok();
+        }
+        if let Err(e) = move_dir(&pending, &backup) {
+            log::warn!("Failed to keep backup of pack '{}': {}", id, e);
+        }
+    }
+
+    record_bundled_fingerprint(src, dst);
+    Ok(report)
+}
+
+/// File in the app data pack folder recording `fingerprint_dir` of the
+/// bundled packs as of the last sync
+const BUNDLED_HASH_FILE: &str = ".bundled-hash";
+
+/// Note in `dst` that it is in sync with the bundled packs in `src`.
+fn record_bundled_fingerprint(src: &Path, dst: &Path) {
+    let result = fingerprint_dir(src)
+        .and_then(|fingerprint| std::fs::write(dst.join(BUNDLED_HASH_FILE), fingerprint));
+    if let Err(e) = result {
+        log::warn!("Failed to record bundled pack hash: {}", e);
+    }
+}
+
+/// Run `sync_bundled_packs_dir` only if the bundled packs changed since the
+/// last sync (by `fingerprint_dir`) or one of their copies went missing.
+/// Returns None when the sync was skipped.
+pub fn sync_bundled_packs_if_changed(
+    src: &Path,
+    dst: &Path,
+    backup_dir: &Path,
+) -> Result<Option<BundledSync>, String> {
+    let fingerprint =
+        fingerprint_dir(src).map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+    let unchanged = std::fs::read_to_string(dst.join(BUNDLED_HASH_FILE))
+        .is_ok_and(|old| old.trim() == fingerprint);
+    let all_present = std::fs::read_dir(src)
+        .map_err(|e| format!("Failed to read {}: {}", src.display(), e))?
+        .flatten()
+        .filter(|e| e.path().join("pack.json").exists())
+        .all(|e| dst.join(e.file_name()).is_dir());
+    if unchanged && all_present {
+        return Ok(None);
+    }
+
+    sync_bundled_packs_dir(src, dst, backup_dir).map(Some)
+}
+
 pub fn create_custom_pack_dir(
     user_soundpacks_dir: &Path,
     resource_dir: &Path,
     name: &str,
 ) -> Result<SoundPack, String> {
-    let name = name.trim().to_string();
-    if name.is_empty() {
-        return Err("Pack name cannot be empty".into());
-    }
+    let name = clean_pack_name(name)?;
 
     let base_id = slugify(&name);
     let id = unique_id(&base_id, user_soundpacks_dir);
@@ -380,57 +1029,104 @@ pub fn create_custom_pack_dir(
         .map_err(|e| format!("Failed to create pack directory: {}", e))?;
 
     // Copy silence.wav as default keydown sound
-    let silence_src = resource_dir.join("resources").join("silence.wav");
-    let silence_dst = sounds_dir.join("keydown.wav");
-    if silence_src.exists() {
-        std::fs::copy(&silence_src, &silence_dst)
-            .map_err(|e| format!("Failed to copy silence.wav: {}", e))?;
-    } else {
-        generate_silence_wav(&silence_dst)
-            .map_err(|e| format!("Failed to generate silence: {}", e))?;
-    }
+    write_silence(resource_dir, &sounds_dir.join("keydown.wav"), 1)
+        .map_err(|e| format!("Failed to generate silence: {}", e))?;
 
-    let pack = SoundPack {
+    let now = unix_now();
+    let mut pack = SoundPack {
+        manifest_version: MANIFEST_VERSION,
         id,
         name,
         author: "User".into(),
         version: "1.0.0".into(),
         description: String::new(),
+        notes: String::new(),
         source: Some("user".into()),
+        color: None,
+        keyup_enabled: true,
         defaults: SoundDefaults {
             keydown: "sounds/keydown.wav".into(),
             keyup: None,
             volume: 0.8,
+            volume_jitter: 0.0,
         },
         key_overrides: Default::default(),
         category_overrides: Default::default(),
         original_names: Default::default(),
+        slot_labels: Default::default(),
+        slot_triggers: Default::default(),
+        checksums: Default::default(),
+        created_at: Some(now),
+        modified_at: Some(now),
         base_path: pack_dir,
+        sideloaded: false,
     };
 
-    write_pack_json(&pack)?;
+    write_pack_json(&mut pack)?;
     Ok(pack)
 }
 
-pub fn import_sound_to_pack(
-    pack_dir: &Path,
-    slot: &str,
-    src_path: &Path,
+/// Shape of the clicks in a generated pack
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SynthParams {
+    /// Pitch of the default key click (Hz); the other slots are tuned relative to it
+    pub frequency: f32,
+    /// How fast the click fades (ms to drop to about a third)
+    pub decay_ms: f32,
+}
+
+impl Default for SynthParams {
+    fn default() -> Self {
+        Self {
+            frequency: 1800.0,
+            decay_ms: 6.0,
+        }
+    }
+}
+
+/// Slots of a generated pack with their (frequency, decay) relative to `SynthParams`
+const SYNTH_SLOTS: &[(&str, f32, f32)] = &[
+    ("default", 1.0, 1.0),
+    ("space", 0.55, 1.6),
+    ("enter", 0.7, 1.4),
+    ("modifier", 1.25, 0.7),
+    ("backspace", 0.85, 1.0),
+];
+
+/// Create a user pack whose default, space, enter, modifier, and backspace
+/// slots are synthesized clicks, for users without sound files of their own.
+pub fn create_synth_pack_dir(
+    user_soundpacks_dir: &Path,
+    resource_dir: &Path,
+    name: &str,
+    params: SynthParams,
 ) -> Result<SoundPack, String> {
-    if !pack_dir.join("pack.json").exists() {
-        return Err("Custom pack not found".into());
+    if !(100.0..=8000.0).contains(&params.frequency) {
+        return Err("Frequency must be between 100 and 8000 Hz".into());
+    }
+    if !(1.0..=100.0).contains(&params.decay_ms) {
+        return Err("Decay must be between 1 and 100 ms".into());
     }
 
-    if !src_path.exists() {
-        return Err("File not found".into());
+    let mut pack = create_custom_pack_dir(user_soundpacks_dir, resource_dir, name)?;
+    let pack_dir = pack.base_path.clone();
+    for (slot, pitch, decay) in SYNTH_SLOTS {
+        let wav = generate_click_wav(params.frequency * pitch, params.decay_ms * decay);
+        match write_sound_to_slot(&pack_dir, slot, "wav", &wav, None) {
+            Ok(updated) => pack = updated,
+            Err(e) => {
+                std::fs::remove_dir_all(&pack_dir).ok();
+                return Err(e);
+            }
+        }
     }
+    Ok(pack)
+}
 
-    // Validate extension
-    let ext = src_path
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.to_lowercase())
-        .unwrap_or_default();
+/// Validate an import's extension and size. Returns the lowercased extension.
+fn validate_import_source(ext: &str, size: u64) -> Result<String, String> {
+    let ext = ext.trim_start_matches('.').to_lowercase();
     if !ALLOWED_EXTENSIONS.contains(&ext.as_str()) {
         return Err(format!(
             "Unsupported format '{}'. Use mp3, wav, or ogg.",
@@ -438,814 +1134,4081 @@ pub fn import_sound_to_pack(
         ));
     }
 
-    // Validate file size
-    let metadata = std::fs::metadata(src_path).map_err(|e| format!("Failed to read file: {}", e))?;
-    if metadata.len() > MAX_FILE_SIZE {
+    if size > MAX_FILE_SIZE {
         return Err(format!(
             "File too large ({:.1}MB). Maximum is 5MB.",
-            metadata.len() as f64 / (1024.0 * 1024.0)
+            size as f64 / (1024.0 * 1024.0)
         ));
     }
 
-    // Remove old sound file for this slot (avoids orphans when extension changes)
-    let mut pack = SoundPack::load(pack_dir)?;
-    if let Some(old_path) = get_slot_path(&pack, slot) {
-        let abs_old = pack_dir.join(&old_path);
-        if abs_old.exists() {
-            std::fs::remove_file(&abs_old).ok();
+    Ok(ext)
+}
+
+/// Guess the codec inside common audio containers from their header bytes.
+fn sniff_codec(data: &[u8]) -> Option<&'static str> {
+    let header = &data[..data.len().min(512)];
+    let contains = |needle: &[u8]| header.windows(needle.len()).any(|w| w == needle);
+
+    if header.starts_with(b"OggS") {
+        if contains(b"OpusHead") {
+            return Some("Opus");
+        }
+        if contains(b"Speex") {
+            return Some("Speex");
+        }
+        if contains(b"\x7fFLAC") {
+            return Some("FLAC");
+        }
+        if contains(b"\x01vorbis") {
+            return Some("Vorbis");
+        }
+    }
+    if header.starts_with(b"fLaC") {
+        return Some("FLAC");
+    }
+    if header.len() >= 8 && &header[4..8] == b"ftyp" {
+        return Some("AAC/ALAC (MP4)");
+    }
+    None
+}
+
+/// Turn a decode failure into a message that names the codec when the
+/// container was readable but its codec isn't supported (e.g. Opus in .ogg).
+fn describe_decode_error(err: &FromFileError, data: &[u8]) -> String {
+    if let FromFileError::SymphoniaError(e) = err {
+        let msg = e.to_string();
+        if msg.contains("unsupported") && msg.contains("codec") {
+            return match sniff_codec(data) {
+                Some(codec) => format!(
+                    "Unsupported audio codec: {}. Use mp3, wav, or Ogg Vorbis.",
+                    codec
+                ),
+                None => "Unsupported audio codec. Use mp3, wav, or Ogg Vorbis.".into(),
+            };
         }
     }
+    format!("Could not decode audio file: {}", err)
+}
+
+/// Make sure the audio data can actually be decoded before it goes into a pack.
+fn check_decodable(data: &[u8]) -> Result<(), String> {
+    StaticSoundData::from_cursor(std::io::Cursor::new(data.to_vec()))
+        .map(|_| ())
+        .map_err(|e| describe_decode_error(&e, data))
+}
+
+/// Write validated audio data into a slot of the pack and update pack.json.
+fn write_sound_to_slot(
+    pack_dir: &Path,
+    slot: &str,
+    ext: &str,
+    data: &[u8],
+    original_name: Option<String>,
+) -> Result<SoundPack, String> {
+    let mut pack = SoundPack::load(pack_dir)?;
+    let old_path = get_slot_path(&pack, slot);
 
     // Copy file to pack sounds directory
     // Sanitize slot name for filesystem (e.g. "key:KeyA" -> "key-KeyA")
     let safe_slot = slot.replace(':', "-");
     let dst_filename = format!("keydown-{}.{}", safe_slot, ext);
     let dst = pack_dir.join("sounds").join(&dst_filename);
-    std::fs::copy(src_path, &dst).map_err(|e| format!("Failed to copy file: {}", e))?;
+    std::fs::write(&dst, data).map_err(|e| format!("Failed to write sound file: {}", e))?;
     let sound_path = format!("sounds/{}", dst_filename);
     apply_slot_to_pack(&mut pack, slot, Some(sound_path));
 
+    // Remove old sound file for this slot (avoids orphans when extension changes)
+    if let Some(old_path) = old_path {
+        remove_if_unreferenced(&pack, &old_path);
+    }
+
     // Store original file name for UI display
-    let original_name = src_path
-        .file_name()
-        .and_then(|f| f.to_str())
-        .unwrap_or(&dst_filename)
-        .to_string();
-    pack.original_names.insert(slot.to_string(), original_name);
+    match original_name {
+        Some(name) => {
+            pack.original_names.insert(slot.to_string(), name);
+        }
+        None => {
+            pack.original_names.remove(slot);
+        }
+    }
 
-    write_pack_json(&pack)?;
+    write_pack_json(&mut pack)?;
     Ok(pack)
 }
 
-pub fn remove_slot_from_pack(
-    pack_dir: &Path,
-    slot: &str,
-    resource_dir: &Path,
+/// Folder an imported file came from, for opening the next file dialog there.
+/// None for a bare file name with no folder.
+pub fn import_dir(src_path: &Path) -> Option<PathBuf> {
+    src_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+}
+
+/// Read and validate a file for import: (normalized extension, contents, file name).
+fn read_import_file(src_path: &Path) -> Result<(String, Vec<u8>, Option<String>), String> {
+    if !src_path.exists() {
+        return Err("File not found".into());
+    }
+
+    let ext = src_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let metadata =
+        std::fs::metadata(src_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let ext = validate_import_source(ext, metadata.len())?;
+
+    let data = std::fs::read(src_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    check_decodable(&data)?;
+
+    let original_name = src_path
+        .file_name()
+        .and_then(|f| f.to_str())
+        .map(|s| s.to_string());
+    Ok((ext, data, original_name))
+}
+
+/// Import a sound file into a slot.
+pub fn import_sound_to_pack(
+    pack_dir: &Path,
+    slot: &str,
+    src_path: &Path,
+) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let (ext, data, original_name) = read_import_file(src_path)?;
+    write_sound_to_slot(pack_dir, slot, &ext, &data, original_name)
+}
+
+/// Import a sound file into a slot with near-silent audio cut from both ends.
+/// The result is stored as wav; `original_names` still records the source
+/// file name.
+pub fn import_sound_to_pack_trimmed(
+    pack_dir: &Path,
+    slot: &str,
+    src_path: &Path,
+) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let (_, data, original_name) = read_import_file(src_path)?;
+    let data = trim_silence_wav(&data)?;
+    write_sound_to_slot(pack_dir, slot, "wav", &data, original_name)
+}
+
+/// Import a file onto the first key in ASSIGNABLE_KEYS without its own keydown
+/// sound. Returns the slot it landed in (e.g. "key:KeyA").
+pub fn import_sound_to_next_empty_key(pack_dir: &Path, src_path: &Path) -> Result<String, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let pack = SoundPack::load(pack_dir)?;
+    let key = ASSIGNABLE_KEYS
+        .iter()
+        .find(|key| {
+            pack.key_overrides
+                .get(**key)
+                .is_none_or(|ks| ks.keydown.is_none())
+        })
+        .ok_or("Every letter and digit key already has a sound")?;
+    let slot = key_slot(key);
+    import_sound_to_pack(pack_dir, &slot, src_path)?;
+    Ok(slot)
+}
+
+/// Cut leading and trailing samples below SILENCE_THRESHOLD and re-encode as wav.
+/// Audio that is silent throughout is kept whole.
+fn trim_silence_wav(data: &[u8]) -> Result<Vec<u8>, String> {
+    let (sound, info) = decode_audio(data)?;
+    let audible = |f: &Frame| f.left.abs() > SILENCE_THRESHOLD || f.right.abs() > SILENCE_THRESHOLD;
+    let start = sound.frames.iter().position(audible);
+    let end = sound.frames.iter().rposition(audible);
+    let trimmed = match (start, end) {
+        (Some(start), Some(end)) => StaticSoundData {
+            slice: Some((start, end + 1)),
+            ..sound
+        },
+        _ => sound,
+    };
+    Ok(encode_wav_channels(&trimmed, info.channels))
+}
+
+/// Frame ranges of the sounds in `sound`: audible stretches separated by at
+/// least SPLIT_MIN_GAP_MS of silence, each trimmed to its audible samples.
+fn split_by_silence(sound: &StaticSoundData) -> Vec<(usize, usize)> {
+    let min_gap = (u64::from(sound.sample_rate) * SPLIT_MIN_GAP_MS / 1000) as usize;
+    let audible = |f: &Frame| f.left.abs() > SILENCE_THRESHOLD || f.right.abs() > SILENCE_THRESHOLD;
+    let mut segments: Vec<(usize, usize)> = Vec::new();
+    for (i, frame) in sound.frames.iter().enumerate() {
+        if !audible(frame) {
+            continue;
+        }
+        match segments.last_mut() {
+            Some((_, end)) if i - *end < min_gap => *end = i + 1,
+            _ => segments.push((i, i + 1)),
+        }
+    }
+    segments
+}
+
+/// Split one recording of several clicks at its silent gaps and import the
+/// pieces, in order, into `slots` as wavs. The number of sounds found must
+/// match the number of slots.
+pub fn import_split_by_silence_to_pack(
+    pack_dir: &Path,
+    src_path: &Path,
+    slots: &[String],
+) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+    if slots.is_empty() {
+        return Err("No slots to assign".into());
+    }
+    if let Some(slot) = slots
+        .iter()
+        .enumerate()
+        .find_map(|(i, slot)| slots[..i].contains(slot).then_some(slot))
+    {
+        return Err(format!("Slot listed twice: {}", slot));
+    }
+
+    let (_, data, original_name) = read_import_file(src_path)?;
+    let (sound, info) = decode_audio(&data)?;
+    let segments = split_by_silence(&sound);
+    if segments.len() != slots.len() {
+        return Err(format!(
+            "Found {} sound(s) separated by silence but {} slot(s) were given",
+            segments.len(),
+            slots.len()
+        ));
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    let mut written = Vec::new();
+    let mut old_paths = Vec::new();
+    for (i, (slot, segment)) in slots.iter().zip(segments).enumerate() {
+        let piece = StaticSoundData {
+            slice: Some(segment),
+            ..sound.clone()
+        };
+        let dst_filename = format!("keydown-{}.wav", slot.replace(':', "-"));
+        let dst = pack_dir.join("sounds").join(&dst_filename);
+        let existed = dst.exists();
+        if let Err(e) = std::fs::write(&dst, encode_wav_channels(&piece, info.channels)) {
+            for path in written {
+                std::fs::remove_file(path).ok();
+            }
+            return Err(format!("Failed to write sound file: {}", e));
+        }
+        if !existed {
+            written.push(dst);
+        }
+
+        old_paths.extend(get_slot_path(&pack, slot));
+        apply_slot_to_pack(&mut pack, slot, Some(format!("sounds/{}", dst_filename)));
+        match &original_name {
+            Some(name) => pack.original_names.insert(
+                slot.clone(),
+                format!("{} ({}/{})", name, i + 1, slots.len()),
+            ),
+            None => pack.original_names.remove(slot),
+        };
+    }
+    if let Err(e) = write_pack_json(&mut pack) {
+        for path in written {
+            std::fs::remove_file(path).ok();
+        }
+        return Err(e);
+    }
+    for old_path in old_paths {
+        remove_if_unreferenced(&pack, &old_path);
+    }
+    Ok(pack)
+}
+
+/// Import one file and point every key in `keys` at it, so keys that share a
+/// sound share a single file in sounds/.
+pub fn assign_sound_to_keys_in_pack(
+    pack_dir: &Path,
+    keys: &[String],
+    src_path: &Path,
+) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+    if keys.is_empty() || keys.iter().any(|k| k.trim().is_empty()) {
+        return Err("No keys to assign".into());
+    }
+    let (ext, data, original_name) = read_import_file(src_path)?;
+    // Load first, so a broken pack.json doesn't leave the copy behind
+    let mut pack = SoundPack::load(pack_dir)?;
+
+    let sounds_dir = pack_dir.join("sounds");
+    let mut n = 1;
+    let dst_filename = loop {
+        let name = format!("keydown-shared-{}.{}", n, ext);
+        if !sounds_dir.join(&name).exists() {
+            break name;
+        }
+        n += 1;
+    };
+    std::fs::write(sounds_dir.join(&dst_filename), &data)
+        .map_err(|e| format!("Failed to write sound file: {}", e))?;
+    let sound_path = format!("sounds/{}", dst_filename);
+
+    let mut old_paths = Vec::new();
+    for key in keys {
+        let slot = key_slot(key);
+        old_paths.extend(get_slot_path(&pack, &slot));
+        apply_slot_to_pack(&mut pack, &slot, Some(sound_path.clone()));
+        match &original_name {
+            Some(name) => pack.original_names.insert(slot, name.clone()),
+            None => pack.original_names.remove(&slot),
+        };
+    }
+    if let Err(e) = write_pack_json(&mut pack) {
+        std::fs::remove_file(sounds_dir.join(&dst_filename)).ok();
+        return Err(e);
+    }
+    for old_path in old_paths {
+        remove_if_unreferenced(&pack, &old_path);
+    }
+    Ok(pack)
+}
+
+/// Slot that owns a key's sound: Space and Return have their own fixed slots.
+fn key_slot(key: &str) -> String {
+    match key {
+        "Space" => "space".to_string(),
+        "Return" => "enter".to_string(),
+        _ => format!("key:{}", key),
+    }
+}
+
+/// Outcome of `import_folder_to_pack`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FolderImport {
+    /// Key names that received a sound
+    pub imported: Vec<String>,
+    /// File names that don't match a key name (or duplicate one already imported)
+    pub skipped: Vec<String>,
+}
+
+/// Import every `<KeyName>.<ext>` file in `folder` (e.g. `KeyA.wav`, `Space.mp3`)
+/// into that key's slot with a single pack.json write. Nothing is written unless
+/// every matching file is valid audio; a failed write removes the files added so far.
+pub fn import_folder_to_pack(pack_dir: &Path, folder: &Path) -> Result<FolderImport, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(folder)
+        .map_err(|e| format!("Failed to read folder: {}", e))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+    files.sort();
+
+    let mut matched = Vec::new();
+    let mut skipped = Vec::new();
+    for path in files {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+        let key = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(canonical_key_name)
+            .filter(|_| ALLOWED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            .filter(|key| !matched.iter().any(|(k, _)| k == key));
+        match key {
+            Some(key) => matched.push((key, path)),
+            None => skipped.push(file_name),
+        }
+    }
+
+    // Validate everything before touching the pack
+    let mut sounds = Vec::new();
+    for (key, path) in matched {
+        let (ext, data, original_name) =
+            read_import_file(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        sounds.push((key, ext, data, original_name));
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    let mut written = Vec::new();
+    let mut old_paths = Vec::new();
+    for (key, ext, data, original_name) in &sounds {
+        let slot = key_slot(key);
+        let dst_filename = format!("keydown-{}.{}", slot.replace(':', "-"), ext);
+        let dst = pack_dir.join("sounds").join(&dst_filename);
+        let existed = dst.exists();
+        if let Err(e) = std::fs::write(&dst, data) {
+            for path in written {
+                std::fs::remove_file(path).ok();
+            }
+            return Err(format!("Failed to write sound file: {}", e));
+        }
+        if !existed {
+            written.push(dst);
+        }
+
+        old_paths.extend(get_slot_path(&pack, &slot));
+        apply_slot_to_pack(&mut pack, &slot, Some(format!("sounds/{}", dst_filename)));
+        match original_name {
+            Some(name) => pack.original_names.insert(slot, name.clone()),
+            None => pack.original_names.remove(&slot),
+        };
+    }
+    if let Err(e) = write_pack_json(&mut pack) {
+        for path in written {
+            std::fs::remove_file(path).ok();
+        }
+        return Err(e);
+    }
+    for old_path in old_paths {
+        remove_if_unreferenced(&pack, &old_path);
+    }
+
+    Ok(FolderImport {
+        imported: sounds
+            .into_iter()
+            .map(|(key, ..)| key.to_string())
+            .collect(),
+        skipped,
+    })
+}
+
+/// Import audio from an in-memory buffer (e.g. drag and drop) into a slot.
+/// Validation matches `import_sound_to_pack`.
+pub fn import_sound_bytes_to_pack(
+    pack_dir: &Path,
+    slot: &str,
+    data: &[u8],
+    ext: &str,
+) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let ext = validate_import_source(ext, data.len() as u64)?;
+    check_decodable(data)?;
+    write_sound_to_slot(pack_dir, slot, &ext, data, None)
+}
+
+pub fn remove_slot_from_pack(
+    pack_dir: &Path,
+    slot: &str,
+    resource_dir: &Path,
+) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    clear_slot(&mut pack, slot, resource_dir);
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Outcome of `remove_slots_from_pack`
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct RemovedSlots {
+    /// Slots that no longer have a sound
+    pub removed: Vec<String>,
+    /// Slots put back to silence instead (only "default", which can't be empty)
+    pub reset: Vec<String>,
+}
+
+/// Remove several slots with a single pack.json write.
+/// Slots that aren't assigned are skipped.
+pub fn remove_slots_from_pack(
+    pack_dir: &Path,
+    slots: &[String],
+    resource_dir: &Path,
+) -> Result<(SoundPack, RemovedSlots), String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    let mut outcome = RemovedSlots::default();
+    for slot in slots {
+        if get_slot_path(&pack, slot).is_none()
+            || outcome.removed.contains(slot)
+            || outcome.reset.contains(slot)
+        {
+            continue;
+        }
+        clear_slot(&mut pack, slot, resource_dir);
+        if slot == "default" {
+            outcome.reset.push(slot.clone());
+        } else {
+            outcome.removed.push(slot.clone());
+        }
+    }
+
+    write_pack_json(&mut pack)?;
+    Ok((pack, outcome))
+}
+
+/// Delete a slot's sound file and unassign it (the default slot resets to silence).
+fn clear_slot(pack: &mut SoundPack, slot: &str, resource_dir: &Path) {
+    let old_path = get_slot_path(pack, slot);
+    // The replacement silence keeps the channel layout of the default it replaces
+    let default_channels = (slot == "default").then(|| {
+        audio_info(&pack.base_path.join(&pack.defaults.keydown))
+            .map(|info| info.channels)
+            .unwrap_or(1)
+    });
+
+    if slot == "default" {
+        pack.defaults.keydown = "sounds/keydown.wav".into();
+    } else {
+        apply_slot_to_pack(pack, slot, None);
+    }
+
+    // Delete the slot's sound file unless another slot shares it
+    if let Some(path) = old_path {
+        remove_if_unreferenced(pack, &path);
+    }
+
+    if let Some(channels) = default_channels {
+        // Reset default to silence.wav
+        let silence = pack.base_path.join("sounds").join("keydown.wav");
+        write_silence(resource_dir, &silence, channels).ok();
+    }
+
+    pack.original_names.remove(slot);
+    if slot.starts_with("key:") {
+        // The per-key slot itself is gone, so its label and trigger go too
+        pack.slot_labels.remove(slot);
+        pack.slot_triggers.remove(slot);
+    }
+}
+
+/// Whether any default/override in the pack still points at `path`.
+fn is_sound_referenced(pack: &SoundPack, path: &str) -> bool {
+    let matches = |p: &Option<String>| p.as_deref() == Some(path);
+    pack.defaults.keydown == path
+        || matches(&pack.defaults.keyup)
+        || pack
+            .key_overrides
+            .values()
+            .any(|k| matches(&k.keydown) || matches(&k.keyup))
+        || pack
+            .category_overrides
+            .values()
+            .any(|c| matches(&c.keydown) || matches(&c.keyup))
+}
+
+/// Delete a sound file once nothing in the pack refers to it.
+fn remove_if_unreferenced(pack: &SoundPack, path: &str) {
+    if is_sound_referenced(pack, path) {
+        return;
+    }
+    let abs_path = pack.base_path.join(path);
+    if abs_path.exists() {
+        std::fs::remove_file(&abs_path).ok();
+    }
+}
+
+/// Bring `original_names` back in line with the pack's slots: configured slots
+/// without a usable name get the sound file's basename, and entries for slots
+/// that no longer exist are dropped.
+pub fn repair_original_names(pack_dir: &Path) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    let configured: Vec<(String, String)> = get_all_slots(&pack)
+        .into_iter()
+        .filter_map(|info| get_slot_path(&pack, &info.slot).map(|path| (info.slot, path)))
+        .collect();
+
+    pack.original_names
+        .retain(|slot, _| configured.iter().any(|(s, _)| s == slot));
+
+    for (slot, path) in configured {
+        // The silence placeholder has no original file
+        if slot == "default" && path == "sounds/keydown.wav" {
+            continue;
+        }
+        let has_name = pack
+            .original_names
+            .get(&slot)
+            .is_some_and(|name| !name.trim().is_empty());
+        if has_name {
+            continue;
+        }
+        if let Some(base) = Path::new(&path).file_name().and_then(|f| f.to_str()) {
+            pack.original_names.insert(slot, base.to_string());
+        }
+    }
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Enable or silence every key in an existing category override.
+pub fn set_pack_category_enabled(
+    pack_dir: &Path,
+    category: &str,
+    enabled: bool,
+) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    let cat = pack
+        .category_overrides
+        .get_mut(category)
+        .ok_or_else(|| format!("Category '{}' not found", category))?;
+    cat.enabled = if enabled { None } else { Some(false) };
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Replace the keys of an existing category override. Key names are matched
+/// case-insensitively; keys moved here leave any other category, so the new
+/// grouping takes effect.
+pub fn set_pack_category_keys(
+    pack_dir: &Path,
+    category: &str,
+    keys: &[String],
 ) -> Result<SoundPack, String> {
     if !pack_dir.join("pack.json").exists() {
         return Err("Custom pack not found".into());
     }
 
-    let mut pack = SoundPack::load(pack_dir)?;
+    let mut canonical: Vec<String> = Vec::with_capacity(keys.len());
+    for key in keys {
+        let name = canonical_key_name(key.trim()).ok_or(format!("Unknown key: {}", key))?;
+        if !canonical.iter().any(|k| k == name) {
+            canonical.push(name.to_string());
+        }
+    }
+    if canonical.is_empty() {
+        return Err("A category needs at least one key".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    if !pack.category_overrides.contains_key(category) {
+        return Err(format!("Category '{}' not found", category));
+    }
+    for (name, cat) in pack.category_overrides.iter_mut() {
+        if name == category {
+            cat.keys = canonical.clone();
+        } else {
+            cat.keys.retain(|k| !canonical.contains(k));
+        }
+    }
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Complete sound mapping for a pack, saved in one go by the batch editor.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SoundPackEdit {
+    pub defaults: SoundDefaults,
+    #[serde(default)]
+    pub key_overrides: BTreeMap<String, KeySound>,
+    #[serde(default)]
+    pub category_overrides: BTreeMap<String, CategoryOverride>,
+}
+
+impl SoundPackEdit {
+    /// Every sound path referenced by this mapping.
+    fn referenced_paths(&self) -> Vec<&str> {
+        mapping_paths(
+            &self.defaults,
+            &self.key_overrides,
+            &self.category_overrides,
+        )
+    }
+}
+
+/// Replace a pack's whole mapping. Nothing is written unless every referenced
+/// file exists inside the pack directory.
+pub fn apply_pack_mapping(pack_dir: &Path, mapping: SoundPackEdit) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+
+    for path in mapping.referenced_paths() {
+        if !is_contained_path(path) || !pack_dir.join(path).is_file() {
+            return Err(format!("Sound file not found in pack: {}", path));
+        }
+    }
+
+    pack.defaults = mapping.defaults;
+    pack.key_overrides = mapping.key_overrides;
+    pack.category_overrides = mapping.category_overrides;
+    pack.clamp_volumes();
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Parse a theme color. Empty clears it; otherwise it must be "#RRGGBB".
+fn parse_pack_color(color: &str) -> Result<Option<String>, String> {
+    let color = color.trim();
+    if color.is_empty() {
+        return Ok(None);
+    }
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(format!(
+            "Invalid color '{}': expected a hex color like #1A2B3C",
+            color
+        ));
+    }
+    Ok(Some(color.to_ascii_uppercase()))
+}
+
+/// Change a custom pack's display name.
+pub fn rename_pack(pack_dir: &Path, new_name: &str) -> Result<SoundPack, String> {
+    let new_name = clean_pack_name(new_name)?;
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    pack.name = new_name;
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Rename a pack and move it to a folder (and id) slugified from the new name,
+/// made unique among user packs. Fails if the id belongs to a bundled pack.
+pub fn change_pack_id_dir(
+    user_soundpacks_dir: &Path,
+    bundled_dir: &Path,
+    pack_id: &str,
+    new_name: &str,
+) -> Result<SoundPack, String> {
+    let pack_dir = user_soundpacks_dir.join(pack_id);
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+    let new_name = clean_pack_name(new_name)?;
+    let base_id = slugify(&new_name);
+    if base_id.is_empty() {
+        return Err("Pack name needs at least one letter or digit".into());
+    }
+
+    let mut pack = SoundPack::load(&pack_dir)?;
+    let new_id = if base_id == pack_id {
+        base_id
+    } else {
+        unique_id(&base_id, user_soundpacks_dir)
+    };
+    if new_id != pack_id && bundled_dir.join(&new_id).join("pack.json").exists() {
+        return Err(format!("'{}' is already used by a bundled pack", new_id));
+    }
+
+    if new_id != pack_id {
+        let new_dir = user_soundpacks_dir.join(&new_id);
+        std::fs::rename(&pack_dir, &new_dir)
+            .map_err(|e| format!("Failed to move pack folder: {}", e))?;
+        pack.base_path = new_dir;
+    }
+    pack.id = new_id;
+    pack.name = new_name;
+    if let Err(e) = write_pack_json(&mut pack) {
+        // Keep the folder name matching the id still in pack.json
+        if pack.base_path != pack_dir {
+            if let Err(undo) = std::fs::rename(&pack.base_path, &pack_dir) {
+                log::warn!(
+                    "Failed to move {} back to {}: {}",
+                    pack.base_path.display(),
+                    pack_dir.display(),
+                    undo
+                );
+            }
+        }
+        return Err(e);
+    }
+    Ok(pack)
+}
+
+/// Set or clear (empty string) a pack's theme color.
+pub fn apply_pack_color(pack_dir: &Path, color: &str) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let color = parse_pack_color(color)?;
+    let mut pack = SoundPack::load(pack_dir)?;
+    pack.color = color;
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Turn a pack's keyup sounds on or off, keeping the assigned files.
+pub fn apply_keyup_enabled(pack_dir: &Path, enabled: bool) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    pack.keyup_enabled = enabled;
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Set a pack's default per-press volume jitter (clamped to 0.0..=MAX_VOLUME_JITTER).
+pub fn apply_volume_jitter(pack_dir: &Path, jitter: f64) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    pack.defaults.volume_jitter = clamp_volume_jitter(jitter);
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Set how long (ms, clamped to MAX_KEY_DELAY_MS) a key slot's keydown sound
+/// waits before starting; 0 clears it. Only key slots ("space", "enter",
+/// "key:<KeyName>") take a delay.
+pub fn apply_slot_delay(pack_dir: &Path, slot: &str, ms: u64) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+    let key_name = match slot {
+        "space" => "Space",
+        "enter" => "Return",
+        _ => slot
+            .strip_prefix("key:")
+            .ok_or_else(|| format!("Slot '{}' is not a key slot", slot))?,
+    };
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    let delay_ms = (ms > 0).then(|| ms.min(MAX_KEY_DELAY_MS));
+    match pack.key_overrides.get_mut(key_name) {
+        Some(key_sound) => {
+            key_sound.delay_ms = delay_ms;
+            // An override that only held the delay has nothing left to do
+            if key_sound.is_noop() {
+                pack.key_overrides.remove(key_name);
+            }
+        }
+        None if delay_ms.is_some() => {
+            pack.key_overrides.insert(
+                key_name.to_string(),
+                KeySound {
+                    keydown: None,
+                    keyup: None,
+                    volume: None,
+                    delay_ms,
+                    ..Default::default()
+                },
+            );
+        }
+        None => {}
+    }
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Remove key and category overrides that change nothing (see
+/// `SoundPack::noop_overrides`). Returns the names of the removed overrides.
+pub fn prune_empty_overrides_dir(pack_dir: &Path) -> Result<Vec<String>, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    let removed = pack.noop_overrides();
+    if removed.is_empty() {
+        return Ok(removed);
+    }
+    pack.key_overrides.retain(|_, ks| !ks.is_noop());
+    pack.category_overrides.retain(|_, cat| !cat.is_noop());
+
+    write_pack_json(&mut pack)?;
+    Ok(removed)
+}
+
+/// Longest notes (in characters) a pack may carry, to keep pack.json small.
+pub const MAX_PACK_NOTES_LEN: usize = 4000;
+
+/// Replace a pack's freeform notes (empty clears them).
+pub fn apply_pack_notes(pack_dir: &Path, notes: &str) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+    if notes.chars().count() > MAX_PACK_NOTES_LEN {
+        return Err(format!(
+            "Notes are too long (max {} characters)",
+            MAX_PACK_NOTES_LEN
+        ));
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    pack.notes = notes.to_string();
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Set a slot's display label, or clear it (empty string) to use the default.
+pub fn apply_slot_label(pack_dir: &Path, slot: &str, label: &str) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    if !get_all_slots(&pack).iter().any(|s| s.slot == slot) {
+        return Err(format!("Unknown slot: {}", slot));
+    }
+
+    let label = label.trim();
+    if label.is_empty() {
+        pack.slot_labels.remove(slot);
+    } else {
+        pack.slot_labels.insert(slot.to_string(), label.to_string());
+    }
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Record the hash of every sound file the pack uses in its pack.json, so
+/// `find_checksum_mismatches` can spot files changed afterwards.
+pub fn write_pack_checksums_dir(pack_dir: &Path) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Sound pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    if let Some(missing) = pack
+        .sound_paths()
+        .into_iter()
+        .find(|path| !pack_dir.join(path).is_file())
+    {
+        return Err(format!("Failed to read {}: file not found", missing));
+    }
+    pack.checksums = sound_checksums(&pack);
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+/// Hash of every readable sound file the pack uses.
+fn sound_checksums(pack: &SoundPack) -> BTreeMap<String, String> {
+    pack.sound_paths()
+        .into_iter()
+        .filter_map(|path| {
+            let hash = hash_file(&pack.base_path.join(path)).ok()?;
+            Some((path.to_string(), hash))
+        })
+        .collect()
+}
+
+/// Sound files whose contents no longer match the pack's recorded checksums
+/// (missing files included). Checksums from older versions are skipped; the
+/// next save replaces them.
+pub fn find_checksum_mismatches(pack: &SoundPack) -> Vec<String> {
+    pack.checksums
+        .iter()
+        .filter(|(_, hash)| is_current_checksum(hash))
+        .filter(|(path, hash)| {
+            hash_file(&pack.base_path.join(path)).map_or(true, |current| &current != *hash)
+        })
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+/// Set when a slot's sound plays. "modifier_up" is a release sound already
+/// and can't be changed.
+pub fn apply_slot_trigger(
+    pack_dir: &Path,
+    slot: &str,
+    mode: TriggerMode,
+) -> Result<SoundPack, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+    if slot == "modifier_up" {
+        return Err("The modifier release slot always plays on release".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    if !get_all_slots(&pack).iter().any(|s| s.slot == slot) {
+        return Err(format!("Unknown slot: {}", slot));
+    }
+
+    if mode == TriggerMode::default() {
+        pack.slot_triggers.remove(slot);
+    } else {
+        pack.slot_triggers.insert(slot.to_string(), mode);
+    }
+
+    write_pack_json(&mut pack)?;
+    Ok(pack)
+}
+
+pub fn delete_pack_dir(pack_dir: &Path) -> Result<(), String> {
+    std::fs::remove_dir_all(pack_dir)
+        .map_err(|e| format!("Failed to delete pack: {}", e))
+}
+
+// --- Trash ---
+
+/// Folder in app data that soft-deleted packs are moved to
+pub const TRASH_DIR: &str = "trash";
+
+/// A soft-deleted pack waiting in the trash
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TrashedPack {
+    /// Folder name in the trash, used to restore it
+    pub trash_id: String,
+    /// Id the pack had before it was deleted
+    pub id: String,
+    pub name: String,
+}
+
+/// Move a folder, copying when a rename isn't possible (e.g. across drives).
+/// A failed copy removes whatever reached `dst`, leaving `src` as it was.
+fn move_dir(src: &Path, dst: &Path) -> std::io::Result<()> {
+    std::fs::rename(src, dst).or_else(|_| {
+        if let Err(e) = copy_dir_recursive(src, dst) {
+            let _ = std::fs::remove_dir_all(dst);
+            return Err(e);
+        }
+        std::fs::remove_dir_all(src)
+    })
+}
+
+/// Move a pack into `trash_dir` instead of deleting it. Returns its trash id.
+pub fn trash_pack_dir(pack_dir: &Path, trash_dir: &Path) -> Result<String, String> {
+    let name = pack_dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid pack folder")?;
+    std::fs::create_dir_all(trash_dir)
+        .map_err(|e| format!("Failed to create trash folder: {}", e))?;
+
+    let trash_id = unique_id(name, trash_dir);
+    move_dir(pack_dir, &trash_dir.join(&trash_id))
+        .map_err(|e| format!("Failed to move pack to trash: {}", e))?;
+    Ok(trash_id)
+}
+
+/// Packs in the trash, sorted by trash id.
+pub fn trashed_packs(trash_dir: &Path) -> Vec<TrashedPack> {
+    let mut packs: Vec<TrashedPack> = discover_packs(trash_dir)
+        .into_iter()
+        .filter_map(|pack| {
+            let trash_id = pack.base_path.file_name()?.to_str()?.to_string();
+            Some(TrashedPack {
+                trash_id,
+                id: pack.id,
+                name: pack.name,
+            })
+        })
+        .collect();
+    packs.sort_by(|a, b| a.trash_id.cmp(&b.trash_id));
+    packs
+}
+
+/// Move a trashed pack back into `user_soundpacks_dir` under its old id, or a
+/// suffixed one if that id has been taken since.
+pub fn restore_trashed_pack_dir(
+    trash_dir: &Path,
+    user_soundpacks_dir: &Path,
+    trash_id: &str,
+) -> Result<SoundPack, String> {
+    if Path::new(trash_id).file_name().and_then(|n| n.to_str()) != Some(trash_id) {
+        return Err("Invalid trash id".into());
+    }
+    let src = trash_dir.join(trash_id);
+    if !src.join("pack.json").exists() {
+        return Err("Pack not found in trash".into());
+    }
+
+    let mut pack = SoundPack::load(&src)?;
+    let id = unique_id(&pack.id, user_soundpacks_dir);
+    let dst = user_soundpacks_dir.join(&id);
+    std::fs::create_dir_all(user_soundpacks_dir)
+        .map_err(|e| format!("Failed to create {}: {}", user_soundpacks_dir.display(), e))?;
+    move_dir(&src, &dst).map_err(|e| format!("Failed to restore pack: {}", e))?;
+
+    pack.base_path = dst;
+    if pack.id != id {
+        pack.id = id;
+        write_pack_json(&mut pack)?;
+    }
+    Ok(pack)
+}
+
+/// Permanently delete everything in the trash. Returns the number of packs removed.
+pub fn empty_trash_dir(trash_dir: &Path) -> Result<usize, String> {
+    let Ok(entries) = std::fs::read_dir(trash_dir) else {
+        return Ok(0);
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let result = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        result.map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+// --- Optimize ---
+
+/// Size change from `optimize_pack_dir`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OptimizeReport {
+    /// Number of sound files re-encoded
+    pub files: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Negative when the pack grew (wav is larger than mp3/ogg)
+    pub bytes_saved: i64,
+}
+
+/// Re-encode every sound in the pack as 16-bit PCM WAV at 44.1kHz so all of
+/// them decode equally fast. References move to the new files and the old ones
+/// are removed; `original_names` is keyed by slot, so display names carry over
+/// unchanged. The new files are staged next to the old ones and swapped in
+/// together with pack.json; on any error the pack is left as it was.
+pub fn optimize_pack_dir(pack_dir: &Path) -> Result<OptimizeReport, String> {
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    let mut pack = SoundPack::load(pack_dir)?;
+    let mut paths: Vec<String> = pack.sound_paths().into_iter().map(String::from).collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut encoded = Vec::new();
+    let mut bytes_before = 0;
+    for path in &paths {
+        let data = std::fs::read(pack_dir.join(path))
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let sound = StaticSoundData::from_cursor(std::io::Cursor::new(data.clone()))
+            .map_err(|e| format!("{}: {}", path, describe_decode_error(&e, &data)))?;
+        bytes_before += data.len() as u64;
+        encoded.push((
+            path.clone(),
+            encode_wav(&resample(&sound, OPTIMIZE_SAMPLE_RATE)),
+        ));
+    }
+
+    // "a.mp3" -> "a.wav", unless that name belongs to another sound or file
+    let mut renames = BTreeMap::new();
+    for (path, _) in &encoded {
+        let new_path = if path.ends_with(".wav") {
+            path.clone()
+        } else {
+            let stem = path
+                .rsplit_once('.')
+                .map_or(path.as_str(), |(stem, _)| stem);
+            let mut candidate = format!("{}.wav", stem);
+            let mut n = 2;
+            while paths.contains(&candidate)
+                || renames.values().any(|p| p == &candidate)
+                || pack_dir.join(&candidate).exists()
+            {
+                candidate = format!("{}-{}.wav", stem, n);
+                n += 1;
+            }
+            candidate
+        };
+        renames.insert(path.clone(), new_path);
+    }
+
+    let staged = |path: &str| pack_dir.join(format!("{}.optimize-tmp", path));
+    let backup = |path: &str| pack_dir.join(format!("{}.optimize-bak", path));
+
+    // Stage every file before touching the pack
+    for (i, (path, wav)) in encoded.iter().enumerate() {
+        if let Err(e) = std::fs::write(staged(&renames[path]), wav) {
+            for (path, _) in &encoded[..=i] {
+                std::fs::remove_file(staged(&renames[path])).ok();
+            }
+            return Err(format!("Failed to write sound file: {}", e));
+        }
+    }
+
+    // Swap the staged files in, keeping any file they replace until pack.json is saved
+    let new_paths: Vec<&String> = renames.values().collect();
+    let mut swapped: Vec<(&String, bool)> = Vec::new();
+    let undo = |swapped: &[(&String, bool)]| {
+        for (path, replaced) in swapped {
+            std::fs::remove_file(pack_dir.join(path)).ok();
+            if *replaced {
+                std::fs::rename(backup(path), pack_dir.join(path)).ok();
+            }
+        }
+        for path in &new_paths {
+            std::fs::remove_file(staged(path)).ok();
+        }
+    };
+    for path in &new_paths {
+        let dst = pack_dir.join(path);
+        let replaced = dst.exists();
+        let result = if replaced {
+            std::fs::rename(&dst, backup(path))
+        } else {
+            Ok(())
+        }
+        .and_then(|()| std::fs::rename(staged(path), &dst));
+        if let Err(e) = result {
+            if replaced && !dst.exists() {
+                std::fs::rename(backup(path), &dst).ok();
+            }
+            undo(&swapped);
+            return Err(format!("Failed to replace sound file: {}", e));
+        }
+        swapped.push((path, replaced));
+    }
+
+    let rename = |p: &mut String| {
+        if let Some(new_path) = renames.get(p.as_str()) {
+            *p = new_path.clone();
+        }
+    };
+    rename(&mut pack.defaults.keydown);
+    pack.defaults.keyup.iter_mut().for_each(rename);
+    for ks in pack.key_overrides.values_mut() {
+        ks.keydown
+            .iter_mut()
+            .chain(ks.keyup.iter_mut())
+            .for_each(rename);
+    }
+    for cat in pack.category_overrides.values_mut() {
+        cat.keydown
+            .iter_mut()
+            .chain(cat.keyup.iter_mut())
+            .for_each(rename);
+    }
+    if let Err(e) = write_pack_json(&mut pack) {
+        undo(&swapped);
+        return Err(e);
+    }
+
+    for (path, replaced) in &swapped {
+        if *replaced {
+            std::fs::remove_file(backup(path)).ok();
+        }
+    }
+    for old_path in renames.keys() {
+        remove_if_unreferenced(&pack, old_path);
+    }
+
+    let bytes_after: u64 = encoded.iter().map(|(_, wav)| wav.len() as u64).sum();
+    Ok(OptimizeReport {
+        files: encoded.len(),
+        bytes_before,
+        bytes_after,
+        bytes_saved: bytes_before as i64 - bytes_after as i64,
+    })
+}
+
+// --- Diagnostics ---
+
+/// Health of one pack directory, for the "run diagnostics" report
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PackDiagnosis {
+    /// Pack id, or the directory name when pack.json couldn't be loaded
+    pub id: String,
+    pub path: PathBuf,
+    pub load_error: Option<String>,
+    pub issues: Vec<PackIssue>,
+    /// Why the default keydown sound can't be decoded, if it can't
+    pub default_sound_error: Option<String>,
+    /// No load error, no error-severity issue, and a decodable default sound.
+    /// Warnings alone don't make a pack unhealthy.
+    pub healthy: bool,
+}
+
+/// Load, validate, and decode-check the default sound of a single pack.
+pub fn diagnose_pack_dir(dir: &Path) -> PackDiagnosis {
+    let dir_name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let pack = match SoundPack::load(dir) {
+        Ok(pack) => pack,
+        Err(e) => {
+            return PackDiagnosis {
+                id: dir_name,
+                path: dir.to_path_buf(),
+                load_error: Some(e),
+                issues: Vec::new(),
+                default_sound_error: None,
+                healthy: false,
+            }
+        }
+    };
+
+    let issues = validate_pack(&pack);
+    let default_sound_error = std::fs::read(dir.join(&pack.defaults.keydown))
+        .map_err(|e| format!("Failed to read default sound: {}", e))
+        .and_then(|data| check_decodable(&data))
+        .err();
+    let healthy = default_sound_error.is_none()
+        && issues
+            .iter()
+            .all(|issue| issue.severity() != IssueSeverity::Error);
+
+    PackDiagnosis {
+        id: pack.id,
+        path: dir.to_path_buf(),
+        load_error: None,
+        issues,
+        default_sound_error,
+        healthy,
+    }
+}
+
+/// Diagnose every pack directory (anything containing a pack.json) under `roots`.
+pub fn diagnose_packs(roots: &[&Path]) -> Vec<PackDiagnosis> {
+    let mut dirs: Vec<PathBuf> = roots
+        .iter()
+        .filter_map(|root| std::fs::read_dir(root).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+        .filter(|path| path.join("pack.json").exists())
+        .collect();
+    dirs.sort();
+    dirs.iter().map(|dir| diagnose_pack_dir(dir)).collect()
+}
+
+/// Move a broken pack folder out of `user_dir` into its quarantine subfolder so
+/// discovery stops tripping over it. Returns the new location.
+pub fn quarantine_pack_dir(user_dir: &Path, pack_dir: &Path) -> Result<PathBuf, String> {
+    let name = pack_dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .filter(|n| *n != QUARANTINE_DIR)
+        .ok_or("Invalid pack folder")?;
+    if pack_dir.parent() != Some(user_dir) || !pack_dir.is_dir() {
+        return Err("Only folders in the custom packs folder can be quarantined".into());
+    }
+
+    let quarantine = user_dir.join(QUARANTINE_DIR);
+    std::fs::create_dir_all(&quarantine)
+        .map_err(|e| format!("Failed to create quarantine folder: {}", e))?;
+    let dest = quarantine.join(unique_id(name, &quarantine));
+    std::fs::rename(pack_dir, &dest).map_err(|e| format!("Failed to quarantine pack: {}", e))?;
+    log::info!("Quarantined {} to {}", pack_dir.display(), dest.display());
+    Ok(dest)
+}
+
+// --- Packs Folder Relocation ---
+
+/// Check that every file in `src` exists in `dst` with the same size.
+fn verify_copy(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+
+        if src_path.is_dir() {
+            verify_copy(&src_path, &dst_path)?;
+        } else if std::fs::metadata(&dst_path)?.len() != entry.metadata()?.len() {
+            return Err(std::io::Error::other(format!(
+                "{} differs from its copy",
+                src_path.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `path` with symlinks and `..` resolved. A path that doesn't exist yet is
+/// resolved through its nearest existing ancestor.
+fn resolve_path(path: &Path) -> PathBuf {
+    if let Ok(resolved) = path.canonicalize() {
+        return resolved;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => resolve_path(parent).join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Folder inside the new packs folder where packs are copied before they
+/// are renamed into place
+const RELOCATE_STAGING_DIR: &str = ".keysound-staging";
+
+/// User packs copied by `relocate_user_packs`. They exist in both folders
+/// until the move is finished or undone.
+#[must_use]
+#[derive(Debug, Default)]
+pub struct Relocation {
+    old: Vec<PathBuf>,
+    new: Vec<PathBuf>,
+}
+
+impl Relocation {
+    /// Remove the old copies, once the new folder is the one in use.
+    pub fn finish(self) {
+        // Every pack is in the new folder; a leftover old copy is only clutter
+        for old in self.old {
+            if let Err(e) = delete_pack_dir(&old) {
+                log::warn!("Failed to remove old copy of {}: {}", old.display(), e);
+            }
+        }
+    }
+
+    /// Remove the new copies, leaving the packs where they were.
+    pub fn undo(self) {
+        for new in self.new {
+            std::fs::remove_dir_all(new).ok();
+        }
+    }
+}
+
+/// Copy all user packs from `old_dir` to `new_dir`.
+/// Every pack is copied into a staging folder and verified, then all are
+/// renamed into place. A failure leaves `old_dir` as it was and nothing behind
+/// in `new_dir`. The old copies stay until the returned `Relocation` is finished.
+pub fn relocate_user_packs(old_dir: &Path, new_dir: &Path) -> Result<Relocation, String> {
+    if !new_dir.is_absolute() {
+        return Err("Packs folder must be an absolute path".into());
+    }
+    let (old_real, new_real) = (resolve_path(old_dir), resolve_path(new_dir));
+    if new_real == old_real {
+        return Err("Packs folder is already at this location".into());
+    }
+    if new_real.starts_with(&old_real) {
+        return Err("New packs folder cannot be inside the current one".into());
+    }
+
+    std::fs::create_dir_all(new_dir)
+        .map_err(|e| format!("Failed to create packs folder: {}", e))?;
+
+    // Make sure the target is writable before moving anything
+    let probe = new_dir.join(".keysound-write-test");
+    std::fs::write(&probe, b"").map_err(|e| format!("Packs folder is not writable: {}", e))?;
+    std::fs::remove_file(&probe).ok();
+
+    let entries: Vec<_> = match std::fs::read_dir(old_dir) {
+        Ok(entries) => entries.flatten().filter(|e| e.path().is_dir()).collect(),
+        Err(_) => return Ok(Relocation::default()), // Nothing to migrate
+    };
+
+    if let Some(conflict) = entries
+        .iter()
+        .find(|e| new_dir.join(e.file_name()).exists())
+    {
+        return Err(format!(
+            "A pack named '{}' already exists in the new folder",
+            conflict.file_name().to_string_lossy()
+        ));
+    }
+
+    let staging = new_dir.join(RELOCATE_STAGING_DIR);
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)
+            .map_err(|e| format!("Failed to clear staging folder: {}", e))?;
+    }
+    for entry in &entries {
+        let src = entry.path();
+        let staged = staging.join(entry.file_name());
+        if let Err(e) = copy_dir_recursive(&src, &staged).and_then(|_| verify_copy(&src, &staged)) {
+            std::fs::remove_dir_all(&staging).ok();
+            return Err(format!("Failed to move pack {}: {}", src.display(), e));
+        }
+    }
+
+    let mut placed = Vec::new();
+    for entry in &entries {
+        let dst = new_dir.join(entry.file_name());
+        if let Err(e) = std::fs::rename(staging.join(entry.file_name()), &dst) {
+            for dst in placed {
+                std::fs::remove_dir_all(dst).ok();
+            }
+            std::fs::remove_dir_all(&staging).ok();
+            return Err(format!(
+                "Failed to move pack {}: {}",
+                entry.path().display(),
+                e
+            ));
+        }
+        placed.push(dst);
+    }
+    std::fs::remove_dir_all(&staging).ok();
+
+    Ok(Relocation {
+        old: entries.iter().map(|entry| entry.path()).collect(),
+        new: placed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sound_pack::{
+        discover_all_packs, find_unloadable_packs, group_duplicate_packs, MAX_VOLUME_JITTER,
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_test_pack_dir(dir: &Path, id: &str, source: Option<&str>) {
+        let pack_dir = dir.join(id);
+        let sounds_dir = pack_dir.join("sounds");
+        fs::create_dir_all(&sounds_dir).unwrap();
+
+        generate_silence_wav(&sounds_dir.join("keydown.wav"), 1).unwrap();
+
+        let mut manifest = serde_json::json!({
+            "id": id,
+            "name": id.to_uppercase(),
+            "author": "Test",
+            "version": "1.0.0",
+            "description": "",
+            "defaults": { "keydown": "sounds/keydown.wav", "volume": 0.8 }
+        });
+        if let Some(src) = source {
+            manifest["source"] = serde_json::json!(src);
+        }
+        fs::write(
+            pack_dir.join("pack.json"),
+            serde_json::to_string_pretty(&manifest).unwrap(),
+        )
+        .unwrap();
+    }
+
+    /// Write a small decodable audio file (the extension doesn't need to match the content).
+    fn write_test_audio(path: &Path) {
+        generate_silence_wav(path, 1).unwrap();
+    }
+
+    // --- slugify ---
+
+    #[test]
+    fn test_slugify_basic() {
+        assert_eq!(slugify("My Custom Pack"), "my-custom-pack");
+    }
+
+    #[test]
+    fn test_slugify_special_chars() {
+        assert_eq!(slugify("Hello! @World# 123"), "hello-world-123");
+    }
+
+    #[test]
+    fn test_slugify_already_clean() {
+        assert_eq!(slugify("clean"), "clean");
+    }
+
+    #[test]
+    fn test_slugify_leading_trailing_spaces() {
+        assert_eq!(slugify("  spaced  "), "spaced");
+    }
+
+    // --- unique_id ---
+
+    #[test]
+    fn test_unique_id_no_collision() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(unique_id("my-pack", dir.path()), "my-pack");
+    }
+
+    #[test]
+    fn test_unique_id_with_collision() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("my-pack")).unwrap();
+        assert_eq!(unique_id("my-pack", dir.path()), "my-pack-2");
+    }
+
+    #[test]
+    fn test_long_pack_name_gets_bounded_id() {
+        let dir = TempDir::new().unwrap();
+        let long_name = "lorem ipsum dolor ".repeat(28);
+        assert!(long_name.len() > 500);
+
+        let first = create_custom_pack_dir(dir.path(), dir.path(), &long_name).unwrap();
+        assert!(first.id.chars().count() <= MAX_SLUG_LEN);
+        assert!(!first.id.ends_with('-'));
+        assert_eq!(first.name.chars().count(), MAX_PACK_NAME_LEN);
+
+        // Suffixing still works on the truncated slug
+        let second = create_custom_pack_dir(dir.path(), dir.path(), &long_name).unwrap();
+        assert_eq!(second.id, format!("{}-2", first.id));
+
+        let renamed = rename_pack(&first.base_path, &"x".repeat(500)).unwrap();
+        assert_eq!(renamed.name.chars().count(), MAX_PACK_NAME_LEN);
+    }
+
+    #[test]
+    fn test_unique_id_multiple_collisions() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("my-pack")).unwrap();
+        fs::create_dir(dir.path().join("my-pack-2")).unwrap();
+        fs::create_dir(dir.path().join("my-pack-3")).unwrap();
+        assert_eq!(unique_id("my-pack", dir.path()), "my-pack-4");
+    }
+
+    #[test]
+    fn test_create_pack_skips_stray_file_with_same_name() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        fs::write(user_dir.join("my-pack"), b"stray").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("gone"), user_dir.join("my-pack-2")).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, dir.path(), "My Pack").unwrap();
+        if cfg!(unix) {
+            assert_eq!(pack.id, "my-pack-3");
+        } else {
+            assert_eq!(pack.id, "my-pack-2");
+        }
+        assert!(user_dir.join(&pack.id).join("pack.json").exists());
+        // The stray file is left alone
+        assert_eq!(fs::read(user_dir.join("my-pack")).unwrap(), b"stray");
+    }
+
+    #[test]
+    fn test_created_packs_are_grouped_as_duplicates() {
+        let dir = TempDir::new().unwrap();
+        let bundled = dir.path().join("soundpacks");
+        let user = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&bundled).unwrap();
+        let first = create_custom_pack_dir(&user, dir.path(), "First").unwrap();
+        let second = create_custom_pack_dir(&user, dir.path(), "Second").unwrap();
+        // Saved later and with checksums, as an edited pack would be
+        let mut pack = SoundPack::load(&second.base_path).unwrap();
+        pack.created_at = Some(1);
+        write_pack_json(&mut pack).unwrap();
+        write_pack_checksums_dir(&second.base_path).unwrap();
+
+        let dupes = group_duplicate_packs(&bundled, &user);
+        assert_eq!(dupes, vec![vec![first.id, second.id]]);
+    }
+
+    #[test]
+    fn test_unique_id_skips_reserved() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(unique_id("default", dir.path()), "default-2");
+        assert_eq!(unique_id("data-version", dir.path()), "data-version-2");
+    }
+
+    #[test]
+    fn test_create_custom_pack_reserved_name_gets_safe_id() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user");
+        fs::create_dir_all(&user_dir).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, dir.path(), "Data Version").unwrap();
+        assert_eq!(pack.id, "data-version-2");
+        assert_eq!(pack.name, "Data Version");
+        let pack = create_custom_pack_dir(&user_dir, dir.path(), "DEFAULT").unwrap();
+        assert_eq!(pack.id, "default-2");
+    }
+
+    #[test]
+    fn test_rename_pack_allows_reserved_word_as_name() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+
+        let pack = rename_pack(&pack_dir, "Default").unwrap();
+        assert_eq!(pack.name, "Default");
+        assert_eq!(pack.id, "p");
+        assert!(rename_pack(&pack_dir, "   ").unwrap_err().contains("empty"));
+    }
+
+    // --- generate_silence_wav ---
+
+    #[test]
+    fn test_generate_silence_wav_creates_valid_wav() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("silence.wav");
+        generate_silence_wav(&path, 1).unwrap();
+
+        assert!(path.exists());
+        let data = fs::read(&path).unwrap();
+        assert_eq!(&data[0..4], b"RIFF");
+        assert_eq!(&data[8..12], b"WAVE");
+        assert_eq!(&data[12..16], b"fmt ");
+        // 44 byte header + 882 bytes data = 926 bytes
+        assert_eq!(data.len(), 926);
+    }
+
+    // --- audio_info ---
+
+    #[test]
+    fn test_audio_info_of_generated_silence() {
+        let dir = TempDir::new().unwrap();
+        let mono = dir.path().join("mono.wav");
+        generate_silence_wav(&mono, 1).unwrap();
+        let expected = AudioInfo {
+            sample_rate: 44100,
+            channels: 1,
+            duration_ms: 10,
+        };
+        assert_eq!(audio_info(&mono).unwrap(), expected);
+
+        let stereo = dir.path().join("stereo.wav");
+        generate_silence_wav(&stereo, 2).unwrap();
+        assert_eq!(
+            audio_info(&stereo).unwrap(),
+            AudioInfo {
+                channels: 2,
+                ..expected
+            }
+        );
+        // The generated stereo file still decodes
+        assert!(StaticSoundData::from_file(&stereo).is_ok());
+    }
+
+    #[test]
+    fn test_audio_info_rejects_non_audio() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.wav");
+        fs::write(&path, b"not audio at all").unwrap();
+        assert!(audio_info(&path).is_err());
+        assert!(audio_info(&dir.path().join("missing.wav")).is_err());
+    }
+
+    #[test]
+    fn test_slot_durations() {
+        let dir = TempDir::new().unwrap();
+        let pack = create_custom_pack_dir(dir.path(), dir.path(), "Lengths").unwrap();
+        // 4410 mono samples at 44.1kHz = 100ms
+        let mut wav = wav_header(44100, 1, 4410 * 2);
+        wav.resize(wav.len() + 4410 * 2, 0);
+        write_sound_to_slot(&pack.base_path, "space", "wav", &wav, None).unwrap();
+        let pack = write_sound_to_slot(&pack.base_path, "key:KeyQ", "wav", &wav, None).unwrap();
+        let key_q = get_slot_path(&pack, "key:KeyQ").unwrap();
+        fs::remove_file(pack.base_path.join(key_q)).unwrap();
+
+        let durations = slot_durations(&pack);
+        assert_eq!(durations["space"].duration_ms, 100);
+        assert!(durations["space"].readable);
+        assert_eq!(durations["default"].duration_ms, 10);
+        assert_eq!(
+            durations["key:KeyQ"],
+            SlotDuration {
+                duration_ms: 0,
+                readable: false
+            }
+        );
+        // Unset slots aren't listed
+        assert!(!durations.contains_key("enter"));
+    }
+
+    #[test]
+    fn test_change_pack_id_moves_folder() {
+        let dir = TempDir::new().unwrap();
+        let bundled = dir.path().join("bundled");
+        create_test_pack_dir(&bundled, "classic", None);
+        let user = dir.path().join("user");
+        let pack = create_custom_pack_dir(&user, dir.path(), "Draft").unwrap();
+        create_custom_pack_dir(&user, dir.path(), "Taken").unwrap();
+
+        let pack = change_pack_id_dir(&user, &bundled, &pack.id, "Final Mix").unwrap();
+        assert_eq!(pack.id, "final-mix");
+        assert_eq!(pack.name, "Final Mix");
+        assert!(!user.join("draft").exists());
+        let on_disk = SoundPack::load(&user.join("final-mix")).unwrap();
+        assert_eq!(on_disk.id, "final-mix");
+
+        // Existing user ids get a suffix, bundled ids are refused
+        let pack = change_pack_id_dir(&user, &bundled, "final-mix", "Taken").unwrap();
+        assert_eq!(pack.id, "taken-2");
+        let err = change_pack_id_dir(&user, &bundled, "taken-2", "Classic").unwrap_err();
+        assert!(err.contains("bundled"), "{}", err);
+        assert!(user.join("taken-2").join("pack.json").exists());
+    }
+
+    #[test]
+    fn test_pack_checksums_detect_changes() {
+        let dir = TempDir::new().unwrap();
+        let pack = create_synth_pack_dir(dir.path(), dir.path(), "Sealed", SynthParams::default())
+            .unwrap();
+        let pack = write_pack_checksums_dir(&pack.base_path).unwrap();
+        assert_eq!(pack.checksums.len(), pack.sound_paths().len());
+
+        let pack = SoundPack::load(&pack.base_path).unwrap();
+        assert!(find_checksum_mismatches(&pack).is_empty());
+
+        let space = get_slot_path(&pack, "space").unwrap();
+        assert_eq!(
+            pack.checksums[&space],
+            hash_file(&pack.base_path.join(&space)).unwrap()
+        );
+        assert_eq!(pack.checksums[&space].len(), 64);
+        fs::write(pack.base_path.join(&space), b"tampered").unwrap();
+        assert_eq!(find_checksum_mismatches(&pack), vec![space]);
+    }
+
+    #[test]
+    fn test_slot_edits_keep_checksums_current() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        write_pack_checksums_dir(&pack_dir).unwrap();
+        let src = dir.path().join("click.wav");
+        write_test_audio(&src);
+
+        let pack = import_sound_to_pack(&pack_dir, "space", &src).unwrap();
+        let space = get_slot_path(&pack, "space").unwrap();
+        assert!(pack.checksums.contains_key(&space));
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert!(find_checksum_mismatches(&pack).is_empty());
+
+        let (pack, _) = remove_slots_from_pack(&pack_dir, &["space".into()], dir.path()).unwrap();
+        assert!(!pack.checksums.contains_key(&space));
+        assert!(find_checksum_mismatches(&SoundPack::load(&pack_dir).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_legacy_checksums_are_not_mismatches() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+        pack.checksums
+            .insert(pack.defaults.keydown.clone(), "0123456789abcdef".into());
+        assert!(find_checksum_mismatches(&pack).is_empty());
+
+        // The next save replaces it with a current checksum
+        write_pack_json(&mut pack).unwrap();
+        assert!(is_current_checksum(&pack.checksums[&pack.defaults.keydown]));
+    }
+
+    #[test]
+    fn test_apply_slot_trigger() {
+        let dir = TempDir::new().unwrap();
+        let pack = create_custom_pack_dir(dir.path(), dir.path(), "Thunk").unwrap();
+
+        let pack = apply_slot_trigger(&pack.base_path, "default", TriggerMode::Release).unwrap();
+        let slots = get_all_slots(&SoundPack::load(&pack.base_path).unwrap());
+        assert_eq!(slots[0].slot, "default");
+        assert_eq!(slots[0].trigger, TriggerMode::Release);
+        assert_eq!(slots[1].trigger, TriggerMode::Press);
+
+        // Back to the default drops the entry
+        let pack = apply_slot_trigger(&pack.base_path, "default", TriggerMode::Press).unwrap();
+        assert!(pack.slot_triggers.is_empty());
+        assert!(apply_slot_trigger(&pack.base_path, "modifier_up", TriggerMode::Both).is_err());
+        assert!(apply_slot_trigger(&pack.base_path, "key:KeyZ", TriggerMode::Both).is_err());
+    }
+
+    #[test]
+    fn test_apply_slot_delay_clamps() {
+        let dir = TempDir::new().unwrap();
+        let pack = create_custom_pack_dir(dir.path(), dir.path(), "Rhythm").unwrap();
+
+        let pack = apply_slot_delay(&pack.base_path, "key:KeyJ", 1000).unwrap();
+        assert_eq!(pack.resolve_delay_ms("KeyJ"), MAX_KEY_DELAY_MS);
+        let pack = apply_slot_delay(&pack.base_path, "space", 30).unwrap();
+        assert_eq!(pack.resolve_delay_ms("Space"), 30);
+        assert_eq!(
+            SoundPack::load(&pack.base_path)
+                .unwrap()
+                .resolve_delay_ms("Space"),
+            30
+        );
+
+        // 0 clears the delay, and with it an override that only held the delay
+        let pack = apply_slot_delay(&pack.base_path, "key:KeyJ", 0).unwrap();
+        assert_eq!(pack.resolve_delay_ms("KeyJ"), 0);
+        assert!(!pack.key_overrides.contains_key("KeyJ"));
+        assert!(pack.noop_overrides().is_empty());
+        // An override with its own sound keeps it
+        let mut pack = pack;
+        apply_slot_to_pack(&mut pack, "space", Some("sounds/keydown.wav".into()));
+        write_pack_json(&mut pack).unwrap();
+        let pack = apply_slot_delay(&pack.base_path, "space", 0).unwrap();
+        assert!(pack.key_overrides["Space"].keydown.is_some());
+        assert!(apply_slot_delay(&pack.base_path, "modifier", 10).is_err());
+    }
+
+    // --- data versioning ---
+
+    #[test]
+    fn test_ensure_data_version_creates_file() {
+        let dir = TempDir::new().unwrap();
+        ensure_data_version(dir.path());
+
+        let version_file = dir.path().join("data-version.json");
+        assert!(version_file.exists());
+
+        let contents = fs::read_to_string(&version_file).unwrap();
+        let v: DataVersion = serde_json::from_str(&contents).unwrap();
+        assert_eq!(v.version, DATA_VERSION);
+    }
+
+    #[test]
+    fn test_ensure_data_version_idempotent() {
+        let dir = TempDir::new().unwrap();
+        ensure_data_version(dir.path());
+        ensure_data_version(dir.path());
+
+        let version_file = dir.path().join("data-version.json");
+        let contents = fs::read_to_string(&version_file).unwrap();
+        let v: DataVersion = serde_json::from_str(&contents).unwrap();
+        assert_eq!(v.version, DATA_VERSION);
+    }
+
+    // --- write_pack_json / SoundPack round-trip ---
+
+    #[test]
+    fn test_write_pack_json_is_deterministic() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        for key in ["KeyZ", "KeyA", "Digit5", "KeyM", "Comma"] {
+            apply_slot_to_pack(
+                &mut pack,
+                &format!("key:{}", key),
+                Some("sounds/keydown.wav".into()),
+            );
+            pack.original_names
+                .insert(format!("key:{}", key), format!("{}.wav", key));
+        }
+        apply_slot_to_pack(&mut pack, "modifier", Some("sounds/keydown.wav".into()));
+        apply_slot_to_pack(&mut pack, "backspace", Some("sounds/keydown.wav".into()));
+
+        write_pack_json(&mut pack).unwrap();
+        let first = fs::read(pack_dir.join("pack.json")).unwrap();
+        let mut reloaded = SoundPack::load(&pack_dir).unwrap();
+        write_pack_json(&mut reloaded).unwrap();
+        let second = fs::read(pack_dir.join("pack.json")).unwrap();
+        assert_eq!(first, second);
+
+        let json = String::from_utf8(first).unwrap();
+        assert!(json.find("\"Comma\"").unwrap() < json.find("\"KeyA\"").unwrap());
+        assert!(json.find("\"KeyA\"").unwrap() < json.find("\"KeyZ\"").unwrap());
+    }
+
+    #[test]
+    fn test_write_and_load_pack_json() {
+        let dir = TempDir::new().unwrap();
+        let pack_dir = dir.path().join("test-pack");
+        fs::create_dir_all(pack_dir.join("sounds")).unwrap();
+
+        let mut pack = SoundPack {
+            manifest_version: MANIFEST_VERSION,
+            id: "test-pack".into(),
+            name: "Test Pack".into(),
+            author: "Tester".into(),
+            version: "1.0.0".into(),
+            description: "A test".into(),
+            notes: String::new(),
+            source: Some("user".into()),
+            color: None,
+            keyup_enabled: true,
+            defaults: SoundDefaults {
+                keydown: "sounds/keydown.wav".into(),
+                keyup: None,
+                volume: 0.8,
+                volume_jitter: 0.0,
+            },
+            key_overrides: Default::default(),
+            category_overrides: Default::default(),
+            original_names: Default::default(),
+            slot_labels: Default::default(),
+            slot_triggers: Default::default(),
+            checksums: Default::default(),
+            created_at: None,
+            modified_at: None,
+            base_path: pack_dir.clone(),
+            sideloaded: false,
+        };
+
+        write_pack_json(&mut pack).unwrap();
+        assert!(pack_dir.join("pack.json").exists());
+
+        let loaded = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(loaded.id, "test-pack");
+        assert_eq!(loaded.name, "Test Pack");
+        assert_eq!(loaded.source, Some("user".into()));
+    }
+
+    // --- apply_slot_to_pack ---
+
+    #[test]
+    fn test_apply_slot_default() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        apply_slot_to_pack(&mut pack, "default", Some("sounds/new.mp3".into()));
+        assert_eq!(pack.defaults.keydown, "sounds/new.mp3");
+    }
+
+    #[test]
+    fn test_apply_slot_space() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        apply_slot_to_pack(&mut pack, "space", Some("sounds/space.mp3".into()));
+        assert!(pack.key_overrides.contains_key("Space"));
+        assert_eq!(
+            pack.key_overrides["Space"].keydown.as_deref(),
+            Some("sounds/space.mp3")
+        );
+
+        apply_slot_to_pack(&mut pack, "space", None);
+        assert!(!pack.key_overrides.contains_key("Space"));
+    }
+
+    #[test]
+    fn test_apply_slot_enter() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        apply_slot_to_pack(&mut pack, "enter", Some("sounds/enter.ogg".into()));
+        assert_eq!(
+            pack.key_overrides["Return"].keydown.as_deref(),
+            Some("sounds/enter.ogg")
+        );
+    }
+
+    #[test]
+    fn test_apply_slot_modifier() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        apply_slot_to_pack(&mut pack, "modifier", Some("sounds/mod.wav".into()));
+        let cat = &pack.category_overrides["modifiers"];
+        assert_eq!(cat.keydown.as_deref(), Some("sounds/mod.wav"));
+        assert!(cat.keys.contains(&"ShiftLeft".to_string()));
+
+        apply_slot_to_pack(&mut pack, "modifier", None);
+        assert!(!pack.category_overrides.contains_key("modifiers"));
+    }
+
+    #[test]
+    fn test_apply_slot_modifier_keyup() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        apply_slot_to_pack(&mut pack, "modifier", Some("sounds/mod.wav".into()));
+        apply_slot_to_pack(&mut pack, "modifier_up", Some("sounds/mod-up.wav".into()));
+        let cat = &pack.category_overrides["modifiers"];
+        assert_eq!(cat.keydown.as_deref(), Some("sounds/mod.wav"));
+        assert_eq!(cat.keyup.as_deref(), Some("sounds/mod-up.wav"));
+        assert_eq!(
+            get_slot_path(&pack, "modifier_up").as_deref(),
+            Some("sounds/mod-up.wav")
+        );
+        assert_eq!(
+            pack.resolve_keyup("ShiftLeft"),
+            Some(pack.base_path.join("sounds/mod-up.wav"))
+        );
+
+        // Removing the keydown sound keeps the keyup channel
+        apply_slot_to_pack(&mut pack, "modifier", None);
+        let cat = &pack.category_overrides["modifiers"];
+        assert!(cat.keydown.is_none());
+        assert_eq!(cat.keyup.as_deref(), Some("sounds/mod-up.wav"));
+
+        apply_slot_to_pack(&mut pack, "modifier_up", None);
+        assert!(!pack.category_overrides.contains_key("modifiers"));
+    }
+
+    #[test]
+    fn test_apply_slot_backspace() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        apply_slot_to_pack(&mut pack, "backspace", Some("sounds/bs.mp3".into()));
+        let cat = &pack.category_overrides["delete"];
+        assert_eq!(cat.keydown.as_deref(), Some("sounds/bs.mp3"));
+        assert!(cat.keys.contains(&"Backspace".to_string()));
+    }
+
+    // --- get_slot_path ---
+
+    #[test]
+    fn test_get_slot_path_default() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        assert_eq!(
+            get_slot_path(&pack, "default"),
+            Some("sounds/keydown.wav".into())
+        );
+    }
+
+    #[test]
+    fn test_get_slot_path_empty_slot() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        assert_eq!(get_slot_path(&pack, "space"), None);
+    }
+
+    // --- get_all_slots ---
+
+    #[test]
+    fn test_get_all_slots_fresh_pack() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        let slots = get_all_slots(&pack);
+        assert_eq!(slots.len(), 6);
+        assert_eq!(slots[0].slot, "default");
+        // Default slot with no original_names entry shows as None (silence placeholder)
+        assert!(slots[0].file_name.is_none());
+        assert_eq!(slots[1].slot, "space");
+        assert!(slots[1].file_name.is_none());
+    }
+
+    #[test]
+    fn test_get_all_slots_with_original_name() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        pack.original_names
+            .insert("default".into(), "my-cool-sound.mp3".into());
+        pack.original_names
+            .insert("space".into(), "spacebar.wav".into());
+        apply_slot_to_pack(&mut pack, "space", Some("sounds/keydown-space.wav".into()));
+
+        let slots = get_all_slots(&pack);
+        assert_eq!(slots[0].file_name.as_deref(), Some("my-cool-sound.mp3"));
+        assert_eq!(slots[1].file_name.as_deref(), Some("spacebar.wav"));
+    }
+
+    // --- copy_dir_recursive ---
+
+    #[test]
+    fn test_copy_dir_recursive() {
+        let src = TempDir::new().unwrap();
+        let dst = TempDir::new().unwrap();
+
+        fs::write(src.path().join("a.txt"), "hello").unwrap();
+        fs::create_dir(src.path().join("sub")).unwrap();
+        fs::write(src.path().join("sub").join("b.txt"), "world").unwrap();
+
+        let dst_dir = dst.path().join("out");
+        copy_dir_recursive(src.path(), &dst_dir).unwrap();
+
+        assert_eq!(fs::read_to_string(dst_dir.join("a.txt")).unwrap(), "hello");
+        assert_eq!(
+            fs::read_to_string(dst_dir.join("sub").join("b.txt")).unwrap(),
+            "world"
+        );
+    }
+
+    // --- discover_all_packs ordering ---
+
+    #[test]
+    fn test_discover_all_packs_custom_before_bundled() {
+        let bundled = TempDir::new().unwrap();
+        let user = TempDir::new().unwrap();
+
+        create_test_pack_dir(bundled.path(), "default", None);
+        create_test_pack_dir(bundled.path(), "alpha", None);
+        create_test_pack_dir(bundled.path(), "beta", None);
+        create_test_pack_dir(user.path(), "custom-a", Some("user"));
+        create_test_pack_dir(user.path(), "custom-b", Some("user"));
+
+        let all = discover_all_packs(bundled.path(), user.path());
+
+        assert_eq!(all.len(), 5);
+        // Order: default, custom-a, custom-b, alpha, beta
+        assert_eq!(all[0].id, "default");
+        assert_eq!(all[1].id, "custom-a");
+        assert_eq!(all[2].id, "custom-b");
+        assert_eq!(all[3].id, "alpha");
+        assert_eq!(all[4].id, "beta");
+    }
+
+    // --- Full lifecycle: create, import, remove slot, delete ---
+
+    #[test]
+    fn test_create_custom_pack() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+
+        // resource_dir won't have silence.wav, so it falls back to generation
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "My Sound").unwrap();
+        assert_eq!(pack.id, "my-sound");
+        assert_eq!(pack.manifest_version, MANIFEST_VERSION);
+        assert_eq!(pack.name, "My Sound");
+        assert_eq!(pack.source, Some("user".into()));
+        assert!(pack.base_path.join("pack.json").exists());
+        assert!(pack.base_path.join("sounds").join("keydown.wav").exists());
+    }
+
+    #[test]
+    fn test_pack_timestamps() {
+        let dir = TempDir::new().unwrap();
+        let pack = create_custom_pack_dir(dir.path(), dir.path(), "Dated").unwrap();
+        let created = pack.created_at.unwrap();
+        assert_eq!(pack.modified_at, Some(created));
+
+        // Writes refresh modified_at and keep created_at
+        let mut stale = SoundPack {
+            modified_at: Some(0),
+            ..pack
+        };
+        write_pack_json(&mut stale).unwrap();
+        let reloaded = SoundPack::load(&stale.base_path).unwrap();
+        assert_eq!(reloaded.created_at, Some(created));
+        assert!(reloaded.modified_at.unwrap() >= created);
+        // The caller's pack carries the stamp that was written
+        assert_eq!(stale.modified_at, reloaded.modified_at);
+    }
+
+    #[test]
+    fn test_create_synth_pack_has_audible_default() {
+        let dir = TempDir::new().unwrap();
+        let pack =
+            create_synth_pack_dir(dir.path(), dir.path(), "Synth", SynthParams::default()).unwrap();
+        assert_eq!(pack.id, "synth");
+
+        let pack = SoundPack::load(&pack.base_path).unwrap();
+        let default = StaticSoundData::from_file(pack.resolve_keydown("KeyA").unwrap()).unwrap();
+        assert!(default.frames.iter().any(|f| f.left.abs() > 0.1));
+        // Every synth slot got its own sound
+        for slot in ["default", "space", "enter", "modifier", "backspace"] {
+            let path = get_slot_path(&pack, slot).unwrap();
+            assert!(pack.base_path.join(path).is_file(), "{}", slot);
+        }
+        assert_ne!(pack.resolve_keydown("Space"), pack.resolve_keydown("KeyA"));
+
+        let err = create_synth_pack_dir(
+            dir.path(),
+            dir.path(),
+            "Shrill",
+            SynthParams {
+                frequency: 20000.0,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(err.contains("Frequency"));
+        assert!(!dir.path().join("shrill").exists());
+    }
+
+    #[test]
+    fn test_render_pack_demo_writes_playable_wav() {
+        let dir = TempDir::new().unwrap();
+        let pack =
+            create_synth_pack_dir(dir.path(), dir.path(), "Demo", SynthParams::default()).unwrap();
+        let dst = dir.path().join("demo.wav");
+        render_pack_demo_file(&pack, &dst).unwrap();
+
+        let info = audio_info(&dst).unwrap();
+        assert_eq!((info.sample_rate, info.channels), (44100, 2));
+        // 43 characters at 110-150ms each
+        assert!(info.duration_ms > 4000, "{:?}", info);
+        let demo = StaticSoundData::from_file(&dst).unwrap();
+        assert!(demo.frames.iter().any(|f| f.left.abs() > 0.1));
+    }
+
+    #[test]
+    fn test_create_custom_pack_collision() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        let p1 = create_custom_pack_dir(&user_dir, &resource_dir, "Same Name").unwrap();
+        let p2 = create_custom_pack_dir(&user_dir, &resource_dir, "Same Name").unwrap();
+        assert_eq!(p1.id, "same-name");
+        assert_eq!(p2.id, "same-name-2");
+    }
+
+    #[test]
+    fn test_create_custom_pack_empty_name() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+
+        let result = create_custom_pack_dir(&user_dir, &resource_dir, "  ");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_import_sound_file() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+
+        let fake_audio = dir.path().join("my-space-sound.mp3");
+        write_test_audio(&fake_audio);
+
+        let pack = import_sound_to_pack(&pack.base_path, "space", &fake_audio).unwrap();
+        assert!(pack.key_overrides.contains_key("Space"));
+        assert_eq!(
+            pack.original_names.get("space").map(|s| s.as_str()),
+            Some("my-space-sound.mp3")
+        );
+        assert!(pack.base_path.join("sounds").join("keydown-space.mp3").exists());
+    }
+
+    #[test]
+    fn test_import_rejects_unsupported_format() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+
+        let bad_file = dir.path().join("sound.txt");
+        fs::write(&bad_file, b"not audio").unwrap();
+
+        let result = import_sound_to_pack(&pack.base_path, "space", &bad_file);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Unsupported format"));
+    }
+
+    #[test]
+    fn test_import_replaces_old_file_different_extension() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+
+        // Import a .wav file for space
+        let wav_file = dir.path().join("space.wav");
+        write_test_audio(&wav_file);
+        import_sound_to_pack(&pack.base_path, "space", &wav_file).unwrap();
+        assert!(pack
+            .base_path
+            .join("sounds")
+            .join("keydown-space.wav")
+            .exists());
+
+        // Import a .mp3 file for the same slot — old .wav should be deleted
+        let mp3_file = dir.path().join("space.mp3");
+        write_test_audio(&mp3_file);
+        import_sound_to_pack(&pack.base_path, "space", &mp3_file).unwrap();
+
+        assert!(!pack.base_path.join("sounds").join("keydown-space.wav").exists());
+        assert!(pack.base_path.join("sounds").join("keydown-space.mp3").exists());
+    }
+
+    #[test]
+    fn test_remove_slot() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+
+        // Import space sound
+        let audio = dir.path().join("space.mp3");
+        write_test_audio(&audio);
+        import_sound_to_pack(&pack.base_path, "space", &audio).unwrap();
+
+        // Remove it
+        let pack = remove_slot_from_pack(&pack.base_path, "space", &resource_dir).unwrap();
+        assert!(!pack.key_overrides.contains_key("Space"));
+        assert!(!pack.original_names.contains_key("space"));
+        assert!(!pack.base_path.join("sounds").join("keydown-space.mp3").exists());
+    }
+
+    #[test]
+    fn test_remove_default_slot_resets_to_silence() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+
+        // Import a custom default sound
+        let audio = dir.path().join("keydown.mp3");
+        write_test_audio(&audio);
+        import_sound_to_pack(&pack.base_path, "default", &audio).unwrap();
+
+        // Remove default — should reset to silence
+        let pack = remove_slot_from_pack(&pack.base_path, "default", &resource_dir).unwrap();
+        assert_eq!(pack.defaults.keydown, "sounds/keydown.wav");
+        assert!(!pack.original_names.contains_key("default"));
+        // silence.wav should exist as keydown.wav
+        assert!(pack.base_path.join("sounds").join("keydown.wav").exists());
+    }
+
+    #[test]
+    fn test_remove_stereo_default_resets_to_stereo_silence() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        // The bundled silence.wav is mono
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(resource_dir.join("resources")).unwrap();
+        generate_silence_wav(&resource_dir.join("resources").join("silence.wav"), 1).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+        let audio = dir.path().join("stereo.wav");
+        generate_silence_wav(&audio, 2).unwrap();
+        import_sound_to_pack(&pack.base_path, "default", &audio).unwrap();
+
+        let pack = remove_slot_from_pack(&pack.base_path, "default", &resource_dir).unwrap();
+        let silence = audio_info(&pack.base_path.join(&pack.defaults.keydown)).unwrap();
+        assert_eq!(silence.channels, 2);
+    }
+
+    #[test]
+    fn test_delete_pack_removes_all_files() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+
+        // Import files
+        let audio1 = dir.path().join("a.mp3");
+        let audio2 = dir.path().join("b.wav");
+        write_test_audio(&audio1);
+        write_test_audio(&audio2);
+        import_sound_to_pack(&pack.base_path, "space", &audio1).unwrap();
+        import_sound_to_pack(&pack.base_path, "enter", &audio2).unwrap();
+
+        let pack_dir = pack.base_path.clone();
+        delete_pack_dir(&pack_dir).unwrap();
+        assert!(!pack_dir.exists());
+    }
+
+    // --- Trash ---
+
+    #[test]
+    fn test_trash_and_restore_pack() {
+        let dir = TempDir::new().unwrap();
+        let user = dir.path().join("user-soundpacks");
+        let trash = dir.path().join(TRASH_DIR);
+        let pack = create_custom_pack_dir(&user, dir.path(), "Keeper").unwrap();
+
+        let trash_id = trash_pack_dir(&pack.base_path, &trash).unwrap();
+        assert!(!pack.base_path.exists());
+        let trashed = trashed_packs(&trash);
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].trash_id, trash_id);
+        assert_eq!(trashed[0].name, "Keeper");
+
+        // The id was reused meanwhile, so the restored pack gets a new one
+        create_custom_pack_dir(&user, dir.path(), "Keeper").unwrap();
+        let restored = restore_trashed_pack_dir(&trash, &user, &trash_id).unwrap();
+        assert_eq!(restored.id, "keeper-2");
+        assert_eq!(
+            SoundPack::load(&user.join("keeper-2")).unwrap().id,
+            "keeper-2"
+        );
+        assert!(trashed_packs(&trash).is_empty());
+
+        assert!(restore_trashed_pack_dir(&trash, &user, "../user-soundpacks/keeper").is_err());
+    }
+
+    #[test]
+    fn test_empty_trash() {
+        let dir = TempDir::new().unwrap();
+        let user = dir.path().join("user-soundpacks");
+        let trash = dir.path().join(TRASH_DIR);
+        for name in ["One", "Two"] {
+            let pack = create_custom_pack_dir(&user, dir.path(), name).unwrap();
+            trash_pack_dir(&pack.base_path, &trash).unwrap();
+        }
+        assert_eq!(trashed_packs(&trash).len(), 2);
+
+        assert_eq!(empty_trash_dir(&trash).unwrap(), 2);
+        assert!(trashed_packs(&trash).is_empty());
+        // Nothing to do the second time, or without a trash folder
+        assert_eq!(empty_trash_dir(&trash).unwrap(), 0);
+        assert_eq!(empty_trash_dir(&dir.path().join("missing")).unwrap(), 0);
+    }
+
+    // --- Full lifecycle ---
+
+    #[test]
+    fn test_full_lifecycle() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        let bundled_dir = dir.path().join("soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        fs::create_dir_all(&bundled_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        // Create a bundled pack
+        create_test_pack_dir(bundled_dir.as_path(), "default", None);
+
+        // Data versioning
+        ensure_data_version(dir.path());
+        assert!(dir.path().join("data-version.json").exists());
+
+        // Create custom pack
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "My Sounds").unwrap();
+        assert_eq!(pack.id, "my-sounds");
+
+        // Import audio files
+        let audio_default = dir.path().join("click.mp3");
+        let audio_space = dir.path().join("spacebar.wav");
+        write_test_audio(&audio_default);
+        write_test_audio(&audio_space);
+
+        import_sound_to_pack(&pack.base_path, "default", &audio_default).unwrap();
+        import_sound_to_pack(&pack.base_path, "space", &audio_space).unwrap();
+
+        // Verify discover_all_packs ordering: default, custom, bundled-others
+        let all = discover_all_packs(&bundled_dir, &user_dir);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].id, "default");
+        assert_eq!(all[1].id, "my-sounds");
+
+        // Verify slots
+        let pack = SoundPack::load(&pack.base_path).unwrap();
+        let slots = get_all_slots(&pack);
+        assert_eq!(slots[0].file_name.as_deref(), Some("click.mp3"));
+        assert_eq!(slots[1].file_name.as_deref(), Some("spacebar.wav"));
+
+        // Remove space slot
+        let pack = remove_slot_from_pack(&pack.base_path, "space", &resource_dir).unwrap();
+        let slots = get_all_slots(&pack);
+        assert!(slots[1].file_name.is_none());
+
+        // Delete custom pack
+        delete_pack_dir(&pack.base_path).unwrap();
+        let all = discover_all_packs(&bundled_dir, &user_dir);
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, "default");
+    }
+
+    // --- Per-key sound slots ---
+
+    #[test]
+    fn test_apply_slot_per_key() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        apply_slot_to_pack(&mut pack, "key:KeyA", Some("sounds/a.mp3".into()));
+        assert!(pack.key_overrides.contains_key("KeyA"));
+        assert_eq!(
+            pack.key_overrides["KeyA"].keydown.as_deref(),
+            Some("sounds/a.mp3")
+        );
+
+        apply_slot_to_pack(&mut pack, "key:KeyA", None);
+        assert!(!pack.key_overrides.contains_key("KeyA"));
+    }
+
+    #[test]
+    fn test_get_slot_path_per_key() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        pack.key_overrides.insert(
+            "KeyB".into(),
+            KeySound {
+                keydown: Some("sounds/b.wav".into()),
+                keyup: None,
+                volume: Some(1.0),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            get_slot_path(&pack, "key:KeyB"),
+            Some("sounds/b.wav".into())
+        );
+        assert_eq!(get_slot_path(&pack, "key:KeyZ"), None);
+    }
+
+    #[test]
+    fn test_get_all_slots_with_per_key() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        pack.key_overrides.insert(
+            "KeyA".into(),
+            KeySound {
+                keydown: Some("sounds/a.mp3".into()),
+                keyup: None,
+                volume: Some(1.0),
+                ..Default::default()
+            },
+        );
+        pack.original_names
+            .insert("key:KeyA".into(), "a-sound.mp3".into());
+
+        let slots = get_all_slots(&pack);
+        assert_eq!(slots.len(), 7); // 6 category + 1 per-key
+        let key_slot = slots.iter().find(|s| s.slot == "key:KeyA").unwrap();
+        assert_eq!(key_slot.label, "KeyA");
+        assert_eq!(key_slot.file_name.as_deref(), Some("a-sound.mp3"));
+    }
+
+    #[test]
+    fn test_get_all_slots_per_key_skips_space_return() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+
+        // Add Space and Return as key_overrides (these are category slots)
+        apply_slot_to_pack(&mut pack, "space", Some("sounds/space.mp3".into()));
+        apply_slot_to_pack(&mut pack, "enter", Some("sounds/enter.mp3".into()));
+        // Add a real per-key override
+        apply_slot_to_pack(&mut pack, "key:KeyC", Some("sounds/c.mp3".into()));
+
+        let slots = get_all_slots(&pack);
+        // Should have 6 category + 1 per-key (Space/Return not duplicated)
+        assert_eq!(slots.len(), 7);
+        assert!(slots.iter().any(|s| s.slot == "key:KeyC"));
+        assert!(!slots.iter().any(|s| s.slot == "key:Space"));
+        assert!(!slots.iter().any(|s| s.slot == "key:Return"));
+    }
+
+    #[test]
+    fn test_get_all_slots_orders_numbered_keys_naturally() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+        for key in ["F10", "KeyA", "F2", "Num1", "F1", "Kp9", "KpDelete", "Kp10"] {
+            apply_slot_to_pack(
+                &mut pack,
+                &format!("key:{}", key),
+                Some("sounds/k.wav".into()),
+            );
+        }
+
+        let per_key: Vec<_> = get_all_slots(&pack)
+            .into_iter()
+            .filter_map(|s| s.slot.strip_prefix("key:").map(String::from))
+            .collect();
+        assert_eq!(
+            per_key,
+            vec!["F1", "F2", "F10", "KeyA", "Kp9", "Kp10", "KpDelete", "Num1"]
+        );
+    }
+
+    #[test]
+    fn test_import_per_key_sound() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
 
-    // Find and delete the sound file for this slot
-    let old_path = get_slot_path(&pack, slot);
-    if let Some(ref path) = old_path {
-        let abs_path = pack_dir.join(path);
-        if abs_path.exists() {
-            std::fs::remove_file(&abs_path).ok();
-        }
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+
+        let audio = dir.path().join("a-key.mp3");
+        write_test_audio(&audio);
+
+        let pack = import_sound_to_pack(&pack.base_path, "key:KeyA", &audio).unwrap();
+
+        assert!(pack.key_overrides.contains_key("KeyA"));
+        assert_eq!(
+            pack.original_names.get("key:KeyA").map(|s| s.as_str()),
+            Some("a-key.mp3")
+        );
+        // Filename uses sanitized slot: "key:KeyA" -> "key-KeyA"
+        assert!(pack
+            .base_path
+            .join("sounds")
+            .join("keydown-key-KeyA.mp3")
+            .exists());
     }
 
-    if slot == "default" {
-        // Reset default to silence.wav
-        let silence_src = resource_dir.join("resources").join("silence.wav");
-        let silence_dst = pack_dir.join("sounds").join("keydown.wav");
-        if silence_src.exists() {
-            std::fs::copy(&silence_src, &silence_dst).ok();
-        } else {
-            generate_silence_wav(&silence_dst).ok();
-        }
-        pack.defaults.keydown = "sounds/keydown.wav".into();
-    } else {
-        apply_slot_to_pack(&mut pack, slot, None);
+    #[test]
+    fn test_remove_per_key_slot() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+
+        let audio = dir.path().join("b.wav");
+        write_test_audio(&audio);
+        import_sound_to_pack(&pack.base_path, "key:KeyB", &audio).unwrap();
+
+        let pack = remove_slot_from_pack(&pack.base_path, "key:KeyB", &resource_dir).unwrap();
+        assert!(!pack.key_overrides.contains_key("KeyB"));
+        assert!(!pack.original_names.contains_key("key:KeyB"));
     }
 
-    pack.original_names.remove(slot);
-    write_pack_json(&pack)?;
-    Ok(pack)
-}
+    #[test]
+    fn test_per_key_multiple_keys() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
 
-pub fn delete_pack_dir(pack_dir: &Path) -> Result<(), String> {
-    std::fs::remove_dir_all(pack_dir)
-        .map_err(|e| format!("Failed to delete pack: {}", e))
-}
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Multi").unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::sound_pack::discover_all_packs;
-    use std::fs;
-    use tempfile::TempDir;
+        let audio_a = dir.path().join("a.mp3");
+        let audio_b = dir.path().join("b.wav");
+        let audio_c = dir.path().join("c.ogg");
+        write_test_audio(&audio_a);
+        write_test_audio(&audio_b);
+        write_test_audio(&audio_c);
 
-    fn create_test_pack_dir(dir: &Path, id: &str, source: Option<&str>) {
-        let pack_dir = dir.join(id);
-        let sounds_dir = pack_dir.join("sounds");
-        fs::create_dir_all(&sounds_dir).unwrap();
+        import_sound_to_pack(&pack.base_path, "key:KeyA", &audio_a).unwrap();
+        import_sound_to_pack(&pack.base_path, "key:KeyB", &audio_b).unwrap();
+        import_sound_to_pack(&pack.base_path, "key:Digit0", &audio_c).unwrap();
 
-        generate_silence_wav(&sounds_dir.join("keydown.wav")).unwrap();
+        let pack = SoundPack::load(&pack.base_path).unwrap();
+        let slots = get_all_slots(&pack);
+        // 6 category + 3 per-key = 9
+        assert_eq!(slots.len(), 9);
 
-        let mut manifest = serde_json::json!({
-            "id": id,
-            "name": id.to_uppercase(),
-            "author": "Test",
-            "version": "1.0.0",
-            "description": "",
-            "defaults": { "keydown": "sounds/keydown.wav", "volume": 0.8 }
-        });
-        if let Some(src) = source {
-            manifest["source"] = serde_json::json!(src);
-        }
-        fs::write(
-            pack_dir.join("pack.json"),
-            serde_json::to_string_pretty(&manifest).unwrap(),
-        )
-        .unwrap();
+        // Per-key slots should be sorted alphabetically
+        let per_key: Vec<_> = slots.iter().filter(|s| s.slot.starts_with("key:")).collect();
+        assert_eq!(per_key[0].slot, "key:Digit0");
+        assert_eq!(per_key[1].slot, "key:KeyA");
+        assert_eq!(per_key[2].slot, "key:KeyB");
     }
 
-    // --- slugify ---
+    // --- relocate_user_packs ---
 
     #[test]
-    fn test_slugify_basic() {
-        assert_eq!(slugify("My Custom Pack"), "my-custom-pack");
+    fn test_relocate_user_packs_moves_packs() {
+        let dir = TempDir::new().unwrap();
+        let old_dir = dir.path().join("user-soundpacks");
+        let new_dir = dir.path().join("elsewhere").join("packs");
+        create_test_pack_dir(&old_dir, "mine", Some("user"));
+
+        let relocation = relocate_user_packs(&old_dir, &new_dir).unwrap();
+        assert!(old_dir.join("mine").join("pack.json").exists());
+        relocation.finish();
+
+        assert!(!old_dir.join("mine").exists());
+        let pack = SoundPack::load(&new_dir.join("mine")).unwrap();
+        assert_eq!(pack.id, "mine");
+        assert!(new_dir
+            .join("mine")
+            .join("sounds")
+            .join("keydown.wav")
+            .exists());
     }
 
     #[test]
-    fn test_slugify_special_chars() {
-        assert_eq!(slugify("Hello! @World# 123"), "hello-world-123");
+    fn test_relocate_user_packs_undo_keeps_old_folder() {
+        let dir = TempDir::new().unwrap();
+        let old_dir = dir.path().join("user-soundpacks");
+        let new_dir = dir.path().join("other");
+        create_test_pack_dir(&old_dir, "mine", Some("user"));
+
+        relocate_user_packs(&old_dir, &new_dir).unwrap().undo();
+
+        assert!(old_dir.join("mine").join("pack.json").exists());
+        assert!(!new_dir.join("mine").exists());
     }
 
     #[test]
-    fn test_slugify_already_clean() {
-        assert_eq!(slugify("clean"), "clean");
+    fn test_relocate_user_packs_rejects_nested_target() {
+        let dir = TempDir::new().unwrap();
+        let old_dir = dir.path().join("user-soundpacks");
+        create_test_pack_dir(&old_dir, "mine", Some("user"));
+
+        let result = relocate_user_packs(&old_dir, &old_dir.join("nested"));
+        assert!(result.unwrap_err().contains("inside"));
+        assert!(old_dir.join("mine").join("pack.json").exists());
     }
 
     #[test]
-    fn test_slugify_leading_trailing_spaces() {
-        assert_eq!(slugify("  spaced  "), "spaced");
+    fn test_relocate_user_packs_rejects_conflict() {
+        let dir = TempDir::new().unwrap();
+        let old_dir = dir.path().join("user-soundpacks");
+        let new_dir = dir.path().join("other");
+        create_test_pack_dir(&old_dir, "mine", Some("user"));
+        create_test_pack_dir(&new_dir, "mine", Some("user"));
+
+        let result = relocate_user_packs(&old_dir, &new_dir);
+        assert!(result.unwrap_err().contains("already exists"));
+        assert!(old_dir.join("mine").join("pack.json").exists());
     }
 
-    // --- unique_id ---
-
     #[test]
-    fn test_unique_id_no_collision() {
+    fn test_relocate_user_packs_rejects_same_folder_spelled_differently() {
         let dir = TempDir::new().unwrap();
-        assert_eq!(unique_id("my-pack", dir.path()), "my-pack");
+        let old_dir = dir.path().join("user-soundpacks");
+        create_test_pack_dir(&old_dir, "mine", Some("user"));
+
+        let respelled = old_dir.join("..").join("user-soundpacks");
+        let result = relocate_user_packs(&old_dir, &respelled);
+        assert!(result.unwrap_err().contains("already at this location"));
+        let result = relocate_user_packs(&old_dir, &respelled.join("nested"));
+        assert!(result.unwrap_err().contains("inside"));
+        assert!(old_dir.join("mine").join("pack.json").exists());
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_unique_id_with_collision() {
+    fn test_relocate_user_packs_failure_leaves_old_folder_whole() {
         let dir = TempDir::new().unwrap();
-        fs::create_dir(dir.path().join("my-pack")).unwrap();
-        assert_eq!(unique_id("my-pack", dir.path()), "my-pack-2");
+        let old_dir = dir.path().join("user-soundpacks");
+        let new_dir = dir.path().join("other");
+        create_test_pack_dir(&old_dir, "a", Some("user"));
+        create_test_pack_dir(&old_dir, "b", Some("user"));
+        // A dangling link can't be copied, so the move fails partway
+        std::os::unix::fs::symlink(dir.path().join("gone"), old_dir.join("b").join("link"))
+            .unwrap();
+
+        assert!(relocate_user_packs(&old_dir, &new_dir).is_err());
+        assert!(old_dir.join("a").join("pack.json").exists());
+        assert!(old_dir.join("b").join("pack.json").exists());
+        assert_eq!(fs::read_dir(&new_dir).unwrap().count(), 0);
     }
 
+    // --- set_pack_category_enabled ---
+
     #[test]
-    fn test_unique_id_multiple_collisions() {
+    fn test_set_pack_category_enabled() {
         let dir = TempDir::new().unwrap();
-        fs::create_dir(dir.path().join("my-pack")).unwrap();
-        fs::create_dir(dir.path().join("my-pack-2")).unwrap();
-        fs::create_dir(dir.path().join("my-pack-3")).unwrap();
-        assert_eq!(unique_id("my-pack", dir.path()), "my-pack-4");
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        apply_slot_to_pack(&mut pack, "modifier", Some("sounds/keydown.wav".into()));
+        write_pack_json(&mut pack).unwrap();
+
+        let pack = set_pack_category_enabled(&pack_dir, "modifiers", false).unwrap();
+        assert_eq!(pack.category_overrides["modifiers"].enabled, Some(false));
+        assert!(pack.resolve_keydown("ShiftLeft").is_none());
+        assert!(pack.resolve_keydown("KeyA").is_some());
+
+        let pack = set_pack_category_enabled(&pack_dir, "modifiers", true).unwrap();
+        assert!(pack.resolve_keydown("ShiftLeft").is_some());
     }
 
-    // --- generate_silence_wav ---
+    #[test]
+    fn test_set_pack_category_enabled_unknown_category() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+
+        let result = set_pack_category_enabled(&dir.path().join("p"), "modifiers", false);
+        assert!(result.unwrap_err().contains("not found"));
+    }
 
     #[test]
-    fn test_generate_silence_wav_creates_valid_wav() {
+    fn test_set_pack_category_keys_adds_caps_lock_to_modifiers() {
         let dir = TempDir::new().unwrap();
-        let path = dir.path().join("silence.wav");
-        generate_silence_wav(&path).unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        apply_slot_to_pack(&mut pack, "modifier", Some("sounds/keydown.wav".into()));
+        apply_slot_to_pack(&mut pack, "backspace", Some("sounds/keydown.wav".into()));
+        pack.category_overrides
+            .get_mut("delete")
+            .unwrap()
+            .keys
+            .push("CapsLock".into());
+        write_pack_json(&mut pack).unwrap();
+        assert_eq!(pack.category_of("CapsLock"), Some("delete"));
+
+        let mut keys = pack.category_overrides["modifiers"].keys.clone();
+        keys.push("capslock".into());
+        let pack = set_pack_category_keys(&pack_dir, "modifiers", &keys).unwrap();
+        assert_eq!(pack.category_of("CapsLock"), Some("modifiers"));
+        assert_eq!(
+            pack.resolve_trace("CapsLock").layer,
+            crate::sound_pack::ResolveLayer::Category("modifiers".into())
+        );
+        assert!(!pack.category_overrides["delete"]
+            .keys
+            .contains(&"CapsLock".into()));
+        // Persisted
+        let reloaded = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(reloaded.category_of("CapsLock"), Some("modifiers"));
+    }
 
-        assert!(path.exists());
-        let data = fs::read(&path).unwrap();
-        assert_eq!(&data[0..4], b"RIFF");
-        assert_eq!(&data[8..12], b"WAVE");
-        assert_eq!(&data[12..16], b"fmt ");
-        // 44 byte header + 882 bytes data = 926 bytes
-        assert_eq!(data.len(), 926);
+    #[test]
+    fn test_set_pack_category_keys_validates_keys() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        apply_slot_to_pack(&mut pack, "modifier", Some("sounds/keydown.wav".into()));
+        write_pack_json(&mut pack).unwrap();
+
+        let err = set_pack_category_keys(&pack_dir, "modifiers", &["Hyper".into()]).unwrap_err();
+        assert_eq!(err, "Unknown key: Hyper");
+        assert!(set_pack_category_keys(&pack_dir, "modifiers", &[]).is_err());
+        let err = set_pack_category_keys(&pack_dir, "arrows", &["UpArrow".into()]).unwrap_err();
+        assert!(err.contains("not found"));
     }
 
-    // --- data versioning ---
+    // --- import validation ---
 
     #[test]
-    fn test_ensure_data_version_creates_file() {
+    fn test_import_rejects_undecodable_file() {
         let dir = TempDir::new().unwrap();
-        ensure_data_version(dir.path());
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
 
-        let version_file = dir.path().join("data-version.json");
-        assert!(version_file.exists());
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
 
-        let contents = fs::read_to_string(&version_file).unwrap();
-        let v: DataVersion = serde_json::from_str(&contents).unwrap();
-        assert_eq!(v.version, DATA_VERSION);
+        let bad_file = dir.path().join("broken.mp3");
+        fs::write(&bad_file, b"not really an mp3").unwrap();
+
+        let result = import_sound_to_pack(&pack.base_path, "space", &bad_file);
+        assert!(result.unwrap_err().contains("Could not decode"));
+        let pack = SoundPack::load(&pack.base_path).unwrap();
+        assert!(!pack.key_overrides.contains_key("Space"));
     }
 
     #[test]
-    fn test_ensure_data_version_idempotent() {
+    fn test_import_sound_bytes() {
         let dir = TempDir::new().unwrap();
-        ensure_data_version(dir.path());
-        ensure_data_version(dir.path());
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
 
-        let version_file = dir.path().join("data-version.json");
-        let contents = fs::read_to_string(&version_file).unwrap();
-        let v: DataVersion = serde_json::from_str(&contents).unwrap();
-        assert_eq!(v.version, DATA_VERSION);
-    }
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
 
-    // --- write_pack_json / SoundPack round-trip ---
+        let wav = dir.path().join("generated.wav");
+        write_test_audio(&wav);
+        let data = fs::read(&wav).unwrap();
+
+        let pack = import_sound_bytes_to_pack(&pack.base_path, "key:KeyA", &data, ".WAV").unwrap();
+        assert_eq!(
+            pack.key_overrides["KeyA"].keydown.as_deref(),
+            Some("sounds/keydown-key-KeyA.wav")
+        );
+        assert_eq!(
+            fs::read(pack.base_path.join("sounds").join("keydown-key-KeyA.wav")).unwrap(),
+            data
+        );
+        assert!(!pack.original_names.contains_key("key:KeyA"));
+    }
 
     #[test]
-    fn test_write_and_load_pack_json() {
+    fn test_import_sound_bytes_validation_matches_file_import() {
         let dir = TempDir::new().unwrap();
-        let pack_dir = dir.path().join("test-pack");
-        fs::create_dir_all(pack_dir.join("sounds")).unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
 
-        let pack = SoundPack {
-            id: "test-pack".into(),
-            name: "Test Pack".into(),
-            author: "Tester".into(),
-            version: "1.0.0".into(),
-            description: "A test".into(),
-            source: Some("user".into()),
-            defaults: SoundDefaults {
-                keydown: "sounds/keydown.wav".into(),
-                keyup: None,
-                volume: 0.8,
-            },
-            key_overrides: Default::default(),
-            category_overrides: Default::default(),
-            original_names: Default::default(),
-            base_path: pack_dir.clone(),
-        };
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
 
-        write_pack_json(&pack).unwrap();
-        assert!(pack_dir.join("pack.json").exists());
+        let result = import_sound_bytes_to_pack(&pack.base_path, "space", b"data", "txt");
+        assert!(result.unwrap_err().contains("Unsupported format"));
 
-        let loaded = SoundPack::load(&pack_dir).unwrap();
-        assert_eq!(loaded.id, "test-pack");
-        assert_eq!(loaded.name, "Test Pack");
-        assert_eq!(loaded.source, Some("user".into()));
+        let result = import_sound_bytes_to_pack(&pack.base_path, "space", b"garbage", "mp3");
+        assert!(result.unwrap_err().contains("Could not decode"));
+
+        let too_big = vec![0u8; MAX_FILE_SIZE as usize + 1];
+        let result = import_sound_bytes_to_pack(&pack.base_path, "space", &too_big, "wav");
+        assert!(result.unwrap_err().contains("too large"));
     }
 
-    // --- apply_slot_to_pack ---
+    // --- unsupported codecs ---
+
+    /// Build one Ogg page (with a valid CRC) holding a single packet.
+    fn ogg_page(header_type: u8, sequence: u32, payload: &[u8]) -> Vec<u8> {
+        let mut page = Vec::new();
+        page.extend_from_slice(b"OggS");
+        page.push(0); // version
+        page.push(header_type);
+        page.extend_from_slice(&0u64.to_le_bytes()); // granule position
+        page.extend_from_slice(&1u32.to_le_bytes()); // stream serial
+        page.extend_from_slice(&sequence.to_le_bytes());
+        page.extend_from_slice(&0u32.to_le_bytes()); // CRC placeholder
+        page.push(1);
+        page.push(payload.len() as u8);
+        page.extend_from_slice(payload);
+
+        let mut crc: u32 = 0;
+        for &byte in &page {
+            crc ^= u32::from(byte) << 24;
+            for _ in 0..8 {
+                crc = if crc & 0x8000_0000 != 0 {
+                    (crc << 1) ^ 0x04c1_1db7
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+        page
+    }
+
+    /// A tiny Ogg Opus stream: valid container, codec kira can't decode.
+    fn ogg_opus_bytes() -> Vec<u8> {
+        let mut head = b"OpusHead".to_vec();
+        head.extend_from_slice(&[1, 1, 0, 0]); // version, channels, pre-skip
+        head.extend_from_slice(&48000u32.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]); // gain, mapping family
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend_from_slice(&[0; 8]);
+
+        let mut data = ogg_page(0x02, 0, &head);
+        data.extend(ogg_page(0x00, 1, &tags));
+        data.extend(ogg_page(0x04, 2, &[0xf8, 0xff, 0xfe]));
+        data
+    }
 
     #[test]
-    fn test_apply_slot_default() {
+    fn test_sniff_codec() {
+        assert_eq!(sniff_codec(&ogg_opus_bytes()), Some("Opus"));
+        assert_eq!(sniff_codec(b"fLaC\0\0\0\x22"), Some("FLAC"));
+        assert_eq!(sniff_codec(b"RIFF....WAVE"), None);
+    }
+
+    #[test]
+    fn test_import_opus_reports_codec() {
         let dir = TempDir::new().unwrap();
-        create_test_pack_dir(dir.path(), "p", Some("user"));
-        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+        let user_dir = dir.path().join("user-soundpacks");
+        fs::create_dir_all(&user_dir).unwrap();
+        let resource_dir = dir.path().join("res");
+        fs::create_dir_all(&resource_dir).unwrap();
+
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+
+        let opus_file = dir.path().join("click.ogg");
+        fs::write(&opus_file, ogg_opus_bytes()).unwrap();
+
+        let err = import_sound_to_pack(&pack.base_path, "space", &opus_file).unwrap_err();
+        assert!(err.contains("Unsupported audio codec: Opus"), "{}", err);
+    }
 
-        apply_slot_to_pack(&mut pack, "default", Some("sounds/new.mp3".into()));
-        assert_eq!(pack.defaults.keydown, "sounds/new.mp3");
+    // --- apply_pack_mapping ---
+
+    fn mapping_from_json(value: serde_json::Value) -> SoundPackEdit {
+        serde_json::from_value(value).unwrap()
     }
 
     #[test]
-    fn test_apply_slot_space() {
+    fn test_set_pack_mapping_applies_batch() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+        let pack_dir = dir.path().join("p");
+        write_test_audio(&pack_dir.join("sounds").join("space.wav"));
 
-        apply_slot_to_pack(&mut pack, "space", Some("sounds/space.mp3".into()));
-        assert!(pack.key_overrides.contains_key("Space"));
+        let mapping = mapping_from_json(serde_json::json!({
+            "defaults": { "keydown": "sounds/keydown.wav", "keyup": null, "volume": 0.5 },
+            "key_overrides": {
+                "Space": { "keydown": "sounds/space.wav", "keyup": null, "volume": null }
+            },
+            "category_overrides": {
+                "modifiers": {
+                    "keys": ["ShiftLeft"],
+                    "keydown": "sounds/space.wav",
+                    "keyup": null,
+                    "volume": 0.7
+                }
+            }
+        }));
+        apply_pack_mapping(&pack_dir, mapping).unwrap();
+
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(pack.defaults.volume, 0.5);
         assert_eq!(
-            pack.key_overrides["Space"].keydown.as_deref(),
-            Some("sounds/space.mp3")
+            pack.resolve_keydown("Space"),
+            Some(pack_dir.join("sounds/space.wav"))
+        );
+        assert_eq!(
+            pack.resolve_keydown("ShiftLeft"),
+            Some(pack_dir.join("sounds/space.wav"))
         );
-
-        apply_slot_to_pack(&mut pack, "space", None);
-        assert!(!pack.key_overrides.contains_key("Space"));
     }
 
     #[test]
-    fn test_apply_slot_enter() {
+    fn test_set_pack_mapping_rejects_missing_file() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+        let pack_dir = dir.path().join("p");
+        let before = fs::read_to_string(pack_dir.join("pack.json")).unwrap();
 
-        apply_slot_to_pack(&mut pack, "enter", Some("sounds/enter.ogg".into()));
-        assert_eq!(
-            pack.key_overrides["Return"].keydown.as_deref(),
-            Some("sounds/enter.ogg")
-        );
+        let mapping = mapping_from_json(serde_json::json!({
+            "defaults": { "keydown": "sounds/keydown.wav", "keyup": null, "volume": 0.5 },
+            "key_overrides": {
+                "Space": { "keydown": "sounds/missing.wav", "keyup": null, "volume": null }
+            }
+        }));
+        let err = apply_pack_mapping(&pack_dir, mapping).unwrap_err();
+        assert!(err.contains("sounds/missing.wav"));
+
+        let escaping = mapping_from_json(serde_json::json!({
+            "defaults": { "keydown": "../p/sounds/keydown.wav", "keyup": null, "volume": 0.5 }
+        }));
+        assert!(apply_pack_mapping(&pack_dir, escaping).is_err());
+
+        let after = fs::read_to_string(pack_dir.join("pack.json")).unwrap();
+        assert_eq!(before, after);
     }
 
+    // --- pack color ---
+
     #[test]
-    fn test_apply_slot_modifier() {
+    fn test_apply_pack_color_round_trip() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+        let pack_dir = dir.path().join("p");
+        assert_eq!(SoundPack::load(&pack_dir).unwrap().color, None);
 
-        apply_slot_to_pack(&mut pack, "modifier", Some("sounds/mod.wav".into()));
-        let cat = &pack.category_overrides["modifiers"];
-        assert_eq!(cat.keydown.as_deref(), Some("sounds/mod.wav"));
-        assert!(cat.keys.contains(&"ShiftLeft".to_string()));
+        apply_pack_color(&pack_dir, "#ff8800").unwrap();
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(pack.color.as_deref(), Some("#FF8800"));
+        assert_eq!(pack.info().color.as_deref(), Some("#FF8800"));
 
-        apply_slot_to_pack(&mut pack, "modifier", None);
-        assert!(!pack.category_overrides.contains_key("modifiers"));
+        apply_pack_color(&pack_dir, "").unwrap();
+        assert_eq!(SoundPack::load(&pack_dir).unwrap().color, None);
     }
 
     #[test]
-    fn test_apply_slot_backspace() {
+    fn test_apply_keyup_enabled_round_trip() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+        let pack_dir = dir.path().join("p");
+        // Older manifests without the field keep keyup sounds on
+        assert!(SoundPack::load(&pack_dir).unwrap().keyup_enabled);
 
-        apply_slot_to_pack(&mut pack, "backspace", Some("sounds/bs.mp3".into()));
-        let cat = &pack.category_overrides["delete"];
-        assert_eq!(cat.keydown.as_deref(), Some("sounds/bs.mp3"));
-        assert!(cat.keys.contains(&"Backspace".to_string()));
+        apply_keyup_enabled(&pack_dir, false).unwrap();
+        assert!(!SoundPack::load(&pack_dir).unwrap().keyup_enabled);
+        assert!(apply_keyup_enabled(&dir.path().join("missing"), true).is_err());
     }
 
-    // --- get_slot_path ---
-
     #[test]
-    fn test_get_slot_path_default() {
+    fn test_apply_volume_jitter_clamps_and_resolves() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let pack = SoundPack::load(&dir.path().join("p")).unwrap();
-
+        let pack_dir = dir.path().join("p");
         assert_eq!(
-            get_slot_path(&pack, "default"),
-            Some("sounds/keydown.wav".into())
+            SoundPack::load(&pack_dir).unwrap().defaults.volume_jitter,
+            0.0
         );
-    }
 
-    #[test]
-    fn test_get_slot_path_empty_slot() {
-        let dir = TempDir::new().unwrap();
-        create_test_pack_dir(dir.path(), "p", Some("user"));
-        let pack = SoundPack::load(&dir.path().join("p")).unwrap();
+        apply_volume_jitter(&pack_dir, 0.1).unwrap();
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(pack.resolve_volume_jitter("KeyA"), 0.1);
 
-        assert_eq!(get_slot_path(&pack, "space"), None);
-    }
+        // Overrides win over the default, like volume
+        pack.key_overrides.insert(
+            "Space".into(),
+            KeySound {
+                keydown: None,
+                keyup: None,
+                volume: None,
+                volume_jitter: Some(0.3),
+                ..Default::default()
+            },
+        );
+        assert_eq!(pack.resolve_volume_jitter("Space"), 0.3);
 
-    // --- get_all_slots ---
+        for (input, expected) in [(5.0, MAX_VOLUME_JITTER), (-1.0, 0.0), (f64::NAN, 0.0)] {
+            let pack = apply_volume_jitter(&pack_dir, input).unwrap();
+            assert_eq!(pack.defaults.volume_jitter, expected);
+        }
+        assert!(apply_volume_jitter(&dir.path().join("missing"), 0.1).is_err());
+    }
 
     #[test]
-    fn test_get_all_slots_fresh_pack() {
+    fn test_prune_empty_overrides_removes_empty_category() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let pack = SoundPack::load(&dir.path().join("p")).unwrap();
-
-        let slots = get_all_slots(&pack);
-        assert_eq!(slots.len(), 5);
-        assert_eq!(slots[0].slot, "default");
-        // Default slot with no original_names entry shows as None (silence placeholder)
-        assert!(slots[0].file_name.is_none());
-        assert_eq!(slots[1].slot, "space");
-        assert!(slots[1].file_name.is_none());
+        let pack_dir = dir.path().join("p");
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        pack.category_overrides.insert(
+            "empty".into(),
+            CategoryOverride {
+                keys: Vec::new(),
+                keydown: Some("sounds/keydown.wav".into()),
+                keyup: None,
+                volume: None,
+                volume_jitter: None,
+                enabled: None,
+            },
+        );
+        pack.category_overrides
+            .insert("modifiers".into(), modifiers_category(Some(0.5)));
+        write_pack_json(&mut pack).unwrap();
+
+        let removed = prune_empty_overrides_dir(&pack_dir).unwrap();
+        assert_eq!(removed, ["category:empty"]);
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert!(!pack.category_overrides.contains_key("empty"));
+        assert!(pack.category_overrides.contains_key("modifiers"));
+        assert!(validate_pack(&pack).is_empty());
+
+        // Nothing left to prune
+        assert!(prune_empty_overrides_dir(&pack_dir).unwrap().is_empty());
+        assert!(prune_empty_overrides_dir(&dir.path().join("missing")).is_err());
     }
 
     #[test]
-    fn test_get_all_slots_with_original_name() {
+    fn test_apply_pack_notes_round_trip() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
-
-        pack.original_names
-            .insert("default".into(), "my-cool-sound.mp3".into());
-        pack.original_names
-            .insert("space".into(), "spacebar.wav".into());
-        apply_slot_to_pack(&mut pack, "space", Some("sounds/keydown-space.wav".into()));
-
-        let slots = get_all_slots(&pack);
-        assert_eq!(slots[0].file_name.as_deref(), Some("my-cool-sound.mp3"));
-        assert_eq!(slots[1].file_name.as_deref(), Some("spacebar.wav"));
+        let pack_dir = dir.path().join("p");
+        // Older manifests without the field have no notes
+        assert_eq!(SoundPack::load(&pack_dir).unwrap().notes, "");
+
+        let notes = "Recorded on a Model M.\nTODO: softer space bar";
+        apply_pack_notes(&pack_dir, notes).unwrap();
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(pack.notes, notes);
+
+        pack.notes.push_str(" (done)");
+        write_pack_json(&mut pack).unwrap();
+        assert!(SoundPack::load(&pack_dir)
+            .unwrap()
+            .notes
+            .ends_with("(done)"));
+
+        let too_long = "x".repeat(MAX_PACK_NOTES_LEN + 1);
+        assert!(apply_pack_notes(&pack_dir, &too_long)
+            .unwrap_err()
+            .contains("too long"));
+        assert!(apply_pack_notes(&dir.path().join("missing"), "").is_err());
     }
 
-    // --- copy_dir_recursive ---
-
     #[test]
-    fn test_copy_dir_recursive() {
-        let src = TempDir::new().unwrap();
-        let dst = TempDir::new().unwrap();
-
-        fs::write(src.path().join("a.txt"), "hello").unwrap();
-        fs::create_dir(src.path().join("sub")).unwrap();
-        fs::write(src.path().join("sub").join("b.txt"), "world").unwrap();
-
-        let dst_dir = dst.path().join("out");
-        copy_dir_recursive(src.path(), &dst_dir).unwrap();
+    fn test_apply_pack_color_rejects_invalid_hex() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        apply_pack_color(&pack_dir, "#123456").unwrap();
 
-        assert_eq!(fs::read_to_string(dst_dir.join("a.txt")).unwrap(), "hello");
+        for bad in ["123456", "#12345", "#1234567", "#GG0000", "red"] {
+            let err = apply_pack_color(&pack_dir, bad).unwrap_err();
+            assert!(err.contains("Invalid color"), "{}", bad);
+        }
         assert_eq!(
-            fs::read_to_string(dst_dir.join("sub").join("b.txt")).unwrap(),
-            "world"
+            SoundPack::load(&pack_dir).unwrap().color.as_deref(),
+            Some("#123456")
         );
     }
 
-    // --- discover_all_packs ordering ---
+    // --- presets ---
 
     #[test]
-    fn test_discover_all_packs_custom_before_bundled() {
-        let bundled = TempDir::new().unwrap();
-        let user = TempDir::new().unwrap();
-
-        create_test_pack_dir(bundled.path(), "default", None);
-        create_test_pack_dir(bundled.path(), "alpha", None);
-        create_test_pack_dir(bundled.path(), "beta", None);
-        create_test_pack_dir(user.path(), "custom-a", Some("user"));
-        create_test_pack_dir(user.path(), "custom-b", Some("user"));
-
-        let all = discover_all_packs(bundled.path(), user.path());
+    fn test_apply_typewriter_preset_creates_categories() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+
+        let pack = apply_preset_to_pack(&pack_dir, PresetKind::Typewriter).unwrap();
+        let modifiers = &pack.category_overrides["modifiers"];
+        assert!(modifiers.keys.contains(&"ShiftLeft".to_string()));
+        assert_eq!(modifiers.volume, Some(0.6));
+        assert!(modifiers.keydown.is_none());
+        let delete = &pack.category_overrides["delete"];
+        assert_eq!(delete.keys, vec!["Backspace", "Delete"]);
+        assert_eq!(delete.volume, Some(0.8));
+
+        // Empty categories fall back to the default sound until one is imported
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(
+            pack.resolve_keydown("Backspace"),
+            pack.resolve_keydown("KeyA")
+        );
+        assert_eq!(pack.resolve_volume("Backspace"), 0.8);
 
-        assert_eq!(all.len(), 5);
-        // Order: default, custom-a, custom-b, alpha, beta
-        assert_eq!(all[0].id, "default");
-        assert_eq!(all[1].id, "custom-a");
-        assert_eq!(all[2].id, "custom-b");
-        assert_eq!(all[3].id, "alpha");
-        assert_eq!(all[4].id, "beta");
+        // Importing into the slot fills the preset category in place
+        let mut pack = pack;
+        apply_slot_to_pack(&mut pack, "backspace", Some("sounds/keydown.wav".into()));
+        assert_eq!(pack.category_overrides["delete"].volume, Some(0.8));
     }
 
-    // --- Full lifecycle: create, import, remove slot, delete ---
+    // --- slot labels ---
 
     #[test]
-    fn test_create_custom_pack() {
+    fn test_slot_label_overrides_default() {
         let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-
-        // resource_dir won't have silence.wav, so it falls back to generation
-        let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
-
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "My Sound").unwrap();
-        assert_eq!(pack.id, "my-sound");
-        assert_eq!(pack.name, "My Sound");
-        assert_eq!(pack.source, Some("user".into()));
-        assert!(pack.base_path.join("pack.json").exists());
-        assert!(pack.base_path.join("sounds").join("keydown.wav").exists());
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        pack.key_overrides.insert(
+            "KeyB".into(),
+            KeySound {
+                keydown: Some("sounds/keydown.wav".into()),
+                keyup: None,
+                volume: None,
+                ..Default::default()
+            },
+        );
+        write_pack_json(&mut pack).unwrap();
+
+        let pack = apply_slot_label(&pack_dir, "key:KeyB", "  Boom ").unwrap();
+        apply_slot_label(&pack_dir, "space", "Thud").unwrap();
+        let label = |pack: &SoundPack, slot: &str| {
+            get_all_slots(pack)
+                .into_iter()
+                .find(|s| s.slot == slot)
+                .unwrap()
+                .label
+        };
+        assert_eq!(label(&pack, "key:KeyB"), "Boom");
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(label(&pack, "space"), "Thud");
+
+        let pack = apply_slot_label(&pack_dir, "key:KeyB", "").unwrap();
+        assert_eq!(label(&pack, "key:KeyB"), "KeyB");
+        assert!(apply_slot_label(&pack_dir, "key:KeyC", "Nope")
+            .unwrap_err()
+            .contains("Unknown slot"));
     }
 
+    // --- repair_original_names ---
+
     #[test]
-    fn test_create_custom_pack_collision() {
+    fn test_repair_original_names_cleans_stale_entries() {
         let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-        let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        apply_slot_to_pack(&mut pack, "space", Some("sounds/space.mp3".into()));
+        apply_slot_to_pack(&mut pack, "key:KeyA", Some("sounds/key_KeyA.wav".into()));
+        pack.original_names
+            .insert("space".into(), "thock.mp3".into());
+        pack.original_names.insert("key:KeyA".into(), "  ".into());
+        pack.original_names
+            .insert("enter".into(), "stale.wav".into());
+        pack.original_names
+            .insert("key:KeyZ".into(), "gone.wav".into());
+        write_pack_json(&mut pack).unwrap();
+
+        let pack = repair_original_names(&pack_dir).unwrap();
+        assert_eq!(pack.original_names.get("space").unwrap(), "thock.mp3");
+        assert_eq!(pack.original_names.get("key:KeyA").unwrap(), "key_KeyA.wav");
+        assert!(!pack.original_names.contains_key("enter"));
+        assert!(!pack.original_names.contains_key("key:KeyZ"));
+        // Silence placeholder stays unnamed
+        assert!(!pack.original_names.contains_key("default"));
 
-        let p1 = create_custom_pack_dir(&user_dir, &resource_dir, "Same Name").unwrap();
-        let p2 = create_custom_pack_dir(&user_dir, &resource_dir, "Same Name").unwrap();
-        assert_eq!(p1.id, "same-name");
-        assert_eq!(p2.id, "same-name-2");
+        let reloaded = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(reloaded.original_names, pack.original_names);
     }
 
     #[test]
-    fn test_create_custom_pack_empty_name() {
-        let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-        let resource_dir = dir.path().join("res");
-
-        let result = create_custom_pack_dir(&user_dir, &resource_dir, "  ");
-        assert!(result.is_err());
+    fn test_supported_formats_match_import_validation() {
+        let formats = supported_formats();
+        assert_eq!(formats.max_file_size, MAX_FILE_SIZE);
+        for ext in &formats.extensions {
+            assert!(validate_import_source(ext, 1).is_ok(), "{}", ext);
+        }
     }
 
+    // --- silence fallback ---
+
     #[test]
-    fn test_import_sound_file() {
+    fn test_zero_byte_silence_resource_is_regenerated() {
         let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
         let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
-
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+        fs::create_dir_all(resource_dir.join("resources")).unwrap();
+        fs::write(resource_dir.join("resources").join("silence.wav"), b"").unwrap();
+        let user_dir = dir.path().join("user");
+        fs::create_dir_all(&user_dir).unwrap();
 
-        // Create a fake mp3 file
-        let fake_audio = dir.path().join("my-space-sound.mp3");
-        fs::write(&fake_audio, b"fake mp3 data").unwrap();
+        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Quiet").unwrap();
+        let keydown = pack.base_path.join("sounds").join("keydown.wav");
+        assert!(fs::metadata(&keydown).unwrap().len() > 0);
+        assert!(StaticSoundData::from_file(&keydown).is_ok());
 
-        let pack = import_sound_to_pack(&pack.base_path, "space", &fake_audio).unwrap();
-        assert!(pack.key_overrides.contains_key("Space"));
-        assert_eq!(
-            pack.original_names.get("space").map(|s| s.as_str()),
-            Some("my-space-sound.mp3")
-        );
-        assert!(pack.base_path.join("sounds").join("keydown-space.mp3").exists());
+        fs::write(&keydown, b"").unwrap();
+        remove_slot_from_pack(&pack.base_path, "default", &resource_dir).unwrap();
+        assert!(StaticSoundData::from_file(&keydown).is_ok());
     }
 
+    // --- remove_slots_from_pack ---
+
     #[test]
-    fn test_import_rejects_unsupported_format() {
+    fn test_remove_slots_batch() {
         let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
         let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
+        let src = dir.path().join("click.wav");
+        write_test_audio(&src);
+        for slot in ["space", "key:KeyA", "key:KeyB", "key:KeyC"] {
+            import_sound_to_pack(&pack_dir, slot, &src).unwrap();
+        }
 
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+        let slots: Vec<String> = ["space", "key:KeyA", "key:KeyB", "enter", "default"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (pack, outcome) = remove_slots_from_pack(&pack_dir, &slots, &resource_dir).unwrap();
+        assert_eq!(outcome.removed, vec!["space", "key:KeyA", "key:KeyB"]);
+        // The default slot only goes back to silence
+        assert_eq!(outcome.reset, vec!["default"]);
 
-        let bad_file = dir.path().join("sound.txt");
-        fs::write(&bad_file, b"not audio").unwrap();
+        assert!(get_slot_path(&pack, "space").is_none());
+        assert!(!pack.key_overrides.contains_key("KeyA"));
+        assert!(!pack.key_overrides.contains_key("KeyB"));
+        assert!(pack.key_overrides.contains_key("KeyC"));
+        assert!(!pack_dir
+            .join("sounds")
+            .join("keydown-key-KeyA.wav")
+            .exists());
+        assert!(pack_dir
+            .join("sounds")
+            .join("keydown-key-KeyC.wav")
+            .exists());
+        assert!(!pack.original_names.contains_key("key:KeyB"));
 
-        let result = import_sound_to_pack(&pack.base_path, "space", &bad_file);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Unsupported format"));
+        let reloaded = SoundPack::load(&pack_dir).unwrap();
+        assert!(reloaded.key_overrides.contains_key("KeyC"));
+        assert!(!reloaded.key_overrides.contains_key("KeyA"));
     }
 
-    #[test]
-    fn test_import_replaces_old_file_different_extension() {
-        let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-        let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
-
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
-
-        // Import a .wav file for space
-        let wav_file = dir.path().join("space.wav");
-        fs::write(&wav_file, b"wav data").unwrap();
-        import_sound_to_pack(&pack.base_path, "space", &wav_file).unwrap();
-        assert!(pack.base_path.join("sounds").join("keydown-space.wav").exists());
-
-        // Import a .mp3 file for the same slot — old .wav should be deleted
-        let mp3_file = dir.path().join("space.mp3");
-        fs::write(&mp3_file, b"mp3 data").unwrap();
-        import_sound_to_pack(&pack.base_path, "space", &mp3_file).unwrap();
+    // --- assign_sound_to_keys_in_pack ---
 
-        assert!(!pack.base_path.join("sounds").join("keydown-space.wav").exists());
-        assert!(pack.base_path.join("sounds").join("keydown-space.mp3").exists());
+    #[test]
+    fn test_import_dir_is_source_parent() {
+        let src_dir = Path::new("downloads").join("clicks");
+        assert_eq!(import_dir(&src_dir.join("click.wav")), Some(src_dir));
+        assert_eq!(import_dir(Path::new("click.wav")), None);
     }
 
     #[test]
-    fn test_remove_slot() {
+    fn test_import_to_next_empty_key_fills_in_order() {
         let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-        let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
-
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
-
-        // Import space sound
-        let audio = dir.path().join("space.mp3");
-        fs::write(&audio, b"fake mp3").unwrap();
-        import_sound_to_pack(&pack.base_path, "space", &audio).unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let src = dir.path().join("click.wav");
+        write_test_audio(&src);
+        // KeyB is taken, so it gets skipped
+        import_sound_to_pack(&pack_dir, "key:KeyB", &src).unwrap();
 
-        // Remove it
-        let pack = remove_slot_from_pack(&pack.base_path, "space", &resource_dir).unwrap();
-        assert!(!pack.key_overrides.contains_key("Space"));
-        assert!(!pack.original_names.contains_key("space"));
-        assert!(!pack.base_path.join("sounds").join("keydown-space.mp3").exists());
+        assert_eq!(
+            import_sound_to_next_empty_key(&pack_dir, &src).unwrap(),
+            "key:KeyA"
+        );
+        assert_eq!(
+            import_sound_to_next_empty_key(&pack_dir, &src).unwrap(),
+            "key:KeyC"
+        );
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        for key in ["KeyA", "KeyB", "KeyC"] {
+            assert!(pack.key_overrides[key].keydown.is_some(), "{}", key);
+        }
+        assert!(!pack.key_overrides.contains_key("KeyD"));
+        assert_eq!(pack.original_names["key:KeyC"], "click.wav");
     }
 
     #[test]
-    fn test_remove_default_slot_resets_to_silence() {
+    fn test_assign_sound_to_keys_unreadable_pack_copies_nothing() {
         let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-        let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let src = dir.path().join("home.wav");
+        write_test_audio(&src);
+        fs::write(pack_dir.join("pack.json"), "{ not json").unwrap();
+
+        let keys = vec!["KeyA".to_string()];
+        assert!(assign_sound_to_keys_in_pack(&pack_dir, &keys, &src).is_err());
+        assert!(!pack_dir.join("sounds/keydown-shared-1.wav").exists());
+    }
 
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+    #[test]
+    fn test_assign_sound_to_keys_shares_one_file() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let src = dir.path().join("home.wav");
+        write_test_audio(&src);
+        import_sound_to_pack(&pack_dir, "key:KeyA", &src).unwrap();
+
+        let keys: Vec<String> = ["KeyA", "KeyS", "KeyD", "Space"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let pack = assign_sound_to_keys_in_pack(&pack_dir, &keys, &src).unwrap();
+
+        let shared = pack.key_overrides["KeyA"].keydown.clone().unwrap();
+        assert_eq!(shared, "sounds/keydown-shared-1.wav");
+        for key in &keys {
+            assert_eq!(
+                pack.key_overrides[key].keydown.as_deref(),
+                Some(shared.as_str())
+            );
+        }
+        assert_eq!(pack.original_names["key:KeyS"], "home.wav");
+        assert_eq!(pack.original_names["space"], "home.wav");
+
+        // One shared file; KeyA's previous file is gone
+        let files: Vec<_> = fs::read_dir(pack_dir.join("sounds"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name != "keydown.wav")
+            .collect();
+        assert_eq!(files, vec!["keydown-shared-1.wav"]);
+
+        // Clearing one key keeps the file for the others
+        let pack = remove_slot_from_pack(&pack_dir, "key:KeyS", dir.path()).unwrap();
+        assert!(pack.base_path.join(&shared).exists());
+        assert!(pack.resolve_keydown("KeyD").is_some());
+    }
 
-        // Import a custom default sound
-        let audio = dir.path().join("keydown.mp3");
-        fs::write(&audio, b"fake mp3").unwrap();
-        import_sound_to_pack(&pack.base_path, "default", &audio).unwrap();
+    // --- import_folder_to_pack ---
 
-        // Remove default — should reset to silence
-        let pack = remove_slot_from_pack(&pack.base_path, "default", &resource_dir).unwrap();
-        assert_eq!(pack.defaults.keydown, "sounds/keydown.wav");
-        assert!(!pack.original_names.contains_key("default"));
-        // silence.wav should exist as keydown.wav
-        assert!(pack.base_path.join("sounds").join("keydown.wav").exists());
+    #[test]
+    fn test_import_folder_matches_key_names() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let folder = dir.path().join("sounds-in");
+        fs::create_dir_all(folder.join("nested")).unwrap();
+        for name in ["KeyA.wav", "space.WAV", "Num1.wav", "KeyA.ogg", "Boom.wav"] {
+            write_test_audio(&folder.join(name));
+        }
+        fs::write(folder.join("KeyB.txt"), b"notes").unwrap();
+
+        let result = import_folder_to_pack(&pack_dir, &folder).unwrap();
+        assert_eq!(result.imported, vec!["KeyA", "Num1", "Space"]);
+        // Files are taken in name order, so the first of two KeyA files wins
+        assert_eq!(result.skipped, vec!["Boom.wav", "KeyA.wav", "KeyB.txt"]);
+
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(
+            pack.key_overrides["KeyA"].keydown.as_deref(),
+            Some("sounds/keydown-key-KeyA.ogg")
+        );
+        assert_eq!(
+            pack.key_overrides["Space"].keydown.as_deref(),
+            Some("sounds/keydown-space.wav")
+        );
+        assert_eq!(pack.original_names["key:Num1"], "Num1.wav");
+        assert!(validate_pack(&pack).is_empty());
     }
 
     #[test]
-    fn test_delete_pack_removes_all_files() {
+    fn test_import_folder_invalid_audio_writes_nothing() {
         let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-        let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let before = fs::read_to_string(pack_dir.join("pack.json")).unwrap();
+        let folder = dir.path().join("sounds-in");
+        fs::create_dir_all(&folder).unwrap();
+        write_test_audio(&folder.join("KeyA.wav"));
+        fs::write(folder.join("KeyB.wav"), b"not audio").unwrap();
+
+        let err = import_folder_to_pack(&pack_dir, &folder).unwrap_err();
+        assert!(err.contains("KeyB.wav"));
+        assert_eq!(
+            fs::read_to_string(pack_dir.join("pack.json")).unwrap(),
+            before
+        );
+        assert!(!pack_dir
+            .join("sounds")
+            .join("keydown-key-KeyA.wav")
+            .exists());
+    }
 
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
+    // --- bundled pack sync ---
 
-        // Import files
-        let audio1 = dir.path().join("a.mp3");
-        let audio2 = dir.path().join("b.wav");
-        fs::write(&audio1, b"fake").unwrap();
-        fs::write(&audio2, b"fake").unwrap();
-        import_sound_to_pack(&pack.base_path, "space", &audio1).unwrap();
-        import_sound_to_pack(&pack.base_path, "enter", &audio2).unwrap();
+    fn create_versioned_pack(dir: &Path, id: &str, version: &str, name: &str) {
+        create_test_pack_dir(dir, id, None);
+        let mut pack = SoundPack::load(&dir.join(id)).unwrap();
+        pack.version = version.into();
+        pack.name = name.into();
+        write_pack_json(&mut pack).unwrap();
+    }
 
-        let pack_dir = pack.base_path.clone();
-        delete_pack_dir(&pack_dir).unwrap();
-        assert!(!pack_dir.exists());
+    #[test]
+    fn test_compare_versions_is_numeric() {
+        use std::cmp::Ordering;
+        assert_eq!(compare_versions("1.10.0", "1.9.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("v2", "1.99"), Ordering::Greater);
+        assert_eq!(compare_versions("", "0.1"), Ordering::Less);
     }
 
-    // --- Full lifecycle ---
+    #[test]
+    fn test_sync_bundled_packs_by_version() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("resources");
+        let dst = dir.path().join("soundpacks");
+        let backups = dir.path().join("bundled-backups");
+        create_versioned_pack(&src, "newer", "1.1.0", "Shipped");
+        create_versioned_pack(&src, "older", "1.0.0", "Shipped");
+        create_versioned_pack(&src, "equal", "1.0.0", "Shipped");
+        create_versioned_pack(&src, "fresh", "1.0.0", "Shipped");
+        create_versioned_pack(&dst, "newer", "1.0.0", "Edited");
+        create_versioned_pack(&dst, "older", "2.0.0", "Edited");
+        create_versioned_pack(&dst, "equal", "1.0.0", "Edited");
+
+        let report = sync_bundled_packs_dir(&src, &dst, &backups).unwrap();
+        assert_eq!(report.installed, vec!["fresh"]);
+        assert_eq!(report.updated, vec!["newer"]);
+        assert_eq!(report.kept, vec!["equal", "older"]);
+
+        let name = |dir: &Path, id: &str| SoundPack::load(&dir.join(id)).unwrap().name;
+        assert_eq!(name(&dst, "newer"), "Shipped");
+        assert_eq!(name(&backups, "newer"), "Edited");
+        assert_eq!(name(&dst, "older"), "Edited");
+        assert_eq!(name(&dst, "equal"), "Edited");
+        assert_eq!(name(&dst, "fresh"), "Shipped");
+
+        // Nothing changes on a second run
+        let report = sync_bundled_packs_dir(&src, &dst, &backups).unwrap();
+        assert!(report.installed.is_empty() && report.updated.is_empty());
+    }
 
+    #[cfg(unix)]
     #[test]
-    fn test_full_lifecycle() {
+    fn test_sync_bundled_packs_failed_update_restores_copy() {
         let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        let bundled_dir = dir.path().join("soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-        fs::create_dir_all(&bundled_dir).unwrap();
-        let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
+        let src = dir.path().join("resources");
+        let dst = dir.path().join("soundpacks");
+        let backups = dir.path().join("bundled-backups");
+        create_versioned_pack(&src, "classic", "1.1.0", "Shipped");
+        create_versioned_pack(&dst, "classic", "1.0.0", "Edited");
+        create_versioned_pack(&backups, "classic", "0.9.0", "Earlier");
+        // A dangling link can't be copied, so the update fails partway
+        std::os::unix::fs::symlink(dir.path().join("gone"), src.join("classic").join("link"))
+            .unwrap();
+
+        assert!(sync_bundled_packs_dir(&src, &dst, &backups).is_err());
+        let name = |dir: &Path| SoundPack::load(&dir.join("classic")).unwrap().name;
+        assert_eq!(name(&dst), "Edited");
+        assert_eq!(name(&backups), "Earlier");
+        assert!(!backups.join("classic.pending").exists());
+    }
 
-        // Create a bundled pack
-        create_test_pack_dir(bundled_dir.as_path(), "default", None);
+    #[test]
+    fn test_sync_bundled_packs_skips_unchanged() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("resources");
+        let dst = dir.path().join("soundpacks");
+        let backups = dir.path().join("bundled-backups");
+        create_versioned_pack(&src, "classic", "1.0.0", "Shipped");
+
+        let report = sync_bundled_packs_if_changed(&src, &dst, &backups)
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.installed, vec!["classic"]);
+        assert!(sync_bundled_packs_if_changed(&src, &dst, &backups)
+            .unwrap()
+            .is_none());
+
+        // A changed bundled file forces a sync
+        create_versioned_pack(&src, "classic", "1.1.0", "Shipped");
+        let report = sync_bundled_packs_if_changed(&src, &dst, &backups)
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.updated, vec!["classic"]);
+        assert!(sync_bundled_packs_if_changed(&src, &dst, &backups)
+            .unwrap()
+            .is_none());
+
+        // So does a deleted copy
+        fs::remove_dir_all(dst.join("classic")).unwrap();
+        let report = sync_bundled_packs_if_changed(&src, &dst, &backups)
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.installed, vec!["classic"]);
+
+        // A manual sync also records what it synced
+        create_versioned_pack(&src, "classic", "1.2.0", "Shipped");
+        sync_bundled_packs_dir(&src, &dst, &backups).unwrap();
+        assert!(sync_bundled_packs_if_changed(&src, &dst, &backups)
+            .unwrap()
+            .is_none());
+    }
 
-        // Data versioning
-        ensure_data_version(dir.path());
-        assert!(dir.path().join("data-version.json").exists());
+    #[test]
+    fn test_fingerprint_dir_reads_manifests_not_audio() {
+        let dir = TempDir::new().unwrap();
+        create_versioned_pack(dir.path(), "classic", "1.0.0", "Shipped");
+        let before = fingerprint_dir(dir.path()).unwrap();
+        assert_eq!(fingerprint_dir(dir.path()).unwrap(), before);
+
+        // Same size, same mtime: the audio isn't read
+        let sound = dir
+            .path()
+            .join("classic")
+            .join("sounds")
+            .join("keydown.wav");
+        let mtime = fs::metadata(&sound).unwrap().modified().unwrap();
+        let mut data = fs::read(&sound).unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        fs::write(&sound, &data).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&sound)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        assert_eq!(fingerprint_dir(dir.path()).unwrap(), before);
+
+        create_versioned_pack(dir.path(), "classic", "1.0.1", "Shipped");
+        assert_ne!(fingerprint_dir(dir.path()).unwrap(), before);
+    }
 
-        // Create custom pack
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "My Sounds").unwrap();
-        assert_eq!(pack.id, "my-sounds");
+    // --- trim silence ---
 
-        // Import audio files
-        let audio_default = dir.path().join("click.mp3");
-        let audio_space = dir.path().join("spacebar.wav");
-        fs::write(&audio_default, b"click data").unwrap();
-        fs::write(&audio_space, b"space data").unwrap();
+    /// Mono 44.1kHz wav: `pad` silent samples, `loud` samples at half scale, `pad` silent.
+    fn write_padded_wav(path: &Path, pad: u32, loud: u32) {
+        let num_samples = pad * 2 + loud;
+        let mut buf = wav_header(44100, 1, num_samples * 2);
+        for i in 0..num_samples {
+            let sample: i16 = if i >= pad && i < pad + loud { 16384 } else { 0 };
+            buf.extend_from_slice(&sample.to_le_bytes());
+        }
+        fs::write(path, buf).unwrap();
+    }
 
-        import_sound_to_pack(&pack.base_path, "default", &audio_default).unwrap();
-        import_sound_to_pack(&pack.base_path, "space", &audio_space).unwrap();
+    #[test]
+    fn test_import_trim_silence_shortens_padded_sample() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let src = dir.path().join("padded.wav");
+        write_padded_wav(&src, 4410, 441);
+
+        let pack = import_sound_to_pack_trimmed(&pack_dir, "space", &src).unwrap();
+        let stored = pack.resolve_keydown("Space").unwrap();
+        let trimmed = StaticSoundData::from_file(&stored).unwrap();
+        assert_eq!(trimmed.frames.len(), 441);
+        assert!(fs::metadata(&stored).unwrap().len() < fs::metadata(&src).unwrap().len());
+        assert_eq!(pack.original_names["space"], "padded.wav");
+
+        // The plain import stores the file untouched
+        let pack = import_sound_to_pack(&pack_dir, "enter", &src).unwrap();
+        let stored = pack.resolve_keydown("Return").unwrap();
+        assert_eq!(fs::read(stored).unwrap(), fs::read(&src).unwrap());
+    }
 
-        // Verify discover_all_packs ordering: default, custom, bundled-others
-        let all = discover_all_packs(&bundled_dir, &user_dir);
-        assert_eq!(all.len(), 2);
-        assert_eq!(all[0].id, "default");
-        assert_eq!(all[1].id, "my-sounds");
+    #[test]
+    fn test_import_trim_silence_keeps_source_channels() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        // Stereo with identical channels: padding, a click, padding
+        let src = dir.path().join("stereo.wav");
+        let mut wav = wav_header(44100, 2, 1323 * 4);
+        for i in 0..1323u32 {
+            let sample: i16 = if (441..882).contains(&i) { 16384 } else { 0 };
+            wav.extend_from_slice(&sample.to_le_bytes());
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        fs::write(&src, wav).unwrap();
 
-        // Verify slots
-        let pack = SoundPack::load(&pack.base_path).unwrap();
-        let slots = get_all_slots(&pack);
-        assert_eq!(slots[0].file_name.as_deref(), Some("click.mp3"));
-        assert_eq!(slots[1].file_name.as_deref(), Some("spacebar.wav"));
+        let pack = import_sound_to_pack_trimmed(&pack_dir, "space", &src).unwrap();
+        let info = audio_info(&pack.resolve_keydown("Space").unwrap()).unwrap();
+        assert_eq!((info.channels, info.duration_ms), (2, 10));
+    }
 
-        // Remove space slot
-        let pack = remove_slot_from_pack(&pack.base_path, "space", &resource_dir).unwrap();
-        let slots = get_all_slots(&pack);
-        assert!(slots[1].file_name.is_none());
+    // --- split by silence ---
 
-        // Delete custom pack
-        delete_pack_dir(&pack.base_path).unwrap();
-        let all = discover_all_packs(&bundled_dir, &user_dir);
-        assert_eq!(all.len(), 1);
-        assert_eq!(all[0].id, "default");
+    /// Mono 44.1kHz wav of `clicks` runs of `loud` half-scale samples, each
+    /// followed by `gap` silent samples.
+    fn write_clicks_wav(path: &Path, clicks: &[u32], gap: u32) {
+        let mut samples = Vec::new();
+        for &loud in clicks {
+            samples.extend(std::iter::repeat_n(16384i16, loud as usize));
+            samples.extend(std::iter::repeat_n(0i16, gap as usize));
+        }
+        let mut buf = wav_header(44100, 1, samples.len() as u32 * 2);
+        for sample in samples {
+            buf.extend_from_slice(&sample.to_le_bytes());
+        }
+        fs::write(path, buf).unwrap();
     }
 
-    // --- Per-key sound slots ---
-
     #[test]
-    fn test_apply_slot_per_key() {
+    fn test_import_split_by_silence_assigns_clicks_in_order() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+        let pack_dir = dir.path().join("p");
+        let src = dir.path().join("clicks.wav");
+        // 100 ms gaps between three clicks of different lengths
+        write_clicks_wav(&src, &[441, 882, 220], 4410);
+
+        let slots: Vec<String> = ["key:KeyA", "space", "enter"].map(String::from).to_vec();
+        let pack = import_split_by_silence_to_pack(&pack_dir, &src, &slots).unwrap();
+        for (key, len) in [("KeyA", 441), ("Space", 882), ("Return", 220)] {
+            let stored = StaticSoundData::from_file(pack.resolve_keydown(key).unwrap()).unwrap();
+            assert_eq!(stored.frames.len(), len);
+        }
+        assert_eq!(pack.original_names["space"], "clicks.wav (2/3)");
+    }
 
-        apply_slot_to_pack(&mut pack, "key:KeyA", Some("sounds/a.mp3".into()));
-        assert!(pack.key_overrides.contains_key("KeyA"));
+    #[test]
+    fn test_import_split_by_silence_failure_leaves_pack_untouched() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let src = dir.path().join("clicks.wav");
+        write_clicks_wav(&src, &[441, 441], 4410);
+        let before = fs::read_to_string(pack_dir.join("pack.json")).unwrap();
+        // A folder in the way makes the second write fail
+        fs::create_dir(pack_dir.join("sounds/keydown-enter.wav")).unwrap();
+
+        let slots: Vec<String> = ["key:KeyA", "enter"].map(String::from).to_vec();
+        assert!(import_split_by_silence_to_pack(&pack_dir, &src, &slots).is_err());
         assert_eq!(
-            pack.key_overrides["KeyA"].keydown.as_deref(),
-            Some("sounds/a.mp3")
+            fs::read_to_string(pack_dir.join("pack.json")).unwrap(),
+            before
         );
-
-        apply_slot_to_pack(&mut pack, "key:KeyA", None);
-        assert!(!pack.key_overrides.contains_key("KeyA"));
+        assert!(!pack_dir.join("sounds/keydown-key-KeyA.wav").exists());
     }
 
     #[test]
-    fn test_get_slot_path_per_key() {
+    fn test_import_split_by_silence_rejects_count_mismatch() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
-
-        pack.key_overrides.insert(
-            "KeyB".into(),
-            KeySound {
-                keydown: Some("sounds/b.wav".into()),
-                keyup: None,
-                volume: Some(1.0),
-            },
-        );
+        let pack_dir = dir.path().join("p");
+        let src = dir.path().join("clicks.wav");
+        // A 10 ms dip is part of the click, not a gap
+        write_clicks_wav(&src, &[441, 441], 441);
 
+        let slots: Vec<String> = ["key:KeyA", "key:KeyB"].map(String::from).to_vec();
+        let err = import_split_by_silence_to_pack(&pack_dir, &src, &slots).unwrap_err();
         assert_eq!(
-            get_slot_path(&pack, "key:KeyB"),
-            Some("sounds/b.wav".into())
+            err,
+            "Found 1 sound(s) separated by silence but 2 slot(s) were given"
         );
-        assert_eq!(get_slot_path(&pack, "key:KeyZ"), None);
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert!(pack.key_overrides.is_empty());
+
+        let twice: Vec<String> = ["space", "space"].map(String::from).to_vec();
+        let err = import_split_by_silence_to_pack(&pack_dir, &src, &twice).unwrap_err();
+        assert_eq!(err, "Slot listed twice: space");
     }
 
+    // --- optimize ---
+
     #[test]
-    fn test_get_all_slots_with_per_key() {
+    fn test_optimize_pack_keeps_references_resolving() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
+        let pack_dir = dir.path().join("p");
+        let src = dir.path().join("click.wav");
+        write_test_audio(&src);
+        import_sound_to_pack(&pack_dir, "key:KeyA", &src).unwrap();
+        import_sound_to_pack(&pack_dir, "space", &src).unwrap();
+        // Non-wav sources end up as .wav (kira sniffs the content, not the name)
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        fs::rename(
+            pack_dir.join("sounds/keydown-space.wav"),
+            pack_dir.join("sounds/keydown-space.ogg"),
+        )
+        .unwrap();
+        apply_slot_to_pack(&mut pack, "space", Some("sounds/keydown-space.ogg".into()));
+        write_pack_json(&mut pack).unwrap();
 
-        pack.key_overrides.insert(
-            "KeyA".into(),
-            KeySound {
-                keydown: Some("sounds/a.mp3".into()),
-                keyup: None,
-                volume: Some(1.0),
-            },
+        let report = optimize_pack_dir(&pack_dir).unwrap();
+        assert_eq!(report.files, 3);
+        assert_eq!(
+            report.bytes_saved,
+            report.bytes_before as i64 - report.bytes_after as i64
         );
-        pack.original_names
-            .insert("key:KeyA".into(), "a-sound.mp3".into());
 
-        let slots = get_all_slots(&pack);
-        assert_eq!(slots.len(), 6); // 5 category + 1 per-key
-        let key_slot = slots.iter().find(|s| s.slot == "key:KeyA").unwrap();
-        assert_eq!(key_slot.label, "KeyA");
-        assert_eq!(key_slot.file_name.as_deref(), Some("a-sound.mp3"));
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(
+            pack.key_overrides["Space"].keydown.as_deref(),
+            Some("sounds/keydown-space.wav")
+        );
+        assert!(!pack_dir.join("sounds/keydown-space.ogg").exists());
+        assert_eq!(pack.original_names["space"], "click.wav");
+        assert!(validate_pack(&pack).is_empty());
+        for key in ["KeyA", "Space", "KeyZ"] {
+            let path = pack.resolve_keydown(key).unwrap();
+            assert!(StaticSoundData::from_file(&path).is_ok(), "{}", key);
+        }
     }
 
     #[test]
-    fn test_get_all_slots_per_key_skips_space_return() {
+    fn test_optimize_pack_resamples_to_44100() {
         let dir = TempDir::new().unwrap();
         create_test_pack_dir(dir.path(), "p", Some("user"));
-        let mut pack = SoundPack::load(&dir.path().join("p")).unwrap();
-
-        // Add Space and Return as key_overrides (these are category slots)
-        apply_slot_to_pack(&mut pack, "space", Some("sounds/space.mp3".into()));
-        apply_slot_to_pack(&mut pack, "enter", Some("sounds/enter.mp3".into()));
-        // Add a real per-key override
-        apply_slot_to_pack(&mut pack, "key:KeyC", Some("sounds/c.mp3".into()));
+        let pack_dir = dir.path().join("p");
+        // 100ms of tone at 22.05kHz, written over the default sound
+        let mut wav = wav_header(22050, 1, 2205 * 2);
+        for i in 0..2205u32 {
+            let sample: i16 = if i % 50 < 25 { 8000 } else { -8000 };
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        fs::write(pack_dir.join(&pack.defaults.keydown), wav).unwrap();
 
-        let slots = get_all_slots(&pack);
-        // Should have 5 category + 1 per-key (Space/Return not duplicated)
-        assert_eq!(slots.len(), 6);
-        assert!(slots.iter().any(|s| s.slot == "key:KeyC"));
-        assert!(!slots.iter().any(|s| s.slot == "key:Space"));
-        assert!(!slots.iter().any(|s| s.slot == "key:Return"));
+        optimize_pack_dir(&pack_dir).unwrap();
+        let info = audio_info(&pack_dir.join(&pack.defaults.keydown)).unwrap();
+        assert_eq!(info.sample_rate, OPTIMIZE_SAMPLE_RATE);
+        assert_eq!(info.duration_ms, 100);
     }
 
     #[test]
-    fn test_import_per_key_sound() {
+    fn test_optimize_pack_failure_leaves_pack_untouched() {
         let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-        let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
-
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
-
-        let audio = dir.path().join("a-key.mp3");
-        fs::write(&audio, b"fake mp3").unwrap();
-
-        let pack = import_sound_to_pack(&pack.base_path, "key:KeyA", &audio).unwrap();
-
-        assert!(pack.key_overrides.contains_key("KeyA"));
-        assert_eq!(
-            pack.original_names.get("key:KeyA").map(|s| s.as_str()),
-            Some("a-key.mp3")
-        );
-        // Filename uses sanitized slot: "key:KeyA" -> "key-KeyA"
-        assert!(pack
-            .base_path
-            .join("sounds")
-            .join("keydown-key-KeyA.mp3")
-            .exists());
+        create_test_pack_dir(dir.path(), "p", Some("user"));
+        let pack_dir = dir.path().join("p");
+        let src = dir.path().join("click.wav");
+        write_test_audio(&src);
+        import_sound_to_pack(&pack_dir, "key:KeyA", &src).unwrap();
+        let before = fs::read(pack_dir.join("pack.json")).unwrap();
+        let default = SoundPack::load(&pack_dir).unwrap().defaults.keydown;
+        let default_bytes = fs::read(pack_dir.join(&default)).unwrap();
+        // A directory where one staged file should go makes staging fail
+        let blocked = pack_dir.join("sounds/keydown-key-KeyA.wav.optimize-tmp");
+        fs::create_dir(&blocked).unwrap();
+
+        assert!(optimize_pack_dir(&pack_dir).is_err());
+        assert_eq!(fs::read(pack_dir.join("pack.json")).unwrap(), before);
+        assert_eq!(fs::read(pack_dir.join(&default)).unwrap(), default_bytes);
+        fs::remove_dir(&blocked).unwrap();
+        let leftovers: Vec<_> = fs::read_dir(pack_dir.join("sounds"))
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().contains(".optimize-"))
+            .collect();
+        assert!(leftovers.is_empty());
     }
 
     #[test]
-    fn test_remove_per_key_slot() {
+    fn test_encode_wav_round_trips() {
         let dir = TempDir::new().unwrap();
-        let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-        let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
-
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Test").unwrap();
-
-        let audio = dir.path().join("b.wav");
-        fs::write(&audio, b"fake wav").unwrap();
-        import_sound_to_pack(&pack.base_path, "key:KeyB", &audio).unwrap();
+        let src = dir.path().join("in.wav");
+        write_test_audio(&src);
+        let sound = StaticSoundData::from_file(&src).unwrap();
 
-        let pack = remove_slot_from_pack(&pack.base_path, "key:KeyB", &resource_dir).unwrap();
-        assert!(!pack.key_overrides.contains_key("KeyB"));
-        assert!(!pack.original_names.contains_key("key:KeyB"));
+        let wav = encode_wav(&sound);
+        // Mono silence re-encodes to the same bytes
+        assert_eq!(wav, fs::read(&src).unwrap());
     }
 
+    // --- diagnostics ---
+
     #[test]
-    fn test_per_key_multiple_keys() {
+    fn test_quarantine_pack_dir_moves_broken_pack() {
         let dir = TempDir::new().unwrap();
         let user_dir = dir.path().join("user-soundpacks");
-        fs::create_dir_all(&user_dir).unwrap();
-        let resource_dir = dir.path().join("res");
-        fs::create_dir_all(&resource_dir).unwrap();
-
-        let pack = create_custom_pack_dir(&user_dir, &resource_dir, "Multi").unwrap();
-
-        let audio_a = dir.path().join("a.mp3");
-        let audio_b = dir.path().join("b.wav");
-        let audio_c = dir.path().join("c.ogg");
-        fs::write(&audio_a, b"fake").unwrap();
-        fs::write(&audio_b, b"fake").unwrap();
-        fs::write(&audio_c, b"fake").unwrap();
+        create_test_pack_dir(&user_dir, "broken", Some("user"));
+        fs::write(user_dir.join("broken").join("pack.json"), "not json").unwrap();
+        assert_eq!(find_unloadable_packs(&[&user_dir]).len(), 1);
+
+        let dest = quarantine_pack_dir(&user_dir, &user_dir.join("broken")).unwrap();
+        assert_eq!(dest, user_dir.join(QUARANTINE_DIR).join("broken"));
+        assert!(dest.join("pack.json").exists());
+        assert!(!user_dir.join("broken").exists());
+        assert!(find_unloadable_packs(&[&user_dir]).is_empty());
+
+        // A second pack with the same name gets a fresh slot in quarantine
+        create_test_pack_dir(&user_dir, "broken", Some("user"));
+        let dest = quarantine_pack_dir(&user_dir, &user_dir.join("broken")).unwrap();
+        assert_eq!(dest, user_dir.join(QUARANTINE_DIR).join("broken-2"));
+
+        // Only direct children of the user packs folder can be moved
+        let outside = dir.path().join("elsewhere");
+        fs::create_dir_all(&outside).unwrap();
+        assert!(quarantine_pack_dir(&user_dir, &outside).is_err());
+        assert!(quarantine_pack_dir(&user_dir, &user_dir.join(QUARANTINE_DIR)).is_err());
+        assert!(quarantine_pack_dir(&user_dir, &user_dir.join("missing")).is_err());
+    }
 
-        import_sound_to_pack(&pack.base_path, "key:KeyA", &audio_a).unwrap();
-        import_sound_to_pack(&pack.base_path, "key:KeyB", &audio_b).unwrap();
-        import_sound_to_pack(&pack.base_path, "key:Digit0", &audio_c).unwrap();
+    #[test]
+    fn test_diagnose_packs_healthy_and_broken() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "good", None);
+        create_test_pack_dir(dir.path(), "bad", None);
+        let bad_dir = dir.path().join("bad");
+        fs::write(bad_dir.join("sounds").join("keydown.wav"), b"not audio").unwrap();
+        let mut bad = SoundPack::load(&bad_dir).unwrap();
+        bad.defaults.keyup = Some("sounds/missing.wav".into());
+        write_pack_json(&mut bad).unwrap();
+        create_test_pack_dir(dir.path(), "corrupt", None);
+        fs::write(dir.path().join("corrupt").join("pack.json"), "{ nope").unwrap();
+
+        let report = diagnose_packs(&[dir.path()]);
+        let ids: Vec<_> = report.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["bad", "corrupt", "good"]);
+
+        let good = &report[2];
+        assert!(good.healthy);
+        assert!(good.issues.is_empty() && good.default_sound_error.is_none());
+
+        let bad = &report[0];
+        assert!(!bad.healthy);
+        assert_eq!(
+            bad.issues,
+            vec![PackIssue::MissingFile {
+                path: "sounds/missing.wav".into()
+            }]
+        );
+        assert!(bad.default_sound_error.is_some());
 
-        let pack = SoundPack::load(&pack.base_path).unwrap();
-        let slots = get_all_slots(&pack);
-        // 5 category + 3 per-key = 8
-        assert_eq!(slots.len(), 8);
+        let corrupt = &report[1];
+        assert!(!corrupt.healthy);
+        assert!(corrupt.load_error.is_some());
+    }
 
-        // Per-key slots should be sorted alphabetically
-        let per_key: Vec<_> = slots.iter().filter(|s| s.slot.starts_with("key:")).collect();
-        assert_eq!(per_key[0].slot, "key:Digit0");
-        assert_eq!(per_key[1].slot, "key:KeyA");
-        assert_eq!(per_key[2].slot, "key:KeyB");
+    #[test]
+    fn test_diagnose_pack_with_only_warnings_is_healthy() {
+        let dir = TempDir::new().unwrap();
+        create_test_pack_dir(dir.path(), "p", None);
+        let pack_dir = dir.path().join("p");
+        let mut pack = SoundPack::load(&pack_dir).unwrap();
+        // Redundant override on a key name no keyboard produces
+        pack.key_overrides
+            .insert("Period".into(), KeySound::default());
+        write_pack_json(&mut pack).unwrap();
+
+        let diagnosis = diagnose_pack_dir(&pack_dir);
+        assert_eq!(diagnosis.issues.len(), 2);
+        assert!(diagnosis
+            .issues
+            .iter()
+            .all(|issue| issue.severity() == IssueSeverity::Warning));
+        assert!(diagnosis.healthy);
     }
 }
@@ -0,0 +1,51 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Typed command failure, serialized to the frontend as `{ kind, message }`
+/// so the UI can react to the reason instead of parsing the text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum KeysoundError {
+    /// No pack with this id, or its folder has no pack.json
+    NotFound(String),
+    /// pack.json exists but couldn't be read or parsed
+    LoadFailed(String),
+    /// The manifest loaded but none of the pack's sounds could be decoded
+    DecodeFailed(String),
+    /// Anything else (poisoned lock, missing app dirs, ...)
+    Other(String),
+}
+
+impl fmt::Display for KeysoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(msg)
+            | Self::LoadFailed(msg)
+            | Self::DecodeFailed(msg)
+            | Self::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for KeysoundError {}
+
+impl From<String> for KeysoundError {
+    fn from(msg: String) -> Self {
+        Self::Other(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_kind_and_message() {
+        let err = KeysoundError::NotFound("Sound pack 'x' not found".into());
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({ "kind": "not_found", "message": "Sound pack 'x' not found" })
+        );
+        assert_eq!(err.to_string(), "Sound pack 'x' not found");
+    }
+}
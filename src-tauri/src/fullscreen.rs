@@ -0,0 +1,92 @@
+/// Reports whether a fullscreen app (typically a game) has focus. The engine
+/// asks this on each key event while "mute on fullscreen" is on.
+pub trait FullscreenProbe: Send {
+    fn is_fullscreen_focused(&self) -> bool;
+}
+
+/// Queries the OS. Only implemented on Windows; elsewhere it never reports
+/// fullscreen, so the option does nothing.
+pub struct SystemFullscreenProbe;
+
+impl FullscreenProbe for SystemFullscreenProbe {
+    fn is_fullscreen_focused(&self) -> bool {
+        #[cfg(windows)]
+        {
+            win32::foreground_is_fullscreen()
+        }
+        #[cfg(not(windows))]
+        {
+            false
+        }
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use std::ffi::c_void;
+
+    type Hwnd = *mut c_void;
+    type Hmonitor = *mut c_void;
+
+    const MONITOR_DEFAULTTONEAREST: u32 = 2;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct Rect {
+        left: i32,
+        top: i32,
+        right: i32,
+        bottom: i32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct MonitorInfo {
+        cb_size: u32,
+        rc_monitor: Rect,
+        rc_work: Rect,
+        flags: u32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetForegroundWindow() -> Hwnd;
+        fn GetDesktopWindow() -> Hwnd;
+        fn GetShellWindow() -> Hwnd;
+        fn GetWindowRect(hwnd: Hwnd, rect: *mut Rect) -> i32;
+        fn MonitorFromWindow(hwnd: Hwnd, flags: u32) -> Hmonitor;
+        fn GetMonitorInfoW(monitor: Hmonitor, info: *mut MonitorInfo) -> i32;
+    }
+
+    /// Whether the foreground window covers its whole monitor. The desktop and
+    /// shell windows don't count.
+    pub fn foreground_is_fullscreen() -> bool {
+        // SAFETY: plain user32 queries; every out-pointer refers to a live local.
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.is_null() || hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+                return false;
+            }
+
+            let mut window = Rect::default();
+            if GetWindowRect(hwnd, &mut window) == 0 {
+                return false;
+            }
+
+            let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+            let mut info = MonitorInfo {
+                cb_size: std::mem::size_of::<MonitorInfo>() as u32,
+                ..Default::default()
+            };
+            if monitor.is_null() || GetMonitorInfoW(monitor, &mut info) == 0 {
+                return false;
+            }
+
+            let screen = info.rc_monitor;
+            window.left <= screen.left
+                && window.top <= screen.top
+                && window.right >= screen.right
+                && window.bottom >= screen.bottom
+        }
+    }
+}
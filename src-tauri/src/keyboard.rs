@@ -1,27 +1,576 @@
 use rdev::{listen, Event, EventType, Key};
-use std::sync::mpsc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{mpsc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 /// Convert an rdev::Key to the string used in pack.json
 pub fn key_to_string(key: &Key) -> String {
     format!("{:?}", key)
 }
 
+/// Every key name `key_to_string` can produce (rdev's `Key` variants, minus `Unknown`)
+pub const KEY_NAMES: &[&str] = &[
+    "Alt",
+    "AltGr",
+    "Backspace",
+    "CapsLock",
+    "ControlLeft",
+    "ControlRight",
+    "Delete",
+    "DownArrow",
+    "End",
+    "Escape",
+    "F1",
+    "F2",
+    "F3",
+    "F4",
+    "F5",
+    "F6",
+    "F7",
+    "F8",
+    "F9",
+    "F10",
+    "F11",
+    "F12",
+    "Home",
+    "LeftArrow",
+    "MetaLeft",
+    "MetaRight",
+    "PageDown",
+    "PageUp",
+    "Return",
+    "RightArrow",
+    "ShiftLeft",
+    "ShiftRight",
+    "Space",
+    "Tab",
+    "UpArrow",
+    "PrintScreen",
+    "ScrollLock",
+    "Pause",
+    "NumLock",
+    "BackQuote",
+    "Num1",
+    "Num2",
+    "Num3",
+    "Num4",
+    "Num5",
+    "Num6",
+    "Num7",
+    "Num8",
+    "Num9",
+    "Num0",
+    "Minus",
+    "Equal",
+    "KeyQ",
+    "KeyW",
+    "KeyE",
+    "KeyR",
+    "KeyT",
+    "KeyY",
+    "KeyU",
+    "KeyI",
+    "KeyO",
+    "KeyP",
+    "LeftBracket",
+    "RightBracket",
+    "KeyA",
+    "KeyS",
+    "KeyD",
+    "KeyF",
+    "KeyG",
+    "KeyH",
+    "KeyJ",
+    "KeyK",
+    "KeyL",
+    "SemiColon",
+    "Quote",
+    "BackSlash",
+    "IntlBackslash",
+    "KeyZ",
+    "KeyX",
+    "KeyC",
+    "KeyV",
+    "KeyB",
+    "KeyN",
+    "KeyM",
+    "Comma",
+    "Dot",
+    "Slash",
+    "Insert",
+    "KpReturn",
+    "KpMinus",
+    "KpPlus",
+    "KpMultiply",
+    "KpDivide",
+    "Kp0",
+    "Kp1",
+    "Kp2",
+    "Kp3",
+    "Kp4",
+    "Kp5",
+    "Kp6",
+    "Kp7",
+    "Kp8",
+    "Kp9",
+    "KpDelete",
+    "Function",
+];
+
+/// Whether a pack may name `key`: any `KEY_NAMES` entry (exact case), a scroll
+/// key, or rdev's "Unknown(<code>)" for keys it has no name for.
+pub fn is_known_key_name(key: &str) -> bool {
+    KEY_NAMES.contains(&key)
+        || matches!(key, "ScrollUp" | "ScrollDown")
+        || key
+            .strip_prefix("Unknown(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .is_some_and(|code| code.parse::<u32>().is_ok())
+}
+
+/// Letter and digit keys in the order batch imports fill them
+pub const ASSIGNABLE_KEYS: &[&str] = &[
+    "KeyA", "KeyB", "KeyC", "KeyD", "KeyE", "KeyF", "KeyG", "KeyH", "KeyI", "KeyJ", "KeyK", "KeyL",
+    "KeyM", "KeyN", "KeyO", "KeyP", "KeyQ", "KeyR", "KeyS", "KeyT", "KeyU", "KeyV", "KeyW", "KeyX",
+    "KeyY", "KeyZ", "Num1", "Num2", "Num3", "Num4", "Num5", "Num6", "Num7", "Num8", "Num9", "Num0",
+];
+
+/// Coarse filter on which keys make sound
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutProfile {
+    #[default]
+    All,
+    /// KeyA-KeyZ only
+    LettersOnly,
+    /// Everything except the numeric keypad
+    NoNumpad,
+    /// Everything except F1-F12 and the Fn key
+    NoFunction,
+}
+
+impl LayoutProfile {
+    /// Whether `key_name` (as produced by `key_to_string`) may make a sound.
+    pub fn allows(self, key_name: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::LettersOnly => key_name
+                .strip_prefix("Key")
+                .is_some_and(|c| c.len() == 1 && c.bytes().all(|b| b.is_ascii_uppercase())),
+            Self::NoNumpad => !(key_name.starts_with("Kp") || key_name == "NumLock"),
+            Self::NoFunction => {
+                let is_f_key = key_name
+                    .strip_prefix('F')
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+                !(is_f_key || key_name == "Function")
+            }
+        }
+    }
+}
+
+/// How the sound hotkey's press and release change whether sound is on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyMode {
+    /// Each press flips sound on/off; release does nothing
+    #[default]
+    Toggle,
+    /// Sound is on only while the hotkey is held
+    PushToPlay,
+    /// Sound is off only while the hotkey is held
+    PushToMute,
+}
+
+impl HotkeyMode {
+    /// Sound state after the hotkey is pressed (`pressed`) or released, given
+    /// the current state `enabled`.
+    pub fn next_enabled(self, pressed: bool, enabled: bool) -> bool {
+        match self {
+            Self::Toggle if pressed => !enabled,
+            Self::Toggle => enabled,
+            Self::PushToPlay => pressed,
+            Self::PushToMute => !pressed,
+        }
+    }
+
+    /// Sound state while the hotkey isn't held, if the mode fixes one.
+    pub fn resting_enabled(self) -> Option<bool> {
+        match self {
+            Self::Toggle => None,
+            Self::PushToPlay => Some(false),
+            Self::PushToMute => Some(true),
+        }
+    }
+}
+
+/// Canonical key name for `name`, ignoring case (e.g. "keya" -> "KeyA").
+pub fn canonical_key_name(name: &str) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|k| k.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+/// A key going down or coming back up, or a mouse wheel notch
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyEvent {
+    Press(String),
+    Release(String),
+    /// Synthetic "ScrollUp" / "ScrollDown" key
+    Scroll(String),
+}
+
+/// Synthetic key name for a vertical wheel movement (None for horizontal-only).
+pub fn scroll_key(delta_y: i64) -> Option<&'static str> {
+    match delta_y.signum() {
+        1 => Some("ScrollUp"),
+        -1 => Some("ScrollDown"),
+        _ => None,
+    }
+}
+
 /// Start the global keyboard listener on a dedicated thread.
-/// Returns a receiver that yields key names on keydown events.
-pub fn start_listener() -> mpsc::Receiver<String> {
+/// Returns a receiver that yields key press and release events.
+/// `on_error` is called if the listener can't start or stops with an error.
+pub fn start_listener(on_error: impl FnOnce(String) + Send + 'static) -> mpsc::Receiver<KeyEvent> {
     let (tx, rx) = mpsc::channel();
 
     thread::spawn(move || {
         if let Err(e) = listen(move |event: Event| {
-            if let EventType::KeyPress(key) = event.event_type {
-                let key_name = key_to_string(&key);
-                let _ = tx.send(key_name);
-            }
+            let key_event = match event.event_type {
+                EventType::KeyPress(key) => KeyEvent::Press(key_to_string(&key)),
+                EventType::KeyRelease(key) => KeyEvent::Release(key_to_string(&key)),
+                EventType::Wheel { delta_y, .. } => match scroll_key(delta_y) {
+                    Some(name) => KeyEvent::Scroll(name.to_string()),
+                    None => return,
+                },
+                _ => return,
+            };
+            let _ = tx.send(key_event);
         }) {
-            log::error!("Keyboard listener error: {:?}", e);
+            report_listener_error(&e, on_error);
         }
     });
 
     rx
 }
+
+/// Log a listener failure and hand the message to `on_error`.
+fn report_listener_error(e: &impl std::fmt::Debug, on_error: impl FnOnce(String)) {
+    let message = format!("Keyboard listener error: {:?}", e);
+    log::error!("{}", message);
+    on_error(message);
+}
+
+/// User layout override: rdev key name -> key name sent to the engine and
+/// key capture. Keys without an entry pass through unchanged.
+#[derive(Default)]
+pub struct KeyRemap {
+    table: Mutex<HashMap<String, String>>,
+}
+
+impl KeyRemap {
+    /// Replace the table. Names are matched case-insensitively and stored in
+    /// canonical form; identity entries are dropped.
+    pub fn set(&self, remap: HashMap<String, String>) -> Result<(), String> {
+        let mut table = HashMap::new();
+        for (from, to) in remap {
+            let from = canonical_key_name(&from).ok_or(format!("Unknown key: {}", from))?;
+            let to = canonical_key_name(&to).ok_or(format!("Unknown key: {}", to))?;
+            if from != to {
+                table.insert(from.to_string(), to.to_string());
+            }
+        }
+        *self.table.lock().map_err(|e| e.to_string())? = table;
+        Ok(())
+    }
+
+    pub fn get(&self) -> HashMap<String, String> {
+        self.table.lock().map(|t| t.clone()).unwrap_or_default()
+    }
+
+    /// Rename the key of a press or release. Scroll events are left alone.
+    pub fn apply(&self, event: KeyEvent) -> KeyEvent {
+        let Ok(table) = self.table.lock() else {
+            return event;
+        };
+        let remap = |key: String| table.get(&key).cloned().unwrap_or(key);
+        match event {
+            KeyEvent::Press(key) => KeyEvent::Press(remap(key)),
+            KeyEvent::Release(key) => KeyEvent::Release(remap(key)),
+            scroll @ KeyEvent::Scroll(_) => scroll,
+        }
+    }
+}
+
+/// Diverts the next key press to a waiting "press a key to assign" capture
+/// instead of the sound engine.
+#[derive(Default)]
+pub struct KeyCapture {
+    /// Sender for the capture currently waiting for a key
+    waiting: Mutex<Option<mpsc::Sender<String>>>,
+    /// Key whose release should also be swallowed after it was captured
+    swallow_release: Mutex<Option<String>>,
+}
+
+impl KeyCapture {
+    /// Offer a listener event to a pending capture.
+    /// Returns true if the event was consumed and must not make a sound.
+    pub fn offer(&self, event: &KeyEvent) -> bool {
+        match event {
+            KeyEvent::Press(key) => {
+                let Some(tx) = self.waiting.lock().ok().and_then(|mut w| w.take()) else {
+                    return false;
+                };
+                if tx.send(key.clone()).is_err() {
+                    // The capture gave up just now; let the key play normally
+                    return false;
+                }
+                if let Ok(mut swallow) = self.swallow_release.lock() {
+                    *swallow = Some(key.clone());
+                }
+                true
+            }
+            KeyEvent::Release(key) => {
+                let Ok(mut swallow) = self.swallow_release.lock() else {
+                    return false;
+                };
+                if swallow.as_deref() == Some(key.as_str()) {
+                    *swallow = None;
+                    true
+                } else {
+                    false
+                }
+            }
+            KeyEvent::Scroll(_) => false,
+        }
+    }
+
+    /// Wait for the next key press and return its name, or fail after `timeout`.
+    pub fn capture(&self, timeout: Duration) -> Result<String, String> {
+        let (tx, rx) = mpsc::channel();
+        {
+            let mut waiting = self.waiting.lock().map_err(|e| e.to_string())?;
+            if waiting.is_some() {
+                return Err("A key capture is already in progress".into());
+            }
+            *waiting = Some(tx);
+        }
+
+        let result = rx.recv_timeout(timeout);
+        if let Ok(mut waiting) = self.waiting.lock() {
+            *waiting = None;
+        }
+        result.map_err(|_| format!("No key pressed within {} seconds", timeout.as_secs()))
+    }
+}
+
+/// Discard key events until `deadline`, so keystrokes typed before the listener
+/// should be active (e.g. the tail of a login password) never make a sound.
+/// Returns the number of events discarded.
+pub fn drain_until<T>(rx: &mpsc::Receiver<T>, deadline: Instant) -> usize {
+    let mut discarded = 0;
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        match rx.recv_timeout(deadline - now) {
+            Ok(_) => discarded += 1,
+            Err(mpsc::RecvTimeoutError::Timeout) => break,
+            Err(mpsc::RecvTimeoutError::Disconnected) => return discarded,
+        }
+    }
+    // Events queued right up to the deadline belong to the delay too
+    discarded + rx.try_iter().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_layout_profile_letters_only() {
+        let profile = LayoutProfile::LettersOnly;
+        assert!(profile.allows("KeyA"));
+        assert!(profile.allows("KeyZ"));
+        // rdev calls the top-row 0 key "Num0"
+        for key in ["Num0", "Space", "Kp1", "F1", "Return"] {
+            assert!(!profile.allows(key), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_layout_profile_no_numpad_and_no_function() {
+        assert!(!LayoutProfile::NoNumpad.allows("Kp5"));
+        assert!(!LayoutProfile::NoNumpad.allows("KpReturn"));
+        assert!(!LayoutProfile::NoNumpad.allows("NumLock"));
+        assert!(LayoutProfile::NoNumpad.allows("Num5"));
+
+        assert!(!LayoutProfile::NoFunction.allows("F1"));
+        assert!(!LayoutProfile::NoFunction.allows("F12"));
+        assert!(!LayoutProfile::NoFunction.allows("Function"));
+        assert!(LayoutProfile::NoFunction.allows("KeyF"));
+
+        assert!(KEY_NAMES.iter().all(|k| LayoutProfile::All.allows(k)));
+    }
+
+    #[test]
+    fn test_hotkey_mode_toggle_flips_on_press_only() {
+        let mode = HotkeyMode::Toggle;
+        assert!(!mode.next_enabled(true, true));
+        assert!(mode.next_enabled(true, false));
+        assert!(mode.next_enabled(false, true));
+        assert!(!mode.next_enabled(false, false));
+        assert_eq!(mode.resting_enabled(), None);
+    }
+
+    #[test]
+    fn test_hotkey_mode_push_to_play_and_push_to_mute() {
+        for enabled in [true, false] {
+            assert!(HotkeyMode::PushToPlay.next_enabled(true, enabled));
+            assert!(!HotkeyMode::PushToPlay.next_enabled(false, enabled));
+            assert!(!HotkeyMode::PushToMute.next_enabled(true, enabled));
+            assert!(HotkeyMode::PushToMute.next_enabled(false, enabled));
+        }
+        assert_eq!(HotkeyMode::PushToPlay.resting_enabled(), Some(false));
+        assert_eq!(HotkeyMode::PushToMute.resting_enabled(), Some(true));
+    }
+
+    #[test]
+    fn test_drain_until_discards_events_during_delay() {
+        let (tx, rx) = mpsc::channel();
+        tx.send("KeyA".to_string()).unwrap();
+        tx.send("KeyB".to_string()).unwrap();
+
+        let sender = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            tx.send("Return".to_string()).unwrap();
+            tx
+        });
+        let discarded = drain_until(&rx, Instant::now() + Duration::from_millis(100));
+        assert_eq!(discarded, 3);
+
+        // Events after the delay are delivered
+        let tx = sender.join().unwrap();
+        tx.send("Space".to_string()).unwrap();
+        assert_eq!(rx.recv().unwrap(), "Space");
+    }
+
+    #[test]
+    fn test_drain_until_past_deadline_only_clears_queue() {
+        let (tx, rx) = mpsc::channel();
+        tx.send("KeyA".to_string()).unwrap();
+        assert_eq!(drain_until(&rx, Instant::now()), 1);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_key_capture_takes_next_press() {
+        let capture = Arc::new(KeyCapture::default());
+        assert!(!capture.offer(&KeyEvent::Press("KeyA".into())));
+
+        let source = Arc::clone(&capture);
+        let events = thread::spawn(move || {
+            // Wait for the capture to start listening
+            while !source.offer(&KeyEvent::Press("KeyQ".into())) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            let consumed_other_release = source.offer(&KeyEvent::Release("KeyA".into()));
+            let consumed_release = source.offer(&KeyEvent::Release("KeyQ".into()));
+            let consumed_next = source.offer(&KeyEvent::Press("KeyW".into()));
+            (consumed_other_release, consumed_release, consumed_next)
+        });
+
+        assert_eq!(capture.capture(Duration::from_secs(5)).unwrap(), "KeyQ");
+        let (other_release, release, next) = events.join().unwrap();
+        assert!(!other_release);
+        assert!(release);
+        assert!(!next);
+    }
+
+    #[test]
+    fn test_known_key_names() {
+        assert!(is_known_key_name("Return"));
+        assert!(is_known_key_name("ScrollDown"));
+        assert!(is_known_key_name("Unknown(179)"));
+        assert!(!is_known_key_name("Retrun"));
+        assert!(!is_known_key_name("return"));
+        assert!(!is_known_key_name("Unknown(x)"));
+    }
+
+    #[test]
+    fn test_scroll_key_from_wheel_delta() {
+        assert_eq!(scroll_key(3), Some("ScrollUp"));
+        assert_eq!(scroll_key(-1), Some("ScrollDown"));
+        assert_eq!(scroll_key(0), None);
+    }
+
+    #[test]
+    fn test_listener_error_is_recorded() {
+        let last_error = Mutex::new(None);
+        report_listener_error(&"EventTapError", |message| {
+            *last_error.lock().unwrap() = Some(message);
+        });
+        assert_eq!(
+            last_error.into_inner().unwrap().as_deref(),
+            Some("Keyboard listener error: \"EventTapError\"")
+        );
+    }
+
+    #[test]
+    fn test_key_remap_swaps_layout_keys() {
+        let remap = KeyRemap::default();
+        remap
+            .set(HashMap::from([
+                ("keyz".to_string(), "KeyY".to_string()),
+                ("KeyY".to_string(), "KeyZ".to_string()),
+                ("KeyA".to_string(), "KeyA".to_string()),
+            ]))
+            .unwrap();
+        assert_eq!(remap.get().len(), 2);
+
+        assert_eq!(
+            remap.apply(KeyEvent::Press("KeyZ".into())),
+            KeyEvent::Press("KeyY".into())
+        );
+        assert_eq!(
+            remap.apply(KeyEvent::Release("KeyZ".into())),
+            KeyEvent::Release("KeyY".into())
+        );
+        assert_eq!(
+            remap.apply(KeyEvent::Press("KeyY".into())),
+            KeyEvent::Press("KeyZ".into())
+        );
+        assert_eq!(
+            remap.apply(KeyEvent::Press("KeyB".into())),
+            KeyEvent::Press("KeyB".into())
+        );
+    }
+
+    #[test]
+    fn test_key_remap_rejects_unknown_keys() {
+        let remap = KeyRemap::default();
+        remap
+            .set(HashMap::from([("KeyZ".to_string(), "KeyY".to_string())]))
+            .unwrap();
+        let err = remap
+            .set(HashMap::from([("KeyZ".to_string(), "Ü".to_string())]))
+            .unwrap_err();
+        assert_eq!(err, "Unknown key: Ü");
+        // A rejected table leaves the previous one in place
+        assert_eq!(remap.get().get("KeyZ").map(String::as_str), Some("KeyY"));
+    }
+
+    #[test]
+    fn test_key_capture_times_out() {
+        let capture = KeyCapture::default();
+        let err = capture.capture(Duration::from_millis(20)).unwrap_err();
+        assert!(err.contains("No key pressed"));
+        // Nothing is waiting anymore, so keys play normally
+        assert!(!capture.offer(&KeyEvent::Press("KeyA".into())));
+    }
+}
@@ -1,226 +1,1621 @@
 mod custom_pack;
+mod error;
+mod fullscreen;
 mod keyboard;
+mod recording;
+mod settings;
 mod sound_engine;
 mod sound_pack;
 
 use custom_pack::{
-    copy_dir_recursive, create_custom_pack_dir, delete_pack_dir, ensure_data_version,
-    get_all_slots, import_sound_to_pack, remove_slot_from_pack, write_pack_json,
-    SlotInfo,
+    apply_keyup_enabled, apply_pack_color, apply_pack_mapping, apply_pack_notes,
+    apply_preset_to_pack, apply_slot_delay, apply_slot_label, apply_slot_trigger,
+    apply_volume_jitter, assign_sound_to_keys_in_pack, change_pack_id_dir, create_custom_pack_dir,
+    create_synth_pack_dir, delete_pack_dir, diagnose_packs, empty_trash_dir, ensure_data_version,
+    find_checksum_mismatches, get_all_slots, get_slot_path, import_dir, import_folder_to_pack,
+    import_sound_bytes_to_pack, import_sound_to_next_empty_key, import_sound_to_pack,
+    import_sound_to_pack_trimmed, import_split_by_silence_to_pack, optimize_pack_dir,
+    prune_empty_overrides_dir, quarantine_pack_dir, relocate_user_packs, remove_slot_from_pack,
+    remove_slots_from_pack, rename_pack, render_pack_demo_file, repair_original_names,
+    restore_trashed_pack_dir, set_pack_category_enabled, set_pack_category_keys, slot_durations,
+    supported_formats, sync_bundled_packs_dir, sync_bundled_packs_if_changed, trash_pack_dir,
+    trashed_packs, write_pack_checksums_dir, BundledSync, FolderImport, OptimizeReport,
+    PackDiagnosis, PresetKind, RemovedSlots, SlotDuration, SlotInfo, SoundPackEdit,
+    SupportedFormats, SynthParams, TrashedPack, DATA_VERSION, TRASH_DIR,
 };
-use sound_engine::SoundEngine;
-use sound_pack::{discover_all_packs, discover_packs, SoundPack, SoundPackInfo};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use error::KeysoundError;
+use keyboard::{HotkeyMode, KeyCapture, KeyEvent, KeyRemap, LayoutProfile};
+use kira::sound::static_sound::StaticSoundData;
+use sound_engine::{
+    DynamicVolume, EngineState, LoadedPack, RetriggerPolicy, SoundEngine, StatsFormat,
+    ThrottleStats,
+};
+use sound_pack::{
+    bundled_packs_root, choose_default_pack, discover_all_packs, find_unloadable_packs,
+    group_duplicate_packs, load_pack_by_id, resolve_pack_dir, scan_all_packs, sort_packs,
+    validate_manifest_json, Coverage, FlatKey, PackIssue, PackSnapshot, ResolveTrace, SlotDiff,
+    SortKey, SoundPack, SoundPackInfo, TriggerMode, UnloadablePack, DEFAULT_PACK_ID,
+    MANIFEST_VERSION,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant};
 use tauri::{
     menu::{MenuBuilder, MenuItemBuilder},
     tray::TrayIconBuilder,
-    AppHandle, Manager, State,
+    AppHandle, Emitter, Manager, State,
 };
+use tauri_plugin_autostart::ManagerExt;
+
+/// Shared application state
+pub struct AppState {
+    pub engine: Mutex<SoundEngine>,
+    /// Where bundled packs are read from: `soundpacks_dir`, or the read-only
+    /// resource dir when loading in place
+    pub bundled_dir: PathBuf,
+    /// Writable copy of the bundled packs in app data
+    pub soundpacks_dir: PathBuf,
+    /// Relocatable via `set_packs_root`, which holds the write lock while it
+    /// moves the packs; commands that write to user packs hold a read lock
+    pub user_soundpacks_dir: RwLock<PathBuf>,
+    pub resource_dir: PathBuf,
+    /// Diverts the next keypress to `capture_next_key`
+    pub key_capture: Arc<KeyCapture>,
+    /// Layout override applied to listener events before capture and playback
+    pub key_remap: KeyRemap,
+    /// Most recent background failure (listener, decode, audio device), for settings
+    pub last_error: Mutex<Option<String>>,
+    /// Cleared when the keyboard listener thread exits with an error
+    pub listener_running: AtomicBool,
+}
+
+impl AppState {
+    /// Current location of user-created packs
+    pub fn user_soundpacks_dir(&self) -> Result<PathBuf, String> {
+        self.user_soundpacks_dir
+            .read()
+            .map(|dir| dir.clone())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Location of user packs, held so `set_packs_root` can't move them while
+    /// a command writes to one. Don't call `user_soundpacks_dir` while holding it.
+    pub fn lock_user_packs(&self) -> Result<RwLockReadGuard<'_, PathBuf>, String> {
+        self.user_soundpacks_dir.read().map_err(|e| e.to_string())
+    }
+
+    /// Where soft-deleted packs go, next to the packs folder in app data
+    pub fn trash_dir(&self) -> Result<PathBuf, String> {
+        self.soundpacks_dir
+            .parent()
+            .map(|app_data| app_data.join(TRASH_DIR))
+            .ok_or_else(|| "App data folder not found".into())
+    }
+}
+
+/// How long `capture_next_key` waits for a keypress
+const KEY_CAPTURE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Version/build info shown in settings for bug reports
+#[derive(serde::Serialize)]
+pub struct AppInfo {
+    pub version: String,
+    pub tauri_version: String,
+    /// App data layout version (see `ensure_data_version`)
+    pub data_version: u32,
+    /// Newest pack.json schema this build reads
+    pub manifest_version: u32,
+}
+
+/// Tell the frontend the engine state changed (tray actions, other windows, etc.)
+fn emit_engine_state(app: &AppHandle, engine: &SoundEngine) {
+    if let Err(e) = app.emit("engine-state-changed", engine.state()) {
+        log::warn!("Failed to emit engine state: {}", e);
+    }
+    update_tray_tooltip(app, engine);
+}
+
+/// Remember a background failure and tell the frontend about it.
+fn record_error(app: &AppHandle, message: String) {
+    if let Some(state) = app.try_state::<AppState>() {
+        if let Ok(mut last_error) = state.last_error.lock() {
+            *last_error = Some(message.clone());
+        }
+    }
+    if let Err(e) = app.emit("last-error", message) {
+        log::warn!("Failed to emit last error: {}", e);
+    }
+}
+
+/// Pick up any failure the engine hit in the background (see `SoundEngine::take_error`).
+fn record_engine_error(app: &AppHandle, engine: &mut SoundEngine) {
+    if let Some(message) = engine.take_error() {
+        record_error(app, message);
+    }
+}
+
+/// Show the active pack in the tray tooltip, e.g. "KeySound — Cherry MX Blue".
+fn update_tray_tooltip(app: &AppHandle, engine: &SoundEngine) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let mut tooltip = match engine.active_pack() {
+        Some(pack) => format!("KeySound — {}", pack.name),
+        None => "KeySound".to_string(),
+    };
+    if !engine.is_enabled() {
+        tooltip.push_str(" (muted)");
+    }
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        log::warn!("Failed to update tray tooltip: {}", e);
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+async fn get_sound_packs(state: State<'_, AppState>) -> Result<Vec<SoundPackInfo>, String> {
+    let packs = discover_all_packs(&state.bundled_dir, &state.user_soundpacks_dir()?);
+    Ok(packs.iter().map(|p| p.info()).collect())
+}
+
+/// Like `get_sound_packs`, ordered by `sort` (newest, name, size, ...).
+#[tauri::command]
+async fn get_sound_packs_sorted(
+    sort: Option<SortKey>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SoundPackInfo>, String> {
+    let mut packs = discover_all_packs(&state.bundled_dir, &state.user_soundpacks_dir()?);
+    sort_packs(&mut packs, sort.unwrap_or_default());
+    Ok(packs.iter().map(|p| p.info()).collect())
+}
+
+/// Play a pack straight from any folder without installing it (for testing
+/// a pack while authoring it). The pack isn't listed, renamed, or deleted.
+#[tauri::command]
+async fn set_active_pack_from_path(
+    path: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SoundPackInfo, String> {
+    let pack = SoundPack::load_sideloaded(Path::new(path.trim()))?;
+    let info = pack.info();
+    let ticket = {
+        let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+        engine.begin_pack_load(None)
+    };
+
+    let loaded = decode_with_progress_events(&app, pack);
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.install_pack_if_current(ticket, loaded);
+    record_engine_error(&app, &mut engine);
+    emit_engine_state(&app, &engine);
+    Ok(info)
+}
+
+/// Decode a pack, emitting `pack-load-progress` as each file finishes.
+fn decode_with_progress_events(app: &AppHandle, pack: SoundPack) -> LoadedPack {
+    LoadedPack::decode_with_progress(pack, |progress| {
+        if let Err(e) = app.emit("pack-load-progress", progress) {
+            log::warn!("Failed to emit pack load progress: {}", e);
+        }
+    })
+}
+
+/// Find a pack's directory by id: bundled packs first, then user packs.
+fn find_pack_dir(state: &AppState, pack_id: &str) -> Result<PathBuf, String> {
+    resolve_pack_dir(&state.bundled_dir, &state.user_soundpacks_dir()?, pack_id)
+        .ok_or_else(|| format!("Sound pack '{}' not found", pack_id))
+}
+
+/// Switch to an installed pack. Fails with `NotFound`, `LoadFailed` or
+/// `DecodeFailed` so the UI can offer a fix (e.g. re-syncing bundled packs).
+#[tauri::command]
+async fn set_active_pack(
+    pack_id: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), KeysoundError> {
+    let pack = load_pack_by_id(&state.bundled_dir, &state.user_soundpacks_dir()?, &pack_id)?;
+    let ticket = {
+        let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+        engine.begin_pack_load(Some(pack_id.clone()))
+    };
+
+    // Decode before taking the lock so keypresses aren't blocked during the switch
+    let loaded = decode_with_progress_events(&app, pack);
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    if loaded.is_silent() {
+        engine.abandon_pack_load(ticket);
+        return Err(KeysoundError::DecodeFailed(format!(
+            "None of the sounds in '{}' could be decoded",
+            pack_id
+        )));
+    }
+    // A pack chosen after this one replaces it; its own load reports the outcome
+    engine.install_pack_if_current(ticket, loaded);
+    record_engine_error(&app, &mut engine);
+    emit_engine_state(&app, &engine);
+    Ok(())
+}
+
+/// Reload `pack` into the engine if `pack_id` is the active pack (or the one
+/// being switched to). Sounds are decoded outside the engine lock; the result
+/// is dropped if another load started meanwhile.
+fn reload_if_active(state: &AppState, pack_id: &str, pack: SoundPack) -> Result<(), String> {
+    let ticket = {
+        let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+        if engine.target_pack_id().as_deref() != Some(pack_id) {
+            return Ok(());
+        }
+        engine.begin_pack_load(Some(pack_id.to_string()))
+    };
+
+    let loaded = LoadedPack::decode(pack);
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.install_pack_if_current(ticket, loaded);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_volume(volume: f64, app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_volume(volume);
+    emit_engine_state(&app, &engine);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_bypass_cooldown(bypass: bool, state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_bypass_cooldown(bypass);
+    Ok(())
+}
+
+/// Per-key cooldown for release sounds (ms), separate from the keydown one.
+#[tauri::command]
+fn set_keyup_cooldown(ms: u64, app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_keyup_cooldown_ms(ms);
+    settings::save(&app, settings::KEYUP_COOLDOWN_MS, ms)
+}
+
+/// Temporarily scale one category's volume (e.g. "modifiers") to compare it
+/// against other keys; None clears it. Not saved to the pack.
+#[tauri::command]
+fn set_category_gain_override(
+    category: String,
+    gain: Option<f64>,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_category_gain_override(&category, gain);
+    Ok(())
+}
+
+/// Temporarily play only one key while tuning its sound; None plays every key again.
+#[tauri::command]
+fn set_solo_key(key: Option<String>, state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_solo_key(key);
+    Ok(())
+}
+
+/// Sound only the first press of a held key (ignore OS auto-repeat until release).
+#[tauri::command]
+fn set_suppress_held_repeats(
+    suppress: bool,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_suppress_held_repeats(suppress);
+    settings::save(&app, settings::SUPPRESS_HELD_REPEATS, suppress)
+}
+
+#[tauri::command]
+fn get_suppress_held_repeats(state: State<AppState>) -> Result<bool, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.is_suppress_held_repeats())
+}
+
+/// Play sounds for mouse wheel scrolling (packs need a "ScrollUp"/"ScrollDown" override).
+#[tauri::command]
+fn set_scroll_enabled(enabled: bool, app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_scroll_enabled(enabled);
+    settings::save(&app, settings::SCROLL_ENABLED, enabled)
+}
+
+/// Play a single sound when several keys go down at once (chords, pasted input).
+#[tauri::command]
+fn set_burst_protection(
+    enabled: bool,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_burst_protection(enabled);
+    settings::save(&app, settings::BURST_PROTECTION, enabled)
+}
+
+#[tauri::command]
+fn get_burst_protection(state: State<AppState>) -> Result<bool, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.is_burst_protection())
+}
+
+/// Favor latency over features: no volume jitter or dynamic volume, fewer
+/// simultaneous sounds and a small output buffer.
+#[tauri::command]
+fn set_performance_mode(
+    enabled: bool,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_performance_mode(enabled)?;
+    settings::save(&app, settings::PERFORMANCE_MODE, enabled)
+}
+
+#[tauri::command]
+fn get_performance_mode(state: State<AppState>) -> Result<bool, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.is_performance_mode())
+}
+
+/// Silence sounds while a fullscreen app (e.g. a game) has focus. Windows only.
+#[tauri::command]
+fn set_mute_on_fullscreen(mute: bool, app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_mute_on_fullscreen(mute);
+    settings::save(&app, settings::MUTE_ON_FULLSCREEN, mute)
+}
+
+#[tauri::command]
+fn get_mute_on_fullscreen(state: State<AppState>) -> Result<bool, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.is_mute_on_fullscreen())
+}
+
+/// Silence sounds while a screen recorder or streaming app runs. Best-effort:
+/// detects known recorders on Windows and Linux only.
+#[tauri::command]
+fn set_mute_on_recording(mute: bool, app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_mute_on_recording(mute);
+    settings::save(&app, settings::MUTE_ON_RECORDING, mute)
+}
+
+#[tauri::command]
+fn get_mute_on_recording(state: State<AppState>) -> Result<bool, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.is_mute_on_recording())
+}
+
+/// Turn typing-speed volume on or off, keeping the current range.
+#[tauri::command]
+fn set_dynamic_volume(
+    enabled: bool,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<DynamicVolume, String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    let dynamic = DynamicVolume {
+        enabled,
+        ..engine.get_dynamic_volume()
+    };
+    apply_dynamic_volume(&app, &mut engine, dynamic)
+}
+
+/// Multipliers used at slow and fast typing speeds.
+#[tauri::command]
+fn set_dynamic_volume_range(
+    min: f64,
+    max: f64,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<DynamicVolume, String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    let dynamic = DynamicVolume {
+        min,
+        max,
+        ..engine.get_dynamic_volume()
+    };
+    apply_dynamic_volume(&app, &mut engine, dynamic)
+}
+
+fn apply_dynamic_volume(
+    app: &AppHandle,
+    engine: &mut SoundEngine,
+    dynamic: DynamicVolume,
+) -> Result<DynamicVolume, String> {
+    engine.set_dynamic_volume(dynamic);
+    let applied = engine.get_dynamic_volume();
+    settings::save(app, settings::DYNAMIC_VOLUME, applied)?;
+    Ok(applied)
+}
+
+#[tauri::command]
+fn get_dynamic_volume(state: State<AppState>) -> Result<DynamicVolume, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.get_dynamic_volume())
+}
+
+/// Key that turns sound on/off (see `set_hotkey_mode`); None disables it.
+#[tauri::command]
+fn set_hotkey(key: Option<String>, app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let key = key
+        .map(|k| {
+            keyboard::canonical_key_name(k.trim())
+                .map(str::to_string)
+                .ok_or(format!("Unknown key: {}", k))
+        })
+        .transpose()?;
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_hotkey(key.clone());
+    settings::save(&app, settings::HOTKEY, key)
+}
+
+#[tauri::command]
+fn get_hotkey(state: State<AppState>) -> Result<Option<String>, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.get_hotkey())
+}
+
+/// Whether the hotkey toggles sound, plays only while held, or mutes only while held.
+#[tauri::command]
+fn set_hotkey_mode(mode: HotkeyMode, app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_hotkey_mode(mode);
+    emit_engine_state(&app, &engine);
+    settings::save(&app, settings::HOTKEY_MODE, mode)
+}
+
+#[tauri::command]
+fn get_hotkey_mode(state: State<AppState>) -> Result<HotkeyMode, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.get_hotkey_mode())
+}
+
+/// Limit sounds to a group of keys (all, letters only, no numpad, no F-keys).
+#[tauri::command]
+fn set_layout_profile(
+    profile: LayoutProfile,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_layout_profile(profile);
+    settings::save(&app, settings::LAYOUT_PROFILE, profile)
+}
+
+#[tauri::command]
+fn get_layout_profile(state: State<AppState>) -> Result<LayoutProfile, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.get_layout_profile())
+}
+
+/// What a key press does while the same key's last sound is still ringing.
+#[tauri::command]
+fn set_retrigger_policy(
+    policy: RetriggerPolicy,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_retrigger_policy(policy);
+    settings::save(&app, settings::RETRIGGER_POLICY, policy)
+}
+
+#[tauri::command]
+fn get_retrigger_policy(state: State<AppState>) -> Result<RetriggerPolicy, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.get_retrigger_policy())
+}
+
+#[tauri::command]
+fn get_scroll_enabled(state: State<AppState>) -> Result<bool, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.is_scroll_enabled())
+}
+
+/// Remap rdev key names (e.g. "KeyZ" -> "KeyY" on a QWERTZ layout).
+#[tauri::command]
+fn set_key_remap(
+    remap: HashMap<String, String>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), String> {
+    state.key_remap.set(remap)?;
+    settings::save(&app, settings::KEY_REMAP, state.key_remap.get())
+}
+
+#[tauri::command]
+fn get_key_remap(state: State<AppState>) -> HashMap<String, String> {
+    state.key_remap.get()
+}
+
+#[tauri::command]
+fn get_keyup_cooldown(state: State<AppState>) -> Result<u64, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.get_keyup_cooldown_ms())
+}
+
+/// Median time (µs) for the audio backend to accept a sound, for lag debugging.
+#[tauri::command]
+fn measure_play_latency(state: State<AppState>) -> Result<u128, String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.measure_play_latency())
+}
+
+#[tauri::command]
+fn get_volume(state: State<AppState>) -> Result<f64, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.get_volume())
+}
+
+#[tauri::command]
+fn set_play_rate_limit(
+    max_plays: usize,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.set_max_plays_per_window(max_plays);
+    settings::save(&app, settings::MAX_PLAYS_PER_WINDOW, engine.get_max_plays_per_window())
+}
+
+/// Plays vs. cooldown-suppressed repeats, for tuning the repeat cooldown.
+#[tauri::command]
+fn get_throttle_stats(state: State<AppState>) -> Result<ThrottleStats, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.throttle_stats())
+}
+
+#[tauri::command]
+fn reset_throttle_stats(state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.reset_throttle_stats();
+    Ok(())
+}
+
+/// Presses per key since startup.
+#[tauri::command]
+fn get_key_stats(state: State<AppState>) -> Result<HashMap<String, u64>, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.key_counts().clone())
+}
+
+/// Presses per category of the active pack (e.g. "modifiers", "other").
+#[tauri::command]
+fn get_category_stats(state: State<AppState>) -> Result<HashMap<String, u64>, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.category_counts())
+}
+
+/// Save per-key and per-category counts as "csv" or "json".
+#[tauri::command]
+fn export_stats(dst_path: String, format: String, state: State<AppState>) -> Result<(), String> {
+    let format = StatsFormat::parse(&format)?;
+    let stats = state.engine.lock().map_err(|e| e.to_string())?.key_stats();
+    stats.export(&PathBuf::from(dst_path), format)
+}
+
+#[tauri::command]
+fn reset_key_stats(state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.reset_key_counts();
+    Ok(())
+}
+
+/// Most recent background failure, if any (shown in settings).
+#[tauri::command]
+fn get_last_error(state: State<AppState>) -> Result<Option<String>, String> {
+    let last_error = state.last_error.lock().map_err(|e| e.to_string())?;
+    Ok(last_error.clone())
+}
+
+#[tauri::command]
+fn clear_last_error(state: State<AppState>) -> Result<(), String> {
+    let mut last_error = state.last_error.lock().map_err(|e| e.to_string())?;
+    *last_error = None;
+    Ok(())
+}
+
+/// Folder in app data holding the previous copy of each updated bundled pack
+const BUNDLED_BACKUPS_DIR: &str = "bundled-backups";
+
+/// Copy new or newer bundled packs into app data (also done at launch).
+#[tauri::command]
+async fn sync_bundled_packs(state: State<'_, AppState>) -> Result<BundledSync, String> {
+    if state.bundled_dir != state.soundpacks_dir {
+        // Bundled packs are read in place; there is no copy to update
+        return Ok(BundledSync::default());
+    }
+    let src = state.resource_dir.join("resources").join("soundpacks");
+    let backups = state
+        .soundpacks_dir
+        .parent()
+        .map(|app_data| app_data.join(BUNDLED_BACKUPS_DIR))
+        .ok_or("App data folder not found")?;
+    let sync = sync_bundled_packs_dir(&src, &state.soundpacks_dir, &backups)?;
+
+    let active = {
+        let engine = state.engine.lock().map_err(|e| e.to_string())?;
+        engine.managed_pack_id()
+    };
+    if let Some(active) = active.filter(|id| sync.updated.contains(id)) {
+        let pack = SoundPack::load(&state.soundpacks_dir.join(&active))?;
+        reload_if_active(&state, &active, pack)?;
+    }
+    Ok(sync)
+}
+
+/// Read bundled packs straight from the resource dir instead of copying them
+/// into app data. Applies from the next launch.
+#[tauri::command]
+fn set_load_bundled_in_place(in_place: bool, app: AppHandle) -> Result<(), String> {
+    settings::save(&app, settings::LOAD_BUNDLED_IN_PLACE, in_place)
+}
+
+/// Delay (ms) after launch before keypresses make sound. Applies from the next launch.
+#[tauri::command]
+fn set_startup_delay(ms: u64, app: AppHandle) -> Result<(), String> {
+    settings::save(&app, settings::STARTUP_DELAY_MS, ms)
+}
+
+#[tauri::command]
+fn get_startup_delay(app: AppHandle) -> u64 {
+    settings::load(&app, settings::STARTUP_DELAY_MS).unwrap_or(0)
+}
+
+#[tauri::command]
+fn get_play_rate_limit(state: State<AppState>) -> Result<usize, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.get_max_plays_per_window())
+}
+
+#[tauri::command]
+fn toggle_sound(app: AppHandle, state: State<AppState>) -> Result<bool, String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    let enabled = engine.toggle();
+    emit_engine_state(&app, &engine);
+    Ok(enabled)
+}
+
+/// Cut all playing sounds immediately and turn sound off.
+#[tauri::command]
+fn panic_stop(app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.panic_stop();
+    emit_engine_state(&app, &engine);
+    Ok(())
+}
+
+/// Play a short click at the current volume while the volume slider is dragged.
+/// Calls closer together than the feedback cooldown are ignored.
+#[tauri::command]
+fn play_volume_feedback(state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.play_volume_feedback();
+    Ok(())
+}
+
+#[tauri::command]
+fn get_enabled(state: State<AppState>) -> Result<bool, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.is_enabled())
+}
+
+#[tauri::command]
+fn get_engine_state(state: State<AppState>) -> Result<EngineState, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.state())
+}
+
+#[tauri::command]
+fn get_active_pack_id(state: State<AppState>) -> Result<Option<String>, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.active_pack_id())
+}
+
+/// Play a pack slot at a candidate volume multiplier (e.g. while dragging a slider).
+#[tauri::command]
+async fn preview_slot_at(
+    pack_id: String,
+    slot: String,
+    volume: f64,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let pack_dir = find_pack_dir(&state, &pack_id)?;
+    let pack = SoundPack::load(&pack_dir)?;
+    let path = get_slot_path(&pack, &slot).ok_or_else(|| format!("Slot '{}' has no sound", slot))?;
+
+    // Decode outside the engine lock
+    let data = StaticSoundData::from_file(pack_dir.join(path))
+        .map_err(|e| format!("Failed to load sound: {}", e))?;
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.play_preview(&data, volume);
+    Ok(())
+}
+
+/// Wait for the user to press a key and return its name (for per-key assignment).
+/// The captured keypress doesn't make a sound.
+#[tauri::command]
+async fn capture_next_key(state: State<'_, AppState>) -> Result<String, String> {
+    let capture = Arc::clone(&state.key_capture);
+    tauri::async_runtime::spawn_blocking(move || capture.capture(KEY_CAPTURE_TIMEOUT))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Dry-run how a key resolves in a pack (for debugging key mappings).
+#[tauri::command]
+async fn resolve_key_debug(
+    pack_id: String,
+    key_name: String,
+    state: State<'_, AppState>,
+) -> Result<ResolveTrace, String> {
+    let pack = SoundPack::load(&find_pack_dir(&state, &pack_id)?)?;
+    Ok(pack.resolve_trace(&key_name))
+}
+
+/// Check an edited pack.json before saving it. Nothing is written.
+#[tauri::command]
+async fn validate_manifest(
+    json: String,
+    pack_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<PackIssue>, String> {
+    validate_manifest_json(&json, &find_pack_dir(&state, &pack_id)?)
+}
+
+/// Which keys have their own sound in a pack and which only get the default.
+#[tauri::command]
+async fn coverage_report(pack_id: String, state: State<'_, AppState>) -> Result<Coverage, String> {
+    let pack = SoundPack::load(&find_pack_dir(&state, &pack_id)?)?;
+    Ok(pack.coverage(keyboard::KEY_NAMES))
+}
+
+/// The sound, volume, and deciding layer of every key in a pack, after fallbacks.
+#[tauri::command]
+async fn flatten_pack(pack_id: String, state: State<'_, AppState>) -> Result<Vec<FlatKey>, String> {
+    let pack = SoundPack::load(&find_pack_dir(&state, &pack_id)?)?;
+    Ok(pack.flatten(keyboard::KEY_NAMES))
+}
+
+/// Capture a pack's current sound configuration for a later `diff_pack_snapshot`.
+#[tauri::command]
+async fn snapshot_pack(pack_id: String, state: State<'_, AppState>) -> Result<PackSnapshot, String> {
+    let pack = SoundPack::load(&find_pack_dir(&state, &pack_id)?)?;
+    Ok(pack.snapshot())
+}
+
+/// What changed in a pack since `snapshot` was taken.
+#[tauri::command]
+async fn diff_pack_snapshot(
+    pack_id: String,
+    snapshot: PackSnapshot,
+    state: State<'_, AppState>,
+) -> Result<Vec<SlotDiff>, String> {
+    let pack = SoundPack::load(&find_pack_dir(&state, &pack_id)?)?;
+    Ok(snapshot.diff(&pack.snapshot()))
+}
+
+/// Release decoded audio (e.g. while the app sits in the tray for hours).
+#[tauri::command]
+fn free_audio_memory(state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.unload();
+    Ok(())
+}
+
+/// Estimated bytes of decoded audio currently held, for diagnostics.
+#[tauri::command]
+fn loaded_audio_bytes(state: State<AppState>) -> Result<usize, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.loaded_audio_bytes())
+}
+
+/// Re-decode the active pack's audio after `free_audio_memory`.
+#[tauri::command]
+async fn reload_audio(state: State<'_, AppState>) -> Result<(), String> {
+    let reload = {
+        let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+        engine.begin_active_reload()
+    };
+    if let Some((ticket, pack)) = reload {
+        // Decode outside the lock; sideloaded packs reload from their own folder too
+        let loaded = LoadedPack::decode(pack);
+        let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+        engine.install_pack_if_current(ticket, loaded);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_typing_speed_wpm(state: State<AppState>) -> Result<f64, String> {
+    let engine = state.engine.lock().map_err(|e| e.to_string())?;
+    Ok(engine.typing_speed_wpm())
+}
+
+#[tauri::command]
+fn play_sound(key: String, app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    engine.play_key(&key);
+    spawn_requested_reload(&app, &mut engine);
+    Ok(())
+}
+
+/// Re-decode released audio on a background thread once a key asks for it,
+/// so neither the input thread nor the engine lock waits on the decode.
+fn spawn_requested_reload(app: &AppHandle, engine: &mut SoundEngine) {
+    let Some((ticket, pack)) = engine.take_reload_request() else {
+        return;
+    };
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let loaded = LoadedPack::decode(pack);
+        if let Some(state) = app.try_state::<AppState>() {
+            if let Ok(mut engine) = state.engine.lock() {
+                engine.install_pack_if_current(ticket, loaded);
+            }
+        }
+    });
+}
+
+/// Diagnostic: load, validate, and decode-check every pack (bundled and user).
+#[tauri::command]
+async fn diagnose_all_packs(state: State<'_, AppState>) -> Result<Vec<PackDiagnosis>, String> {
+    let user_dir = state.user_soundpacks_dir()?;
+    Ok(diagnose_packs(&[&state.bundled_dir, &user_dir]))
+}
+
+/// Pack folders (bundled and user) skipped by discovery because they failed to load.
+#[tauri::command]
+async fn get_unloadable_packs(state: State<'_, AppState>) -> Result<Vec<UnloadablePack>, String> {
+    let user_dir = state.user_soundpacks_dir()?;
+    Ok(find_unloadable_packs(&[&state.bundled_dir, &user_dir]))
+}
+
+/// Move a broken user pack folder into `quarantine/` so it stops erroring.
+/// Returns the folder's new path.
+#[tauri::command]
+async fn quarantine_pack(dir: String, state: State<'_, AppState>) -> Result<String, String> {
+    let dest = quarantine_pack_dir(&state.lock_user_packs()?, Path::new(&dir))?;
+    Ok(dest.to_string_lossy().into_owned())
+}
+
+/// Diagnostic: list groups of packs (bundled and user) with identical content.
+#[tauri::command]
+async fn find_duplicate_packs(state: State<'_, AppState>) -> Result<Vec<Vec<String>>, String> {
+    Ok(group_duplicate_packs(
+        &state.bundled_dir,
+        &state.user_soundpacks_dir()?,
+    ))
+}
+
+/// Which OS integrations are live, for the diagnostics panel
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct IntegrationStatus {
+    pub single_instance: bool,
+    pub autostart_enabled: bool,
+    pub listener_running: bool,
+}
+
+impl IntegrationStatus {
+    /// A failed autostart query is reported as disabled.
+    fn new(
+        single_instance: bool,
+        autostart: Result<bool, String>,
+        listener_running: bool,
+    ) -> Self {
+        let autostart_enabled = autostart.unwrap_or_else(|e| {
+            log::warn!("Failed to query autostart: {}", e);
+            false
+        });
+        Self {
+            single_instance,
+            autostart_enabled,
+            listener_running,
+        }
+    }
+}
+
+#[tauri::command]
+fn get_integration_status(app: AppHandle, state: State<AppState>) -> IntegrationStatus {
+    IntegrationStatus::new(
+        // Registered unconditionally in `run`
+        true,
+        app.autolaunch().is_enabled().map_err(|e| e.to_string()),
+        state.listener_running.load(Ordering::Relaxed),
+    )
+}
+
+#[tauri::command]
+fn get_app_info() -> AppInfo {
+    AppInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        data_version: DATA_VERSION,
+        manifest_version: MANIFEST_VERSION,
+    }
+}
+
+/// Check bundled packs for updates at launch. When off, only the manual
+/// `sync_bundled_packs` command copies them.
+#[tauri::command]
+fn set_sync_on_startup(enabled: bool, app: AppHandle) -> Result<(), String> {
+    settings::save(&app, settings::SYNC_BUNDLED_ON_STARTUP, enabled)
+}
+
+#[tauri::command]
+fn get_sync_on_startup(app: AppHandle) -> bool {
+    settings::load(&app, settings::SYNC_BUNDLED_ON_STARTUP).unwrap_or(true)
+}
+
+/// Launch hidden in the tray instead of showing the window.
+#[tauri::command]
+fn set_start_minimized(enabled: bool, app: AppHandle) -> Result<(), String> {
+    settings::save(&app, settings::START_MINIMIZED, enabled)
+}
+
+#[tauri::command]
+fn get_start_minimized(app: AppHandle) -> bool {
+    settings::load(&app, settings::START_MINIMIZED).unwrap_or(false)
+}
+
+/// Whether to hide the main window at launch. The very first launch always
+/// shows it, so a new user isn't left with an app that seems not to open.
+fn should_start_hidden(start_minimized: bool, first_run: bool) -> bool {
+    start_minimized && !first_run
+}
+
+#[tauri::command]
+async fn hide_to_tray(app: AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("main") {
+        window.hide().map_err(|e| e.to_string())?;
+        emit_window_visibility(&app, false);
+    }
+    Ok(())
+}
+
+/// Whether the main window is shown (false while hidden to the tray).
+#[tauri::command]
+fn get_window_visible(app: AppHandle) -> Result<bool, String> {
+    match app.get_webview_window("main") {
+        Some(window) => window.is_visible().map_err(|e| e.to_string()),
+        None => Ok(false),
+    }
+}
+
+/// Bring the main window back from the tray and focus it.
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        emit_window_visibility(app, true);
+    }
+}
+
+fn emit_window_visibility(app: &AppHandle, visible: bool) {
+    if let Err(e) = app.emit("window-visibility-changed", visible) {
+        log::warn!("Failed to emit window visibility: {}", e);
+    }
+}
+
+// --- Custom Pack Commands ---
+
+/// Extensions and size limit accepted by sound imports.
+#[tauri::command]
+fn get_supported_formats() -> SupportedFormats {
+    supported_formats()
+}
+
+#[tauri::command]
+async fn create_custom_pack(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<SoundPackInfo, String> {
+    let pack = create_custom_pack_dir(&state.lock_user_packs()?, &state.resource_dir, &name)?;
+    Ok(pack.info())
+}
+
+/// Create a user pack of generated clicks (no sound files needed).
+#[tauri::command]
+async fn create_synth_pack(
+    name: String,
+    params: Option<SynthParams>,
+    state: State<'_, AppState>,
+) -> Result<SoundPackInfo, String> {
+    let pack = create_synth_pack_dir(
+        &state.lock_user_packs()?,
+        &state.resource_dir,
+        &name,
+        params.unwrap_or_default(),
+    )?;
+    Ok(pack.info())
+}
+
+/// Choose whether a slot's sound plays on press, release, or both.
+#[tauri::command]
+async fn set_slot_trigger(
+    pack_id: String,
+    slot: String,
+    mode: TriggerMode,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = apply_slot_trigger(&pack_dir, &slot, mode)?;
+
+    reload_if_active(&state, &pack_id, pack)
+}
+
+/// Delay a key slot's keydown sound by `ms` (0–200) for rhythm effects.
+#[tauri::command]
+async fn set_slot_delay(
+    pack_id: String,
+    slot: String,
+    ms: u64,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = apply_slot_delay(&pack_dir, &slot, ms)?;
+
+    reload_if_active(&state, &pack_id, pack)
+}
+
+/// Render the pack typing a short phrase into a wav at `dst_path`, for sharing.
+#[tauri::command]
+async fn render_pack_demo(
+    pack_id: String,
+    dst_path: PathBuf,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let pack = SoundPack::load(&find_pack_dir(&state, &pack_id)?)?;
+    render_pack_demo_file(&pack, &dst_path)
+}
+
+/// Remember the folder of an imported file for `get_last_import_dir`.
+/// Failing to save it doesn't fail the import.
+fn remember_import_dir(app: &AppHandle, src: &Path) {
+    if let Some(dir) = import_dir(src) {
+        if let Err(e) = settings::save(app, settings::LAST_IMPORT_DIR, dir) {
+            log::warn!("Failed to remember import folder: {}", e);
+        }
+    }
+}
+
+/// Folder the last sound was imported from, to open the file dialog there.
+#[tauri::command]
+fn get_last_import_dir(app: AppHandle) -> Option<PathBuf> {
+    settings::load(&app, settings::LAST_IMPORT_DIR)
+}
+
+/// Import a file onto the next letter/digit key without a sound of its own.
+/// Returns the slot it used (e.g. "key:KeyA").
+#[tauri::command]
+async fn import_to_next_empty_key(
+    pack_id: String,
+    file_path: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let slot = import_sound_to_next_empty_key(&pack_dir, Path::new(&file_path))?;
+    remember_import_dir(&app, Path::new(&file_path));
+    reload_if_active(&state, &pack_id, SoundPack::load(&pack_dir)?)?;
+    Ok(slot)
+}
+
+#[tauri::command]
+async fn import_sound_file(
+    pack_id: String,
+    slot: String,
+    file_path: String,
+    trim_silence: Option<bool>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let src = std::path::Path::new(&file_path);
+    let pack = if trim_silence.unwrap_or(false) {
+        import_sound_to_pack_trimmed(&pack_dir, &slot, src)?
+    } else {
+        import_sound_to_pack(&pack_dir, &slot, src)?
+    };
+    remember_import_dir(&app, src);
+
+    reload_if_active(&state, &pack_id, pack)
+}
+
+#[tauri::command]
+async fn import_sound_bytes(
+    pack_id: String,
+    slot: String,
+    data: Vec<u8>,
+    ext: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = import_sound_bytes_to_pack(&pack_dir, &slot, &data, &ext)?;
+    reload_if_active(&state, &pack_id, pack)
+}
 
-/// Shared application state
-pub struct AppState {
-    pub engine: Mutex<SoundEngine>,
-    pub soundpacks_dir: PathBuf,
-    pub user_soundpacks_dir: PathBuf,
-    pub resource_dir: PathBuf,
+/// Split a recording of several clicks at its silent gaps and import the
+/// pieces into `slots`, in order.
+#[tauri::command]
+async fn import_split_by_silence(
+    pack_id: String,
+    file_path: String,
+    slots: Vec<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let src = Path::new(&file_path);
+    let pack = import_split_by_silence_to_pack(&pack_dir, src, &slots)?;
+    remember_import_dir(&app, src);
+    reload_if_active(&state, &pack_id, pack)
 }
 
-// --- Tauri Commands ---
+/// Import one file and assign it to several keys at once.
+#[tauri::command]
+async fn assign_sound_to_keys(
+    pack_id: String,
+    keys: Vec<String>,
+    file_path: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let src = std::path::Path::new(&file_path);
+    let pack = assign_sound_to_keys_in_pack(&pack_dir, &keys, src)?;
+    remember_import_dir(&app, src);
+
+    reload_if_active(&state, &pack_id, pack)
+}
 
 #[tauri::command]
-async fn get_sound_packs(state: State<'_, AppState>) -> Result<Vec<SoundPackInfo>, String> {
-    let packs = discover_all_packs(&state.soundpacks_dir, &state.user_soundpacks_dir);
-    Ok(packs.iter().map(|p| p.info()).collect())
+async fn remove_sound_slot(
+    pack_id: String,
+    slot: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = remove_slot_from_pack(&pack_dir, &slot, &state.resource_dir)?;
+
+    reload_if_active(&state, &pack_id, pack)
+}
+
+/// Remove several slots at once, reloading the engine only once.
+/// Reports which slots were removed and which were only reset to silence.
+#[tauri::command]
+async fn remove_slots(
+    pack_id: String,
+    slots: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<RemovedSlots, String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let (pack, outcome) = remove_slots_from_pack(&pack_dir, &slots, &state.resource_dir)?;
+
+    reload_if_active(&state, &pack_id, pack)?;
+    Ok(outcome)
 }
 
+/// Delete a custom pack: moved to the trash, or gone for good with `permanent`.
 #[tauri::command]
-async fn set_active_pack(pack_id: String, state: State<'_, AppState>) -> Result<(), String> {
-    // Look in bundled packs first, then user packs
-    let pack_dir = state.soundpacks_dir.join(&pack_id);
-    let pack_dir = if pack_dir.join("pack.json").exists() {
-        pack_dir
+async fn delete_custom_pack(
+    pack_id: String,
+    permanent: Option<bool>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    if !pack_dir.exists() {
+        return Err("Custom pack not found".into());
+    }
+
+    // Refuse to delete bundled packs
+    if state.bundled_dir.join(&pack_id).exists() {
+        return Err("Cannot delete a bundled sound pack".into());
+    }
+
+    // Moved to the trash unless asked otherwise, so it can be restored
+    if permanent.unwrap_or(false) {
+        delete_pack_dir(&pack_dir)?;
     } else {
-        let user_dir = state.user_soundpacks_dir.join(&pack_id);
-        if user_dir.join("pack.json").exists() {
-            user_dir
-        } else {
-            return Err(format!("Sound pack '{}' not found", pack_id));
+        trash_pack_dir(&pack_dir, &state.trash_dir()?)?;
+    }
+
+    // If this was the active pack, switch to the default pack
+    let fallback = {
+        let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+        if engine.managed_pack_id().as_deref() != Some(&pack_id) {
+            return Ok(());
         }
+        let packs = discover_all_packs(&state.bundled_dir, &packs_root);
+        let preferred: Option<String> = settings::load(&app, settings::DEFAULT_PACK_ID);
+        choose_default_pack(packs, preferred.as_deref())
+            .map(|pack| (engine.begin_pack_load(Some(pack.id.clone())), pack))
+    };
+    drop(packs_root);
+    let Some((ticket, pack)) = fallback else {
+        return Ok(());
     };
 
-    let pack = SoundPack::load(&pack_dir)?;
+    // Decode outside the lock, as in `set_active_pack`
+    let fallback_id = pack.id.clone();
+    let loaded = decode_with_progress_events(&app, pack);
     let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
-    engine.load_pack(pack)
+    if loaded.is_silent() {
+        engine.abandon_pack_load(ticket);
+        record_error(
+            &app,
+            format!("None of the sounds in '{}' could be decoded", fallback_id),
+        );
+    } else {
+        engine.install_pack_if_current(ticket, loaded);
+        record_engine_error(&app, &mut engine);
+    }
+    emit_engine_state(&app, &engine);
+    Ok(())
 }
 
 #[tauri::command]
-fn set_volume(volume: f64, state: State<AppState>) -> Result<(), String> {
-    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
-    engine.set_volume(volume);
-    Ok(())
+async fn list_trashed_packs(state: State<'_, AppState>) -> Result<Vec<TrashedPack>, String> {
+    Ok(trashed_packs(&state.trash_dir()?))
 }
 
+/// Put a deleted pack back; returns its id (suffixed if the old one is taken).
 #[tauri::command]
-fn get_volume(state: State<AppState>) -> Result<f64, String> {
-    let engine = state.engine.lock().map_err(|e| e.to_string())?;
-    Ok(engine.get_volume())
+async fn restore_trashed_pack(
+    trash_id: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let pack = restore_trashed_pack_dir(&state.trash_dir()?, &state.lock_user_packs()?, &trash_id)?;
+    Ok(pack.id)
 }
 
+/// Permanently delete all trashed packs. Returns how many were removed.
 #[tauri::command]
-fn toggle_sound(state: State<AppState>) -> Result<bool, String> {
-    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
-    Ok(engine.toggle())
+async fn empty_trash(state: State<'_, AppState>) -> Result<usize, String> {
+    empty_trash_dir(&state.trash_dir()?)
 }
 
+/// Choose the pack loaded at startup and after deleting the active pack.
 #[tauri::command]
-fn get_enabled(state: State<AppState>) -> Result<bool, String> {
-    let engine = state.engine.lock().map_err(|e| e.to_string())?;
-    Ok(engine.is_enabled())
+async fn set_default_pack(
+    pack_id: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    find_pack_dir(&state, &pack_id)?;
+    settings::save(&app, settings::DEFAULT_PACK_ID, &pack_id)
 }
 
 #[tauri::command]
-fn get_active_pack_id(state: State<AppState>) -> Result<Option<String>, String> {
-    let engine = state.engine.lock().map_err(|e| e.to_string())?;
-    Ok(engine.active_pack_id())
+fn get_default_pack(app: AppHandle) -> String {
+    settings::load(&app, settings::DEFAULT_PACK_ID).unwrap_or_else(|| DEFAULT_PACK_ID.to_string())
 }
 
 #[tauri::command]
-fn play_sound(key: String, state: State<AppState>) -> Result<(), String> {
-    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
-    engine.play_key(&key);
+async fn rename_custom_pack(
+    pack_id: String,
+    new_name: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    rename_pack(&pack_dir, &new_name)?;
     Ok(())
 }
 
+/// Rename a custom pack and move it to an id slugified from the new name.
+/// Returns the new id; the active pack and the default pack follow the move.
 #[tauri::command]
-async fn hide_to_tray(app: AppHandle) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("main") {
-        window.hide().map_err(|e| e.to_string())?;
+async fn change_pack_id(
+    pack_id: String,
+    new_name: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack = change_pack_id_dir(&packs_root, &state.bundled_dir, &pack_id, &new_name)?;
+    let new_id = pack.id.clone();
+
+    {
+        let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+        engine.repoint_pack(&pack_id, pack);
+        emit_engine_state(&app, &engine);
     }
+    let preferred: Option<String> = settings::load(&app, settings::DEFAULT_PACK_ID);
+    if preferred.as_deref() == Some(&pack_id) {
+        settings::save(&app, settings::DEFAULT_PACK_ID, &new_id)?;
+    }
+    Ok(new_id)
+}
+
+/// Store a hash of each of the pack's sound files in its pack.json.
+#[tauri::command]
+async fn write_pack_checksums(pack_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = resolve_pack_dir(&state.bundled_dir, &packs_root, &pack_id)
+        .ok_or_else(|| format!("Sound pack '{}' not found", pack_id))?;
+    write_pack_checksums_dir(&pack_dir)?;
     Ok(())
 }
 
-// --- Custom Pack Commands ---
+/// Sound files changed (or gone) since `write_pack_checksums`.
+#[tauri::command]
+async fn verify_pack_integrity(
+    pack_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let pack = SoundPack::load(&find_pack_dir(&state, &pack_id)?)?;
+    Ok(find_checksum_mismatches(&pack))
+}
 
+/// Fix up display file names after manual edits to a pack.
 #[tauri::command]
-async fn create_custom_pack(
-    name: String,
+async fn repair_pack_names(
+    pack_id: String,
     state: State<'_, AppState>,
-) -> Result<SoundPackInfo, String> {
-    let pack = create_custom_pack_dir(
-        &state.user_soundpacks_dir,
-        &state.resource_dir,
-        &name,
-    )?;
-    Ok(pack.info())
+) -> Result<Vec<SlotInfo>, String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = repair_original_names(&pack_dir)?;
+    Ok(get_all_slots(&pack))
 }
 
+/// Length in ms of each configured slot's sound, for waveform displays.
 #[tauri::command]
-async fn import_sound_file(
+async fn get_slot_durations(
     pack_id: String,
-    slot: String,
-    file_path: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let pack_dir = state.user_soundpacks_dir.join(&pack_id);
-    let src = std::path::Path::new(&file_path);
-    let pack = import_sound_to_pack(&pack_dir, &slot, src)?;
+) -> Result<BTreeMap<String, SlotDuration>, String> {
+    let pack = SoundPack::load(&find_pack_dir(&state, &pack_id)?)?;
+    Ok(slot_durations(&pack))
+}
 
-    // Reload if this is the active pack
-    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
-    if engine.active_pack_id().as_deref() == Some(&pack_id) {
-        engine.load_pack(pack)?;
+#[tauri::command]
+async fn get_custom_pack_slots(
+    pack_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<SlotInfo>, String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    if !pack_dir.join("pack.json").exists() {
+        return Err("Custom pack not found".into());
     }
 
-    Ok(())
+    let pack = SoundPack::load(&pack_dir)?;
+    Ok(get_all_slots(&pack))
 }
 
 #[tauri::command]
-async fn remove_sound_slot(
+async fn set_category_enabled(
     pack_id: String,
-    slot: String,
+    category: String,
+    enabled: bool,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let pack_dir = state.user_soundpacks_dir.join(&pack_id);
-    let pack = remove_slot_from_pack(&pack_dir, &slot, &state.resource_dir)?;
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = set_pack_category_enabled(&pack_dir, &category, enabled)?;
 
-    // Reload if active
-    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
-    if engine.active_pack_id().as_deref() == Some(&pack_id) {
-        engine.load_pack(pack)?;
-    }
+    reload_if_active(&state, &pack_id, pack)
+}
 
-    Ok(())
+/// Choose which keys share a category's sound (e.g. add CapsLock to modifiers).
+#[tauri::command]
+async fn set_category_keys(
+    pack_id: String,
+    category: String,
+    keys: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = set_pack_category_keys(&pack_dir, &category, &keys)?;
+    reload_if_active(&state, &pack_id, pack)
 }
 
 #[tauri::command]
-async fn delete_custom_pack(
+async fn set_pack_mapping(
     pack_id: String,
+    mapping: SoundPackEdit,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let pack_dir = state.user_soundpacks_dir.join(&pack_id);
-    if !pack_dir.exists() {
-        return Err("Custom pack not found".into());
-    }
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = apply_pack_mapping(&pack_dir, mapping)?;
 
-    // Refuse to delete bundled packs
-    if state.soundpacks_dir.join(&pack_id).exists() {
-        return Err("Cannot delete a bundled sound pack".into());
-    }
+    reload_if_active(&state, &pack_id, pack)
+}
+
+/// Set a pack's theme color ("#RRGGBB"), or clear it with an empty string.
+#[tauri::command]
+async fn set_pack_color(
+    pack_id: String,
+    color: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    apply_pack_color(&pack_dir, &color)?;
+    Ok(())
+}
 
-    delete_pack_dir(&pack_dir)?;
+/// Full manifest of an installed pack, including fields left out of
+/// `SoundPackInfo` (notes, slot labels, original file names).
+#[tauri::command]
+async fn get_pack_details(pack_id: String, state: State<'_, AppState>) -> Result<SoundPack, String> {
+    SoundPack::load(&find_pack_dir(&state, &pack_id)?)
+}
 
-    // If this was the active pack, switch to default
-    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
-    if engine.active_pack_id().as_deref() == Some(&pack_id) {
-        let default_dir = state.soundpacks_dir.join("default");
-        if default_dir.exists() {
-            if let Ok(pack) = SoundPack::load(&default_dir) {
-                engine.load_pack(pack).ok();
-            }
-        }
-    }
+/// Set how much a user pack's keydown volume varies between presses (0.1 = ±10%).
+#[tauri::command]
+async fn set_volume_jitter(
+    pack_id: String,
+    jitter: f64,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = apply_volume_jitter(&pack_dir, jitter)?;
+    reload_if_active(&state, &pack_id, pack)
+}
 
+/// Replace a user pack's freeform notes.
+#[tauri::command]
+async fn set_pack_notes(
+    pack_id: String,
+    notes: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    apply_pack_notes(&pack_dir, &notes)?;
     Ok(())
 }
 
+/// Turn a user pack's keyup sounds on or off without unassigning them.
 #[tauri::command]
-async fn rename_custom_pack(
+async fn set_keyup_enabled(
     pack_id: String,
-    new_name: String,
+    enabled: bool,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let new_name = new_name.trim().to_string();
-    if new_name.is_empty() {
-        return Err("Pack name cannot be empty".into());
-    }
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = apply_keyup_enabled(&pack_dir, enabled)?;
+    reload_if_active(&state, &pack_id, pack)
+}
 
-    let pack_dir = state.user_soundpacks_dir.join(&pack_id);
-    if !pack_dir.join("pack.json").exists() {
-        return Err("Custom pack not found".into());
-    }
+/// Set up a preset's category overrides (e.g. modifiers, backspace) on a user pack.
+#[tauri::command]
+async fn apply_preset(
+    pack_id: String,
+    preset: PresetKind,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let pack = apply_preset_to_pack(&pack_dir, preset)?;
+    reload_if_active(&state, &pack_id, pack)
+}
+
+/// Import a folder of `<KeyName>.<ext>` files into the matching per-key slots.
+#[tauri::command]
+async fn import_folder(
+    pack_id: String,
+    folder_path: String,
+    state: State<'_, AppState>,
+) -> Result<FolderImport, String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let result = import_folder_to_pack(&pack_dir, &PathBuf::from(folder_path))?;
+    reload_if_active(&state, &pack_id, SoundPack::load(&pack_dir)?)?;
+    Ok(result)
+}
 
-    let mut pack = SoundPack::load(&pack_dir)?;
-    pack.name = new_name;
-    write_pack_json(&pack)
+/// Re-encode a user pack's sounds to wav for uniform decode cost.
+#[tauri::command]
+async fn optimize_pack(
+    pack_id: String,
+    state: State<'_, AppState>,
+) -> Result<OptimizeReport, String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let report = optimize_pack_dir(&pack_dir)?;
+    reload_if_active(&state, &pack_id, SoundPack::load(&pack_dir)?)?;
+    Ok(report)
 }
 
+/// Drop overrides that do nothing (no keys, or no sound/volume/silencing) from
+/// a user pack. Returns the removed overrides as "key:<Key>" / "category:<name>".
 #[tauri::command]
-async fn get_custom_pack_slots(
+async fn prune_empty_overrides(
     pack_id: String,
     state: State<'_, AppState>,
-) -> Result<Vec<SlotInfo>, String> {
-    let pack_dir = state.user_soundpacks_dir.join(&pack_id);
-    if !pack_dir.join("pack.json").exists() {
-        return Err("Custom pack not found".into());
+) -> Result<Vec<String>, String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    let removed = prune_empty_overrides_dir(&pack_dir)?;
+    if !removed.is_empty() {
+        reload_if_active(&state, &pack_id, SoundPack::load(&pack_dir)?)?;
     }
+    Ok(removed)
+}
 
-    let pack = SoundPack::load(&pack_dir)?;
-    Ok(get_all_slots(&pack))
+/// Give a slot a custom display label, or clear it with an empty string.
+#[tauri::command]
+async fn set_slot_label(
+    pack_id: String,
+    slot: String,
+    label: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let packs_root = state.lock_user_packs()?;
+    let pack_dir = packs_root.join(&pack_id);
+    apply_slot_label(&pack_dir, &slot, &label)?;
+    Ok(())
+}
+
+/// Move user packs to a new folder (e.g. another drive) and remember it.
+#[tauri::command]
+async fn set_packs_root(
+    path: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<SoundPackInfo>, String> {
+    let new_dir = PathBuf::from(path.trim());
+    // Held until the move is final, so no command writes to a pack mid-copy
+    let mut packs_root = state
+        .user_soundpacks_dir
+        .write()
+        .map_err(|e| e.to_string())?;
+    let relocation = relocate_user_packs(&packs_root, &new_dir)?;
+    if let Err(e) = settings::save(&app, settings::CUSTOM_PACKS_ROOT, &new_dir) {
+        relocation.undo();
+        return Err(e);
+    }
+    *packs_root = new_dir.clone();
+    relocation.finish();
+
+    // Follow the active pack to its new folder, keeping its decoded sounds
+    let mut engine = state.engine.lock().map_err(|e| e.to_string())?;
+    if let Some(active_id) = engine.managed_pack_id() {
+        let moved_dir = new_dir.join(&active_id);
+        if moved_dir.join("pack.json").exists() {
+            engine.repoint_pack(&active_id, SoundPack::load(&moved_dir)?);
+        }
+    }
+    drop(engine);
+    drop(packs_root);
+
+    let packs = discover_all_packs(&state.bundled_dir, &new_dir);
+    Ok(packs.iter().map(|p| p.info()).collect())
 }
 
 // --- Tray Setup ---
 
+const TRAY_ID: &str = "main";
+
 fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     let toggle = MenuItemBuilder::new("Toggle Sound")
         .id("toggle")
@@ -232,7 +1627,7 @@ fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         .items(&[&toggle, &show, &quit])
         .build()?;
 
-    TrayIconBuilder::new()
+    TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .tooltip("KeySound")
@@ -242,15 +1637,11 @@ fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
                     if let Ok(mut engine) = state.engine.lock() {
                         let enabled = engine.toggle();
                         log::info!("Sound {}", if enabled { "enabled" } else { "disabled" });
+                        emit_engine_state(app, &engine);
                     }
                 }
             }
-            "show" => {
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
-            }
+            "show" => show_main_window(app),
             "quit" => {
                 app.exit(0);
             }
@@ -258,15 +1649,17 @@ fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         })
         .on_tray_icon_event(|tray, event| {
             if let tauri::tray::TrayIconEvent::Click { .. } = event {
-                let app = tray.app_handle();
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
+                show_main_window(tray.app_handle());
             }
         })
         .build(app)?;
 
+    if let Some(state) = app.try_state::<AppState>() {
+        if let Ok(engine) = state.engine.lock() {
+            update_tray_tooltip(app, &engine);
+        }
+    }
+
     Ok(())
 }
 
@@ -277,10 +1670,7 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
             // Focus existing window when second instance is launched
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.show();
-                let _ = window.set_focus();
-            }
+            show_main_window(app);
         }))
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
@@ -291,20 +1681,128 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
             get_sound_packs,
+            get_sound_packs_sorted,
             set_active_pack,
+            set_active_pack_from_path,
             set_volume,
             get_volume,
+            set_bypass_cooldown,
+            set_keyup_cooldown,
+            get_keyup_cooldown,
+            set_solo_key,
+            set_hotkey,
+            get_hotkey,
+            set_hotkey_mode,
+            get_hotkey_mode,
+            set_category_gain_override,
+            set_suppress_held_repeats,
+            get_suppress_held_repeats,
+            set_scroll_enabled,
+            get_scroll_enabled,
+            set_mute_on_fullscreen,
+            get_mute_on_fullscreen,
+            set_burst_protection,
+            get_burst_protection,
+            set_performance_mode,
+            get_performance_mode,
+            set_mute_on_recording,
+            get_mute_on_recording,
+            set_retrigger_policy,
+            get_retrigger_policy,
+            set_layout_profile,
+            get_layout_profile,
+            set_dynamic_volume,
+            set_dynamic_volume_range,
+            get_dynamic_volume,
+            set_key_remap,
+            get_key_remap,
+            set_play_rate_limit,
+            get_play_rate_limit,
+            get_throttle_stats,
+            reset_throttle_stats,
+            get_key_stats,
+            get_category_stats,
+            export_stats,
+            reset_key_stats,
+            get_last_error,
+            clear_last_error,
+            set_load_bundled_in_place,
+            sync_bundled_packs,
+            set_startup_delay,
+            get_startup_delay,
             toggle_sound,
+            panic_stop,
+            play_volume_feedback,
             get_enabled,
+            get_engine_state,
             get_active_pack_id,
+            get_typing_speed_wpm,
+            measure_play_latency,
+            resolve_key_debug,
+            coverage_report,
+            flatten_pack,
+            snapshot_pack,
+            diff_pack_snapshot,
+            validate_manifest,
+            capture_next_key,
+            preview_slot_at,
+            free_audio_memory,
+            loaded_audio_bytes,
+            reload_audio,
             play_sound,
+            find_duplicate_packs,
+            diagnose_all_packs,
+            get_unloadable_packs,
+            quarantine_pack,
+            get_app_info,
+            get_integration_status,
             hide_to_tray,
+            get_window_visible,
+            set_sync_on_startup,
+            get_sync_on_startup,
+            set_start_minimized,
+            get_start_minimized,
+            get_supported_formats,
             create_custom_pack,
+            create_synth_pack,
+            render_pack_demo,
+            set_slot_delay,
+            set_slot_trigger,
             import_sound_file,
+            import_to_next_empty_key,
+            get_last_import_dir,
+            import_sound_bytes,
+            import_split_by_silence,
+            assign_sound_to_keys,
             remove_sound_slot,
+            remove_slots,
             delete_custom_pack,
+            list_trashed_packs,
+            restore_trashed_pack,
+            empty_trash,
+            set_default_pack,
+            get_default_pack,
             rename_custom_pack,
+            change_pack_id,
+            write_pack_checksums,
+            verify_pack_integrity,
             get_custom_pack_slots,
+            get_slot_durations,
+            repair_pack_names,
+            set_category_enabled,
+            set_category_keys,
+            set_pack_mapping,
+            set_pack_color,
+            set_keyup_enabled,
+            get_pack_details,
+            set_pack_notes,
+            set_volume_jitter,
+            set_slot_label,
+            apply_preset,
+            import_folder,
+            optimize_pack,
+            prune_empty_overrides,
+            set_packs_root,
         ])
         .setup(|app| {
             let app_data_dir = app
@@ -312,15 +1810,27 @@ pub fn run() {
                 .app_data_dir()
                 .expect("Failed to get app data dir");
             let soundpacks_dir = app_data_dir.join("soundpacks");
-            let user_soundpacks_dir = app_data_dir.join("user-soundpacks");
+            let user_soundpacks_dir = settings::load(app.handle(), settings::CUSTOM_PACKS_ROOT)
+                .unwrap_or_else(|| app_data_dir.join("user-soundpacks"));
 
             // Create directories
             std::fs::create_dir_all(&soundpacks_dir).ok();
             std::fs::create_dir_all(&user_soundpacks_dir).ok();
 
+            // No data-version.json yet means this is the first launch
+            let first_run = !app_data_dir.join("data-version.json").exists();
+
             // Data versioning / migration
             ensure_data_version(&app_data_dir);
 
+            let start_minimized =
+                settings::load(app.handle(), settings::START_MINIMIZED).unwrap_or(false);
+            if should_start_hidden(start_minimized, first_run) {
+                if let Some(window) = app.get_webview_window("main") {
+                    window.hide().ok();
+                }
+            }
+
             // Sync bundled sound packs to app data dir on launch
             let resource_dir = app
                 .path()
@@ -328,44 +1838,154 @@ pub fn run() {
                 .expect("Failed to get resource dir");
             let bundled_packs = resource_dir.join("resources").join("soundpacks");
 
-            if bundled_packs.exists() {
-                copy_dir_recursive(&bundled_packs, &soundpacks_dir).ok();
+            let load_in_place: bool =
+                settings::load(app.handle(), settings::LOAD_BUNDLED_IN_PLACE).unwrap_or(false);
+            let bundled_dir = bundled_packs_root(&bundled_packs, &soundpacks_dir, load_in_place);
+
+            // Reading in place skips the copy entirely
+            let sync_on_startup: bool =
+                settings::load(app.handle(), settings::SYNC_BUNDLED_ON_STARTUP).unwrap_or(true);
+            if sync_on_startup && bundled_packs.exists() && bundled_dir == soundpacks_dir {
+                let backups = app_data_dir.join(BUNDLED_BACKUPS_DIR);
+                match sync_bundled_packs_if_changed(&bundled_packs, &soundpacks_dir, &backups) {
+                    Ok(Some(sync)) if !sync.updated.is_empty() => {
+                        log::info!("Updated bundled packs: {}", sync.updated.join(", "))
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::error!("Failed to sync bundled packs: {}", e),
+                }
             }
 
             // Initialize sound engine
             let mut engine = SoundEngine::new().expect("Failed to initialize audio engine");
+            if let Some(max_plays) = settings::load(app.handle(), settings::MAX_PLAYS_PER_WINDOW) {
+                engine.set_max_plays_per_window(max_plays);
+            }
+            if let Some(ms) = settings::load(app.handle(), settings::KEYUP_COOLDOWN_MS) {
+                engine.set_keyup_cooldown_ms(ms);
+            }
+            if let Some(enabled) = settings::load(app.handle(), settings::SCROLL_ENABLED) {
+                engine.set_scroll_enabled(enabled);
+            }
+            if let Some(suppress) = settings::load(app.handle(), settings::SUPPRESS_HELD_REPEATS) {
+                engine.set_suppress_held_repeats(suppress);
+            }
+            if let Some(policy) = settings::load(app.handle(), settings::RETRIGGER_POLICY) {
+                engine.set_retrigger_policy(policy);
+            }
+            if let Some(dynamic) = settings::load(app.handle(), settings::DYNAMIC_VOLUME) {
+                engine.set_dynamic_volume(dynamic);
+            }
+            if let Some(mute) = settings::load(app.handle(), settings::MUTE_ON_FULLSCREEN) {
+                engine.set_mute_on_fullscreen(mute);
+            }
+            if let Some(mute) = settings::load(app.handle(), settings::MUTE_ON_RECORDING) {
+                engine.set_mute_on_recording(mute);
+            }
+            if let Some(enabled) = settings::load(app.handle(), settings::BURST_PROTECTION) {
+                engine.set_burst_protection(enabled);
+            }
+            if let Some(profile) = settings::load(app.handle(), settings::LAYOUT_PROFILE) {
+                engine.set_layout_profile(profile);
+            }
+            if let Some(Some(key)) = settings::load(app.handle(), settings::HOTKEY) {
+                engine.set_hotkey(Some(key));
+            }
+            if let Some(mode) = settings::load(app.handle(), settings::HOTKEY_MODE) {
+                engine.set_hotkey_mode(mode);
+            }
+            if settings::load(app.handle(), settings::PERFORMANCE_MODE) == Some(true) {
+                if let Err(e) = engine.set_performance_mode(true) {
+                    log::error!("Failed to enable performance mode: {}", e);
+                }
+            }
 
-            // Load the first available pack (default)
-            let packs = discover_packs(&soundpacks_dir);
-            if let Some(first_pack) = packs.into_iter().next() {
-                log::info!("Loading default sound pack: {}", first_pack.name);
-                if let Err(e) = engine.load_pack(first_pack) {
+            // Load the default pack (user's choice, then "default", then the first found)
+            let (packs, unloadable) = scan_all_packs(&bundled_dir, &user_soundpacks_dir);
+            let preferred: Option<String> = settings::load(app.handle(), settings::DEFAULT_PACK_ID);
+            if let Some(default_pack) = choose_default_pack(packs, preferred.as_deref()) {
+                log::info!("Loading default sound pack: {}", default_pack.name);
+                if let Err(e) = engine.load_pack(default_pack) {
                     log::error!("Failed to load sound pack: {}", e);
                 }
             } else {
-                log::warn!("No sound packs found in {}", soundpacks_dir.display());
+                log::warn!("No sound packs found in {}", bundled_dir.display());
+            }
+
+            let key_remap = KeyRemap::default();
+            if let Some(remap) = settings::load(app.handle(), settings::KEY_REMAP) {
+                if let Err(e) = key_remap.set(remap) {
+                    log::warn!("Ignoring saved key remap: {}", e);
+                }
             }
 
             let state = AppState {
                 engine: Mutex::new(engine),
+                bundled_dir,
                 soundpacks_dir,
-                user_soundpacks_dir,
+                user_soundpacks_dir: RwLock::new(user_soundpacks_dir),
                 resource_dir,
+                key_capture: Arc::new(KeyCapture::default()),
+                key_remap,
+                last_error: Mutex::new(None),
+                listener_running: AtomicBool::new(true),
             };
             app.manage(state);
 
+            if !unloadable.is_empty() {
+                if let Err(e) = app.emit("unloadable-packs", &unloadable) {
+                    log::warn!("Failed to emit unloadable packs: {}", e);
+                }
+                record_error(
+                    app.handle(),
+                    format!("{} sound pack(s) could not be loaded", unloadable.len()),
+                );
+            }
+
             // Setup system tray
             setup_tray(app.handle())?;
 
             // Start keyboard listener and connect to sound engine
-            let key_rx = keyboard::start_listener();
+            let error_handle = app.handle().clone();
+            let key_rx = keyboard::start_listener(move |message| {
+                if let Some(state) = error_handle.try_state::<AppState>() {
+                    state.listener_running.store(false, Ordering::Relaxed);
+                }
+                record_error(&error_handle, message);
+            });
             let app_handle = app.handle().clone();
+            let startup_delay_ms: u64 =
+                settings::load(app.handle(), settings::STARTUP_DELAY_MS).unwrap_or(0);
+            let listen_from = Instant::now() + Duration::from_millis(startup_delay_ms);
 
             std::thread::spawn(move || {
-                while let Ok(key_name) = key_rx.recv() {
+                let discarded = keyboard::drain_until(&key_rx, listen_from);
+                if discarded > 0 {
+                    log::info!("Ignored {} keypresses during startup delay", discarded);
+                }
+                while let Ok(key_event) = key_rx.recv() {
                     if let Some(state) = app_handle.try_state::<AppState>() {
+                        let key_event = state.key_remap.apply(key_event);
+                        if state.key_capture.offer(&key_event) {
+                            continue;
+                        }
                         if let Ok(mut engine) = state.engine.lock() {
-                            engine.play_key(&key_name);
+                            let hotkey = match &key_event {
+                                KeyEvent::Press(key_name) => engine.handle_hotkey(key_name, true),
+                                KeyEvent::Release(key_name) => engine.handle_hotkey(key_name, false),
+                                KeyEvent::Scroll(_) => false,
+                            };
+                            if hotkey {
+                                emit_engine_state(&app_handle, &engine);
+                                continue;
+                            }
+                            match key_event {
+                                KeyEvent::Press(key_name) => engine.play_key(&key_name),
+                                KeyEvent::Release(key_name) => engine.play_key_up(&key_name),
+                                KeyEvent::Scroll(key_name) => engine.play_scroll(&key_name),
+                            }
+                            spawn_requested_reload(&app_handle, &mut engine);
+                            record_engine_error(&app_handle, &mut engine);
                         }
                     }
                 }
@@ -376,3 +1996,33 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_hidden_only_after_first_run() {
+        assert!(should_start_hidden(true, false));
+        // First launch shows the window even if the setting is somehow on
+        assert!(!should_start_hidden(true, true));
+        assert!(!should_start_hidden(false, false));
+        assert!(!should_start_hidden(false, true));
+    }
+
+    #[test]
+    fn test_integration_status_from_flags() {
+        assert_eq!(
+            IntegrationStatus::new(true, Ok(true), false),
+            IntegrationStatus {
+                single_instance: true,
+                autostart_enabled: true,
+                listener_running: false,
+            }
+        );
+        // An autostart query failure reads as disabled
+        let status = IntegrationStatus::new(true, Err("no launch agent".into()), true);
+        assert!(!status.autostart_enabled);
+        assert!(status.listener_running);
+    }
+}
@@ -0,0 +1,188 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::Duration;
+
+/// Reports whether the screen is being shared or recorded. The engine asks
+/// this on each key event while "mute on recording" is on.
+pub trait CaptureProbe: Send {
+    fn is_capturing(&self) -> bool;
+}
+
+/// How often the background thread rescans the process list
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Linux truncates `/proc/<pid>/comm` to this many bytes
+const COMM_LEN: usize = 15;
+
+/// Executable names (lowercase, without extension) of common screen recorders
+/// and streaming tools.
+const RECORDER_PROCESSES: &[&str] = &[
+    "obs",
+    "obs64",
+    "obs32",
+    "streamlabs obs",
+    "xsplit.core",
+    "bandicam",
+    "camtasia",
+    "camrecorder",
+    "sharex",
+    "simplescreenrecorder",
+    "kazam",
+    "peek",
+    "vokoscreenng",
+    "gpu-screen-recorder",
+    "wf-recorder",
+    "kooha",
+];
+
+/// Best-effort check: looks for a known recorder among running processes
+/// (Windows and Linux). Video-call screen sharing isn't detected, and on other
+/// platforms it never reports capturing, so the option does nothing.
+///
+/// The scan runs on a background thread, started by the first query, so key
+/// events only read the latest result. The thread exits once the probe is
+/// dropped.
+#[derive(Default)]
+pub struct SystemCaptureProbe {
+    capturing: Arc<AtomicBool>,
+    poller: OnceLock<()>,
+}
+
+impl CaptureProbe for SystemCaptureProbe {
+    fn is_capturing(&self) -> bool {
+        self.poller
+            .get_or_init(|| spawn_poller(Arc::downgrade(&self.capturing)));
+        self.capturing.load(Ordering::Relaxed)
+    }
+}
+
+/// Rescans the process list every `SCAN_INTERVAL` until `capturing` is dropped.
+fn spawn_poller(capturing: Weak<AtomicBool>) {
+    let spawned = std::thread::Builder::new()
+        .name("capture-probe".into())
+        .spawn(move || loop {
+            let found = process_names().iter().any(|name| is_recorder(name));
+            match capturing.upgrade() {
+                Some(flag) => flag.store(found, Ordering::Relaxed),
+                None => break,
+            }
+            std::thread::sleep(SCAN_INTERVAL);
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to start capture probe thread: {}", e);
+    }
+}
+
+/// Whether a process name (any case, with or without ".exe") is a known recorder.
+/// A name exactly `COMM_LEN` bytes long also matches recorders it's a prefix of,
+/// since Linux cuts longer names short.
+fn is_recorder(process: &str) -> bool {
+    let name = process.trim().to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    RECORDER_PROCESSES
+        .iter()
+        .any(|r| *r == name || (name.len() == COMM_LEN && r.starts_with(name)))
+}
+
+#[cfg(target_os = "linux")]
+fn process_names() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter_map(|e| std::fs::read_to_string(e.path().join("comm")).ok())
+        .collect()
+}
+
+#[cfg(windows)]
+fn process_names() -> Vec<String> {
+    win32::process_names()
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn process_names() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(windows)]
+mod win32 {
+    use std::ffi::c_void;
+
+    type Handle = *mut c_void;
+
+    const TH32CS_SNAPPROCESS: u32 = 0x2;
+    const INVALID_HANDLE_VALUE: Handle = -1isize as Handle;
+    const MAX_PATH: usize = 260;
+
+    #[repr(C)]
+    struct ProcessEntry32W {
+        dw_size: u32,
+        cnt_usage: u32,
+        th32_process_id: u32,
+        th32_default_heap_id: usize,
+        th32_module_id: u32,
+        cnt_threads: u32,
+        th32_parent_process_id: u32,
+        pc_pri_class_base: i32,
+        dw_flags: u32,
+        sz_exe_file: [u16; MAX_PATH],
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateToolhelp32Snapshot(flags: u32, process_id: u32) -> Handle;
+        fn Process32FirstW(snapshot: Handle, entry: *mut ProcessEntry32W) -> i32;
+        fn Process32NextW(snapshot: Handle, entry: *mut ProcessEntry32W) -> i32;
+        fn CloseHandle(handle: Handle) -> i32;
+    }
+
+    /// Executable names of all running processes.
+    pub fn process_names() -> Vec<String> {
+        let mut names = Vec::new();
+        // SAFETY: the snapshot handle is closed before returning and `entry`
+        // is a live local with `dw_size` set as the API requires.
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return names;
+            }
+            let mut entry: ProcessEntry32W = std::mem::zeroed();
+            entry.dw_size = std::mem::size_of::<ProcessEntry32W>() as u32;
+            let mut ok = Process32FirstW(snapshot, &mut entry);
+            while ok != 0 {
+                let len = entry
+                    .sz_exe_file
+                    .iter()
+                    .position(|&c| c == 0)
+                    .unwrap_or(MAX_PATH);
+                names.push(String::from_utf16_lossy(&entry.sz_exe_file[..len]));
+                ok = Process32NextW(snapshot, &mut entry);
+            }
+            CloseHandle(snapshot);
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_recorder_matches_known_names() {
+        assert!(is_recorder("obs64.exe"));
+        assert!(is_recorder("OBS\n"));
+        assert!(is_recorder("SimpleScreenRecorder"));
+        assert!(!is_recorder("firefox"));
+        assert!(!is_recorder("obsidian"));
+    }
+
+    #[test]
+    fn test_is_recorder_matches_truncated_comm_names() {
+        assert!(is_recorder("simplescreenrec\n"));
+        assert!(is_recorder("gpu-screen-reco"));
+        assert!(!is_recorder("simplescreen"));
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{de::DeserializeOwned, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+/// Store file shared with the frontend (`LazyStore("settings.json")`)
+pub const SETTINGS_STORE: &str = "settings.json";
+
+// --- Setting Keys ---
+
+pub const MAX_PLAYS_PER_WINDOW: &str = "maxPlaysPerWindow";
+pub const CUSTOM_PACKS_ROOT: &str = "customPacksRoot";
+pub const STARTUP_DELAY_MS: &str = "startupDelayMs";
+pub const LOAD_BUNDLED_IN_PLACE: &str = "loadBundledInPlace";
+pub const KEYUP_COOLDOWN_MS: &str = "keyupCooldownMs";
+pub const SCROLL_ENABLED: &str = "scrollEnabled";
+pub const SUPPRESS_HELD_REPEATS: &str = "suppressHeldRepeats";
+pub const DEFAULT_PACK_ID: &str = "defaultPackId";
+pub const KEY_REMAP: &str = "keyRemap";
+pub const RETRIGGER_POLICY: &str = "retriggerPolicy";
+pub const START_MINIMIZED: &str = "startMinimized";
+pub const DYNAMIC_VOLUME: &str = "dynamicVolume";
+pub const MUTE_ON_FULLSCREEN: &str = "muteOnFullscreen";
+pub const LAYOUT_PROFILE: &str = "layoutProfile";
+pub const LAST_IMPORT_DIR: &str = "lastImportDir";
+pub const SYNC_BUNDLED_ON_STARTUP: &str = "syncBundledOnStartup";
+pub const MUTE_ON_RECORDING: &str = "muteOnRecording";
+pub const BURST_PROTECTION: &str = "burstProtection";
+pub const HOTKEY: &str = "hotkey";
+pub const HOTKEY_MODE: &str = "hotkeyMode";
+pub const PERFORMANCE_MODE: &str = "performanceMode";
+
+/// Read a persisted setting, returning None if it is missing or malformed.
+pub fn load<T: DeserializeOwned>(app: &AppHandle, key: &str) -> Option<T> {
+    let store = app.store(SETTINGS_STORE).ok()?;
+    store
+        .get(key)
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+/// Persist a setting and flush the store to disk.
+pub fn save<T: Serialize>(app: &AppHandle, key: &str, value: T) -> Result<(), String> {
+    let store = app
+        .store(SETTINGS_STORE)
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    let value =
+        serde_json::to_value(value).map_err(|e| format!("Failed to serialize setting: {}", e))?;
+    store.set(key, value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save settings: {}", e))
+}
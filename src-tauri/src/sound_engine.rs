@@ -1,17 +1,88 @@
+use cpal::BufferSize;
 use kira::{
-    sound::static_sound::StaticSoundData, AudioManager, AudioManagerSettings, Decibels,
-    DefaultBackend,
+    sound::{
+        static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings},
+        PlaybackState,
+    },
+    AudioManager, AudioManagerSettings, Decibels, DefaultBackend, Frame, StartTime, Tween,
 };
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use crate::sound_pack::SoundPack;
+use crate::fullscreen::{FullscreenProbe, SystemFullscreenProbe};
+use crate::keyboard::{HotkeyMode, LayoutProfile};
+use crate::recording::{CaptureProbe, SystemCaptureProbe};
+use crate::sound_pack::{SoundPack, MAX_PACK_VOLUME};
 
 /// Minimum interval between repeated sounds for the same key (ms).
 /// Prevents buzzing/crackling when holding a key down.
 const KEY_REPEAT_COOLDOWN_MS: u128 = 80;
 
+/// A key still "held" this long after its press is assumed to have lost its
+/// release event (some Linux/Wayland setups never send one) and may sound again (ms).
+const HELD_KEY_WATCHDOG_MS: u128 = 1000;
+
+/// Minimum interval between scroll sounds (ms). Wheel events arrive in bursts,
+/// so this is shared by both directions and longer than the key cooldown.
+const SCROLL_COOLDOWN_MS: u128 = 150;
+
+/// Duration of the master volume ramp on the output track (ms), so fast
+/// slider drags glide instead of stepping.
+const VOLUME_RAMP_MS: u64 = 50;
+
+/// Window over which started sounds are counted for global rate limiting (ms).
+const PLAY_RATE_WINDOW_MS: u128 = 10;
+
+/// Default maximum number of sounds started within one rate window.
+/// Plays beyond this are dropped to avoid flooding the audio backend.
+pub const DEFAULT_MAX_PLAYS_PER_WINDOW: usize = 8;
+
+/// With burst protection on, key presses this close after a played one are
+/// collapsed into it (ms). Short enough that fast typing never hits it.
+const BURST_WINDOW_MS: u128 = 5;
+
+/// Output device buffer in performance mode (frames). About 6 ms at 44.1 kHz,
+/// where the device default is often 20 ms or more.
+const PERFORMANCE_BUFFER_FRAMES: u32 = 256;
+
+/// Cap on `max_plays_per_window` in performance mode
+const PERFORMANCE_MAX_PLAYS_PER_WINDOW: usize = 4;
+
+/// Number of recent inter-keystroke intervals used for the typing speed estimate.
+const TYPING_SAMPLE_SIZE: usize = 20;
+
+/// Intervals longer than this are pauses, not typing, and are left out of the estimate (ms).
+const TYPING_PAUSE_MS: u128 = 2000;
+
+/// Bucket in `category_counts` for keys outside every category.
+pub const UNCATEGORIZED: &str = "other";
+
+/// Average characters per word for WPM estimates.
+const CHARS_PER_WORD: f64 = 5.0;
+
+/// Typing speeds (WPM) at which dynamic volume reaches its min and max multiplier.
+const DYNAMIC_VOLUME_SLOW_WPM: f64 = 20.0;
+const DYNAMIC_VOLUME_FAST_WPM: f64 = 100.0;
+
+/// Consecutive `manager.play` failures before the output device is considered lost.
+const PLAY_FAILURE_THRESHOLD: u32 = 3;
+
+/// Minimum time between audio manager rebuild attempts (ms), so a device
+/// that stays broken doesn't cause a rebuild on every keypress.
+const REBUILD_RETRY_MS: u128 = 5000;
+
+/// Minimum interval between volume feedback clicks while dragging the slider (ms).
+const VOLUME_FEEDBACK_COOLDOWN_MS: u128 = 120;
+
+/// Synthetic key name the volume feedback click is throttled under.
+const VOLUME_FEEDBACK_KEY: &str = "__volume_feedback__";
+
+/// Number of test clicks submitted by `measure_play_latency`.
+const LATENCY_SAMPLES: usize = 5;
+
 /// Convert a linear amplitude (0.0-1.0) to decibels
 fn amplitude_to_db(amplitude: f64) -> f64 {
     if amplitude <= 0.0 {
@@ -21,40 +92,312 @@ fn amplitude_to_db(amplitude: f64) -> f64 {
     }
 }
 
-pub struct SoundEngine {
-    manager: AudioManager,
-    /// Pre-loaded sounds: file path -> sound data
-    sounds: HashMap<PathBuf, StaticSoundData>,
-    /// Currently active sound pack
-    active_pack: Option<SoundPack>,
-    /// Master volume (0.0 - 1.0)
-    volume: f64,
-    /// Whether sound is enabled
-    enabled: bool,
-    /// Per-key last play time for repeat throttling
-    last_play: HashMap<String, Instant>,
+/// Small xorshift64* generator for volume jitter; not for anything that
+/// needs real randomness.
+struct JitterRng(u64);
+
+impl JitterRng {
+    fn from_clock() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        // The state must never be zero
+        Self(nanos | 1)
+    }
+
+    /// Uniform value in -1.0..1.0.
+    fn next_signed(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        let bits = self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11;
+        bits as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+    }
 }
 
-impl SoundEngine {
+/// Upper bound for a category gain override
+pub const MAX_CATEGORY_GAIN: f64 = 4.0;
+
+/// Scale `volume` by a random factor in 1.0 ± `jitter`, kept within 0.0..=MAX_PACK_VOLUME.
+fn jittered_volume(volume: f64, jitter: f64, rng: &mut JitterRng) -> f64 {
+    if jitter <= 0.0 {
+        return volume;
+    }
+    (volume * (1.0 + jitter * rng.next_signed())).clamp(0.0, MAX_PACK_VOLUME)
+}
+
+/// A 5 ms decaying click, generated in memory so latency tests don't depend on a pack.
+fn builtin_click() -> StaticSoundData {
+    const SAMPLE_RATE: u32 = 48_000;
+    let frames: Vec<Frame> = (0..SAMPLE_RATE / 200)
+        .map(|i| Frame::from_mono(0.5 * (-(i as f32) / 30.0).exp()))
+        .collect();
+    StaticSoundData {
+        sample_rate: SAMPLE_RATE,
+        frames: frames.into(),
+        settings: StaticSoundSettings::default(),
+        slice: None,
+    }
+}
+
+/// Where the engine sends sounds. `KiraPlayer` plays them on the output
+/// device; tests swap in a recorder to check what would have played.
+pub trait Player: Send {
+    /// Start `data` at `db` decibels of gain. Returns a handle to stop the
+    /// sound with, if the player has one.
+    fn play(
+        &mut self,
+        data: &StaticSoundData,
+        db: f32,
+    ) -> Result<Option<Box<dyn PlayingSound>>, String>;
+
+    /// Move the master volume to `db` decibels over `tween`.
+    fn set_master_volume(&mut self, db: f32, tween: Tween);
+
+    /// Reopen the output (after the device was lost, or to change its buffer):
+    /// a small fixed buffer with `low_latency`, the device default otherwise.
+    fn reconnect(&mut self, low_latency: bool) -> Result<(), String>;
+}
+
+/// A sound started by a `Player`
+pub trait PlayingSound: Send {
+    fn state(&self) -> PlaybackState;
+
+    /// Fade the sound out over `tween` and stop it.
+    fn stop(&mut self, tween: Tween);
+}
+
+impl PlayingSound for StaticSoundHandle {
+    fn state(&self) -> PlaybackState {
+        StaticSoundHandle::state(self)
+    }
+
+    fn stop(&mut self, tween: Tween) {
+        StaticSoundHandle::stop(self, tween)
+    }
+}
+
+/// Plays through kira on the default output device
+pub struct KiraPlayer(AudioManager);
+
+impl KiraPlayer {
     pub fn new() -> Result<Self, String> {
+        Self::with_low_latency(false)
+    }
+
+    /// Player whose device buffer is PERFORMANCE_BUFFER_FRAMES when
+    /// `low_latency` is set, falling back to the device default if the device
+    /// refuses that size.
+    pub fn with_low_latency(low_latency: bool) -> Result<Self, String> {
+        if low_latency {
+            let mut settings = AudioManagerSettings::<DefaultBackend>::default();
+            settings.backend_settings.buffer_size = BufferSize::Fixed(PERFORMANCE_BUFFER_FRAMES);
+            match AudioManager::<DefaultBackend>::new(settings) {
+                Ok(manager) => return Ok(Self(manager)),
+                Err(e) => log::warn!("Small audio buffer unavailable, using default: {}", e),
+            }
+        }
         let manager = AudioManager::<DefaultBackend>::new(AudioManagerSettings::default())
             .map_err(|e| format!("Failed to create audio manager: {}", e))?;
+        Ok(Self(manager))
+    }
+}
 
-        Ok(Self {
-            manager,
-            sounds: HashMap::new(),
-            active_pack: None,
-            volume: 1.0,
-            enabled: true,
-            last_play: HashMap::new(),
-        })
+impl Player for KiraPlayer {
+    fn play(
+        &mut self,
+        data: &StaticSoundData,
+        db: f32,
+    ) -> Result<Option<Box<dyn PlayingSound>>, String> {
+        self.0
+            .play(data.volume(Decibels(db)))
+            .map(|handle| Some(Box::new(handle) as Box<dyn PlayingSound>))
+            .map_err(|e| e.to_string())
     }
 
-    /// Load a sound pack and pre-load all its sound files
-    pub fn load_pack(&mut self, pack: SoundPack) -> Result<(), String> {
-        self.sounds.clear();
-        self.last_play.clear();
+    fn set_master_volume(&mut self, db: f32, tween: Tween) {
+        self.0.main_track().set_volume(Decibels(db), tween);
+    }
+
+    fn reconnect(&mut self, low_latency: bool) -> Result<(), String> {
+        *self = Self::with_low_latency(low_latency)?;
+        Ok(())
+    }
+}
+
+/// Snapshot of the engine's user-facing state, sent to the frontend on change
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineState {
+    pub enabled: bool,
+    pub volume: f64,
+    pub active_pack_id: Option<String>,
+    /// The active pack was loaded from an arbitrary folder, not installed
+    pub sideloaded: bool,
+    /// Playback keeps failing (e.g. the output device was unplugged)
+    pub degraded: bool,
+}
+
+/// How many plays went through vs. were suppressed by the per-key cooldown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ThrottleStats {
+    pub played: u64,
+    pub throttled: u64,
+}
+
+/// Keydown volume that follows typing speed: `min` at DYNAMIC_VOLUME_SLOW_WPM
+/// or slower, rising linearly to `max` at DYNAMIC_VOLUME_FAST_WPM
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DynamicVolume {
+    pub enabled: bool,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Default for DynamicVolume {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min: 0.8,
+            max: 1.2,
+        }
+    }
+}
+
+impl DynamicVolume {
+    /// Multiplier for a typing speed of `wpm` (1.0 when disabled).
+    pub fn multiplier(&self, wpm: f64) -> f64 {
+        if !self.enabled {
+            return 1.0;
+        }
+        let t =
+            (wpm - DYNAMIC_VOLUME_SLOW_WPM) / (DYNAMIC_VOLUME_FAST_WPM - DYNAMIC_VOLUME_SLOW_WPM);
+        self.min + (self.max - self.min) * t.clamp(0.0, 1.0)
+    }
+}
+
+/// What a key press does while the same key's previous sound is still ringing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetriggerPolicy {
+    /// Play on top of the previous sound
+    #[default]
+    Overlap,
+    /// Stop the previous sound, then play
+    Restart,
+    /// Don't play until the previous sound has finished
+    Ignore,
+}
+
+/// Press count of one key and the category it was bucketed into
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyStat {
+    pub count: u64,
+    pub category: String,
+}
+
+/// Per-key and per-category press counts (sorted, so exports are stable)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct KeyStats {
+    pub keys: BTreeMap<String, KeyStat>,
+    pub categories: BTreeMap<String, u64>,
+}
+
+/// File format for `KeyStats::export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsFormat {
+    Csv,
+    Json,
+}
+
+impl StatsFormat {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format.trim().to_lowercase().as_str() {
+            "csv" => Ok(StatsFormat::Csv),
+            "json" => Ok(StatsFormat::Json),
+            other => Err(format!(
+                "Unsupported export format '{}'. Use csv or json.",
+                other
+            )),
+        }
+    }
+}
+
+/// Quote a CSV field per RFC 4180 when it holds a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl KeyStats {
+    /// CSV with one row per key: `key,count,category`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("key,count,category\n");
+        for (key, stat) in &self.keys {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                csv_field(key),
+                stat.count,
+                csv_field(&stat.category)
+            ));
+        }
+        csv
+    }
+
+    /// Write the stats to `dst`. The destination's folder must already exist.
+    pub fn export(&self, dst: &Path, format: StatsFormat) -> Result<(), String> {
+        let folder = dst.parent().filter(|p| !p.as_os_str().is_empty());
+        if folder.is_some_and(|p| !p.is_dir()) {
+            return Err("Destination folder does not exist".into());
+        }
+        let contents = match format {
+            StatsFormat::Csv => self.to_csv(),
+            StatsFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| format!("Failed to serialize stats: {}", e))?,
+        };
+        std::fs::write(dst, contents).map_err(|e| format!("Failed to write stats: {}", e))
+    }
+}
+
+/// A sound pack with all of its sound files decoded, ready to be installed
+/// into the engine.
+pub struct LoadedPack {
+    pack: SoundPack,
+    /// Pre-loaded sounds: file path -> sound data
+    sounds: HashMap<PathBuf, StaticSoundData>,
+    /// Sound files that were missing or failed to decode
+    warnings: Vec<String>,
+}
+
+/// Decoding progress of a pack load: `loaded` of `total` files are done
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoadProgress {
+    pub pack_id: String,
+    pub loaded: usize,
+    pub total: usize,
+}
+
+impl LoadedPack {
+    /// Whether no sound decoded at all, leaving the pack with nothing to play.
+    pub fn is_silent(&self) -> bool {
+        self.sounds.is_empty()
+    }
+
+    /// Decode every sound file referenced by the pack.
+    /// Doesn't touch the engine, so it can run without holding the engine lock.
+    pub fn decode(pack: SoundPack) -> Self {
+        Self::decode_with_progress(pack, |_| {})
+    }
 
+    /// Like `decode`, calling `on_progress` from the decoding threads as each
+    /// file finishes (failed files count as done).
+    pub fn decode_with_progress(
+        pack: SoundPack,
+        on_progress: impl Fn(LoadProgress) + Sync,
+    ) -> Self {
         // Collect all unique sound file paths from the pack
         let mut paths_to_load: Vec<PathBuf> = Vec::new();
 
@@ -89,11 +432,14 @@ impl SoundEngine {
         paths_to_load.dedup();
 
         // Pre-load all sounds in parallel (disk I/O + audio decode)
+        let mut warnings = Vec::new();
         let paths_to_load: Vec<PathBuf> = paths_to_load
             .into_iter()
             .filter(|p| {
                 if !p.exists() {
-                    log::warn!("Sound file not found: {}", p.display());
+                    let warning = format!("Sound file not found: {}", p.display());
+                    log::warn!("{}", warning);
+                    warnings.push(warning);
                     false
                 } else {
                     true
@@ -101,13 +447,21 @@ impl SoundEngine {
             })
             .collect();
 
+        let total = paths_to_load.len();
+        let loaded = AtomicUsize::new(0);
         let results: Vec<_> = std::thread::scope(|s| {
             let handles: Vec<_> = paths_to_load
                 .iter()
                 .map(|path| {
                     let path = path.clone();
+                    let (loaded, on_progress, pack_id) = (&loaded, &on_progress, &pack.id);
                     s.spawn(move || {
                         let result = StaticSoundData::from_file(&path);
+                        on_progress(LoadProgress {
+                            pack_id: pack_id.clone(),
+                            loaded: loaded.fetch_add(1, Ordering::Relaxed) + 1,
+                            total,
+                        });
                         (path, result)
                     })
                 })
@@ -115,106 +469,1135 @@ impl SoundEngine {
             handles.into_iter().map(|h| h.join().unwrap()).collect()
         });
 
+        let mut sounds = HashMap::new();
         for (path, result) in results {
             match result {
                 Ok(data) => {
-                    self.sounds.insert(path, data);
+                    sounds.insert(path, data);
                 }
                 Err(e) => {
-                    log::warn!("Failed to load sound {}: {}", path.display(), e);
+                    let warning = format!("Failed to load sound {}: {}", path.display(), e);
+                    log::warn!("{}", warning);
+                    warnings.push(warning);
                 }
             }
         }
 
+        Self {
+            pack,
+            sounds,
+            warnings,
+        }
+    }
+}
+
+pub struct SoundEngine {
+    player: Box<dyn Player>,
+    /// Pre-loaded sounds: file path -> sound data
+    sounds: HashMap<PathBuf, StaticSoundData>,
+    /// Currently active sound pack
+    active_pack: Option<SoundPack>,
+    /// Master volume (0.0 - 1.0), the target of the main track's volume ramp
+    volume: f64,
+    /// Whether sound is enabled
+    enabled: bool,
+    /// Per-key last play time for repeat throttling
+    last_play: HashMap<String, Instant>,
+    /// Per-key last keyup play time, throttled separately from keydown
+    last_play_up: HashMap<String, Instant>,
+    /// Minimum interval between keyup sounds for the same key (ms)
+    keyup_cooldown_ms: u128,
+    /// Only sound the first press of a held key, ignoring OS auto-repeat until release
+    suppress_held_repeats: bool,
+    /// Keys pressed and not yet released -> time of the press that sounded
+    held_keys: HashMap<String, Instant>,
+    /// While set, only this key makes a sound (tuning aid, not persisted)
+    solo_key: Option<String>,
+    /// Volume multiplier per category, for comparing categories while tuning
+    /// (not persisted, the pack is untouched)
+    category_gains: HashMap<String, f64>,
+    /// Which keys make sound at all
+    layout_profile: LayoutProfile,
+    /// Handling of a key press while that key's last sound still rings
+    retrigger_policy: RetriggerPolicy,
+    /// Last keydown sound per key, kept unless the policy is Overlap
+    ringing: HashMap<String, Box<dyn PlayingSound>>,
+    /// Ringing sounds stopped early by the Restart policy
+    restarted_plays: u64,
+    /// Scale keydown volume with typing speed
+    dynamic_volume: DynamicVolume,
+    /// Source of the pack's per-press volume jitter
+    jitter_rng: JitterRng,
+    /// Other started sounds that may still be playing, for `panic_stop`
+    active_sounds: Vec<Box<dyn PlayingSound>>,
+    /// Stay silent while a fullscreen app (e.g. a game) has focus
+    mute_on_fullscreen: bool,
+    fullscreen_probe: Box<dyn FullscreenProbe>,
+    /// Stay silent while the screen is being recorded or streamed
+    mute_on_recording: bool,
+    capture_probe: Box<dyn CaptureProbe>,
+    /// Play sounds for mouse wheel scrolling (off unless the user opts in)
+    scroll_enabled: bool,
+    /// Time of the last scroll sound, either direction
+    last_scroll: Option<Instant>,
+    /// Collapse near-simultaneous presses (chords, pasted input) into one sound
+    burst_protection: bool,
+    /// Time of the keydown sound that opened the current burst window
+    burst_started: Option<Instant>,
+    /// Favor latency over effects: no jitter or dynamic volume, fewer
+    /// simultaneous plays, small output buffer
+    performance_mode: bool,
+    /// Key that turns sound on/off (never makes a sound itself)
+    hotkey: Option<String>,
+    /// What pressing and releasing the hotkey does
+    hotkey_mode: HotkeyMode,
+    /// Hotkey is down, so OS auto-repeat presses are ignored
+    hotkey_held: bool,
+    /// Start times of recent sounds (global rate limiting across all keys)
+    recent_plays: VecDeque<Instant>,
+    /// Maximum number of sounds started within PLAY_RATE_WINDOW_MS
+    max_plays_per_window: usize,
+    /// Number of plays dropped by the global rate limiter
+    dropped_plays: u64,
+    /// Time of the most recent keypress (any key)
+    last_keypress: Option<Instant>,
+    /// Interval between the two most recent keypresses (ms)
+    last_interval: Option<u128>,
+    /// Recent inter-keystroke intervals (ms), excluding pauses
+    recent_intervals: VecDeque<u128>,
+    /// Presses per key since startup (or the last reset), muted or not
+    key_counts: HashMap<String, u64>,
+    /// Decoded audio was released via `unload`; re-decode on next play
+    unloaded: bool,
+    /// A key arrived while unloaded; see `take_reload_request`
+    reload_requested: bool,
+    /// Play every keypress, including OS auto-repeat (skips the per-key cooldown)
+    bypass_cooldown: bool,
+    /// `Player::play` failures since the last successful play
+    consecutive_play_failures: u32,
+    /// Time of the last audio manager rebuild attempt
+    last_rebuild_attempt: Option<Instant>,
+    /// Number of audio manager rebuild attempts since startup
+    rebuild_attempts: u64,
+    /// Played vs. cooldown-suppressed counts (resettable)
+    throttle_stats: ThrottleStats,
+    /// Most recent background failure not yet picked up via `take_error`
+    pending_error: Option<String>,
+    /// Bumped by every pack load. A decode that finishes after a newer load
+    /// started is stale and `install_pack_if_current` drops it.
+    load_generation: u64,
+    /// Managed id of the pack the newest `begin_pack_load` is decoding
+    /// (Some(None) for a sideloaded pack), until it's installed or abandoned
+    pending_pack: Option<Option<String>>,
+}
+
+impl SoundEngine {
+    pub fn new() -> Result<Self, String> {
+        Ok(Self::with_player(Box::new(KiraPlayer::new()?)))
+    }
+
+    /// Engine that sends its sounds to `player` instead of the output device.
+    pub fn with_player(player: Box<dyn Player>) -> Self {
+        Self {
+            player,
+            sounds: HashMap::new(),
+            active_pack: None,
+            volume: 1.0,
+            enabled: true,
+            last_play: HashMap::new(),
+            last_play_up: HashMap::new(),
+            keyup_cooldown_ms: KEY_REPEAT_COOLDOWN_MS,
+            suppress_held_repeats: false,
+            held_keys: HashMap::new(),
+            solo_key: None,
+            category_gains: HashMap::new(),
+            layout_profile: LayoutProfile::All,
+            retrigger_policy: RetriggerPolicy::Overlap,
+            ringing: HashMap::new(),
+            restarted_plays: 0,
+            dynamic_volume: DynamicVolume::default(),
+            jitter_rng: JitterRng::from_clock(),
+            active_sounds: Vec::new(),
+            mute_on_fullscreen: false,
+            fullscreen_probe: Box::new(SystemFullscreenProbe),
+            mute_on_recording: false,
+            capture_probe: Box::new(SystemCaptureProbe::default()),
+            scroll_enabled: false,
+            last_scroll: None,
+            burst_protection: false,
+            burst_started: None,
+            performance_mode: false,
+            hotkey: None,
+            hotkey_mode: HotkeyMode::Toggle,
+            hotkey_held: false,
+            recent_plays: VecDeque::new(),
+            max_plays_per_window: DEFAULT_MAX_PLAYS_PER_WINDOW,
+            dropped_plays: 0,
+            last_keypress: None,
+            last_interval: None,
+            recent_intervals: VecDeque::new(),
+            key_counts: HashMap::new(),
+            unloaded: false,
+            reload_requested: false,
+            bypass_cooldown: false,
+            consecutive_play_failures: 0,
+            last_rebuild_attempt: None,
+            rebuild_attempts: 0,
+            throttle_stats: ThrottleStats::default(),
+            pending_error: None,
+            load_generation: 0,
+            pending_pack: None,
+        }
+    }
+
+    /// Load a sound pack and pre-load all its sound files
+    pub fn load_pack(&mut self, pack: SoundPack) -> Result<(), String> {
+        self.install_pack(LoadedPack::decode(pack));
+        Ok(())
+    }
+
+    /// Swap in an already-decoded pack. This is cheap, so callers can decode
+    /// with `LoadedPack::decode` first and only hold the engine lock for the swap.
+    pub fn install_pack(&mut self, loaded: LoadedPack) {
+        // Anything still decoding was requested before this pack
+        self.load_generation += 1;
+        self.pending_pack = None;
+        self.sounds = loaded.sounds;
+        self.last_play.clear();
+        self.last_play_up.clear();
+        self.unloaded = false;
+        self.reload_requested = false;
+        if let Some(warning) = loaded.warnings.last() {
+            self.pending_error = Some(warning.clone());
+        }
+
         log::info!(
             "Loaded sound pack '{}' with {} sounds",
-            pack.name,
+            loaded.pack.name,
             self.sounds.len()
         );
+        self.active_pack = Some(loaded.pack);
+    }
+
+    /// Note that `pack_id` (None for a sideloaded pack) is being decoded outside
+    /// the lock. Returns the ticket for `install_pack_if_current`.
+    pub fn begin_pack_load(&mut self, pack_id: Option<String>) -> u64 {
+        self.load_generation += 1;
+        self.pending_pack = Some(pack_id);
+        self.load_generation
+    }
+
+    /// Install a pack decoded since `begin_pack_load` returned `ticket`, unless
+    /// a newer load has started since. Returns whether it was installed.
+    pub fn install_pack_if_current(&mut self, ticket: u64, loaded: LoadedPack) -> bool {
+        if ticket != self.load_generation {
+            log::info!("Dropped stale load of sound pack '{}'", loaded.pack.name);
+            return false;
+        }
+        self.install_pack(loaded);
+        true
+    }
+
+    /// Give up on the load `ticket` (e.g. nothing in it could be decoded).
+    pub fn abandon_pack_load(&mut self, ticket: u64) {
+        if ticket == self.load_generation {
+            self.pending_pack = None;
+        }
+    }
+
+    /// Managed id of the pack that will be active once pending loads finish.
+    pub fn target_pack_id(&self) -> Option<String> {
+        match &self.pending_pack {
+            Some(pack_id) => pack_id.clone(),
+            None => self.managed_pack_id(),
+        }
+    }
+
+    /// Follow the active pack `old_id` to its new id and folder after a move,
+    /// keeping the decoded sounds (re-keyed to the new paths). Returns whether
+    /// `old_id` was active.
+    pub fn repoint_pack(&mut self, old_id: &str, pack: SoundPack) -> bool {
+        let Some(old_base) = self
+            .active_pack
+            .as_ref()
+            .filter(|p| !p.is_sideloaded() && p.id == old_id)
+            .map(|p| p.base_path.clone())
+        else {
+            return false;
+        };
+        self.sounds = std::mem::take(&mut self.sounds)
+            .into_iter()
+            .map(|(path, data)| match path.strip_prefix(&old_base) {
+                Ok(rel) => (pack.base_path.join(rel), data),
+                Err(_) => (path, data),
+            })
+            .collect();
         self.active_pack = Some(pack);
-        Ok(())
+        true
+    }
+
+    /// Release decoded audio while keeping the active pack selected.
+    /// Sounds are decoded again after the next keypress (see
+    /// `take_reload_request`) or via `reload`.
+    pub fn unload(&mut self) {
+        self.sounds = HashMap::new();
+        self.last_play.clear();
+        self.last_play_up.clear();
+        self.unloaded = true;
+        log::info!("Released decoded audio");
+    }
+
+    pub fn is_unloaded(&self) -> bool {
+        self.unloaded
+    }
+
+    /// Pack to re-decode after `unload`, once a key has been pressed since,
+    /// with the ticket for `install_pack_if_current`. Callers decode it off the
+    /// input thread; keys stay silent until it's installed. None while a load
+    /// is already under way.
+    pub fn take_reload_request(&mut self) -> Option<(u64, SoundPack)> {
+        if !self.reload_requested || self.pending_pack.is_some() {
+            return None;
+        }
+        self.reload_requested = false;
+        self.begin_active_reload()
+    }
+
+    /// Start re-decoding the active pack outside the lock: its ticket for
+    /// `install_pack_if_current` and a copy to decode. None with no pack.
+    pub fn begin_active_reload(&mut self) -> Option<(u64, SoundPack)> {
+        let pack = self.active_pack.clone()?;
+        let ticket = self.begin_pack_load((!pack.is_sideloaded()).then(|| pack.id.clone()));
+        Some((ticket, pack))
+    }
+
+    /// Re-decode the active pack's sounds (after `unload`).
+    pub fn reload(&mut self) {
+        if let Some(pack) = self.active_pack.clone() {
+            self.install_pack(LoadedPack::decode(pack));
+        }
     }
 
     /// Play the sound for a keypress.
     /// Throttles repeated plays of the same key to avoid buzzing on key hold.
     pub fn play_key(&mut self, key_name: &str) {
-        if !self.enabled {
+        self.play_key_at(key_name, Instant::now());
+    }
+
+    fn play_key_at(&mut self, key_name: &str, now: Instant) {
+        self.record_keypress(key_name, now);
+
+        if !self.enabled
+            || !self.layout_profile.allows(key_name)
+            || self.is_muted_by_solo(key_name)
+            || self.is_muted_by_fullscreen()
+            || self.is_muted_by_recording()
+        {
             return;
         }
 
-        // Per-key cooldown: skip if same key was played too recently
-        let now = Instant::now();
-        if let Some(last) = self.last_play.get(key_name) {
-            if now.duration_since(*last).as_millis() < KEY_REPEAT_COOLDOWN_MS {
-                return;
+        if self.unloaded {
+            self.reload_requested = true;
+            return;
+        }
+
+        // Held-key suppression, with a watchdog for releases that never arrive
+        if self.suppress_held_repeats && !self.bypass_cooldown {
+            if let Some(pressed) = self.held_keys.get(key_name) {
+                if now.duration_since(*pressed).as_millis() < HELD_KEY_WATCHDOG_MS {
+                    self.throttle_stats.throttled += 1;
+                    return;
+                }
+                log::debug!(
+                    "No release for {} after {}ms, clearing held state",
+                    key_name,
+                    HELD_KEY_WATCHDOG_MS
+                );
             }
+            self.held_keys.insert(key_name.to_string(), now);
         }
 
-        let pack = match &self.active_pack {
-            Some(p) => p,
-            None => return,
-        };
+        // Per-key cooldown: skip if same key was played too recently.
+        // Always on, so repeats are bounded even if held tracking goes wrong.
+        if !self.bypass_cooldown {
+            if let Some(last) = self.last_play.get(key_name) {
+                if now.duration_since(*last).as_millis() < KEY_REPEAT_COOLDOWN_MS {
+                    self.throttle_stats.throttled += 1;
+                    return;
+                }
+            }
+        }
 
-        let sound_path = match pack.resolve_keydown(key_name) {
+        let sound_path = match self
+            .active_pack
+            .as_ref()
+            .filter(|pack| pack.resolve_trigger(key_name).on_press())
+            .and_then(|pack| pack.resolve_keydown(key_name))
+        {
             Some(p) => p,
             None => return,
         };
 
-        let sound_data = match self.sounds.get(&sound_path) {
-            Some(d) => d,
-            None => return,
-        };
-
-        let key_volume = pack.resolve_volume(key_name);
-        let final_volume = self.volume * key_volume;
-        let db = amplitude_to_db(final_volume);
-
-        let data_with_volume = sound_data.volume(Decibels(db as f32));
+        if self.is_in_burst(now) {
+            self.throttle_stats.throttled += 1;
+            return;
+        }
 
-        if let Err(e) = self.manager.play(data_with_volume) {
-            log::error!("Failed to play sound: {}", e);
+        if !self.play_sound(key_name, &sound_path, now, true) {
+            return;
         }
 
-        self.last_play.insert(key_name.to_string(), now);
+        self.burst_started = Some(now);
+        if !self.bypass_cooldown {
+            self.last_play.insert(key_name.to_string(), now);
+        }
     }
 
-    pub fn set_volume(&mut self, volume: f64) {
-        self.volume = volume.clamp(0.0, 1.0);
+    /// Whether burst protection swallows a key press at `now`.
+    fn is_in_burst(&self, now: Instant) -> bool {
+        self.burst_protection
+            && self
+                .burst_started
+                .is_some_and(|at| now.duration_since(at).as_millis() < BURST_WINDOW_MS)
     }
 
-    pub fn get_volume(&self) -> f64 {
-        self.volume
+    /// Play the sound for a key release.
+    /// Throttled per key with its own cooldown, so rapid press/release doesn't buzz.
+    pub fn play_key_up(&mut self, key_name: &str) {
+        self.play_key_up_at(key_name, Instant::now());
     }
 
-    pub fn set_enabled(&mut self, enabled: bool) {
-        self.enabled = enabled;
-    }
+    fn play_key_up_at(&mut self, key_name: &str, now: Instant) {
+        self.held_keys.remove(key_name);
 
-    pub fn is_enabled(&self) -> bool {
-        self.enabled
-    }
+        if !self.enabled
+            || !self.layout_profile.allows(key_name)
+            || self.is_muted_by_solo(key_name)
+            || self.is_muted_by_fullscreen()
+            || self.is_muted_by_recording()
+        {
+            return;
+        }
 
-    pub fn toggle(&mut self) -> bool {
-        self.enabled = !self.enabled;
-        self.enabled
-    }
+        if self.unloaded {
+            self.reload_requested = true;
+            return;
+        }
 
-    pub fn active_pack_id(&self) -> Option<String> {
-        self.active_pack.as_ref().map(|p| p.id.clone())
-    }
+        if !self.bypass_cooldown {
+            if let Some(last) = self.last_play_up.get(key_name) {
+                if now.duration_since(*last).as_millis() < self.keyup_cooldown_ms {
+                    self.throttle_stats.throttled += 1;
+                    return;
+                }
+            }
+        }
 
-    /// Load a sound pack from a directory path
-    pub fn load_pack_from_path(&mut self, pack_dir: &Path) -> Result<(), String> {
-        let pack = SoundPack::load(pack_dir)?;
-        self.load_pack(pack)
-    }
+        // Slots triggered on release play their keydown sound here instead
+        let sound_path = match self.active_pack.as_ref().and_then(|pack| {
+            if pack.resolve_trigger(key_name).on_release() {
+                pack.resolve_keydown(key_name)
+            } else if pack.keyup_enabled {
+                pack.resolve_keyup(key_name)
+            } else {
+                None
+            }
+        }) {
+            Some(p) => p,
+            None => return,
+        };
 
-    /// Check if a key is within cooldown period (would be throttled).
-    pub fn is_key_in_cooldown(&self, key_name: &str) -> bool {
-        if let Some(last) = self.last_play.get(key_name) {
-            Instant::now().duration_since(*last).as_millis() < KEY_REPEAT_COOLDOWN_MS
-        } else {
-            false
+        if !self.play_sound(key_name, &sound_path, now, false) {
+            return;
+        }
+
+        if !self.bypass_cooldown {
+            self.last_play_up.insert(key_name.to_string(), now);
+        }
+    }
+
+    /// Play the sound for a wheel notch ("ScrollUp"/"ScrollDown"), if scroll sounds
+    /// are enabled and the pack has one. Bursts are throttled by SCROLL_COOLDOWN_MS.
+    pub fn play_scroll(&mut self, scroll_key: &str) {
+        self.play_scroll_at(scroll_key, Instant::now());
+    }
+
+    fn play_scroll_at(&mut self, scroll_key: &str, now: Instant) {
+        if !self.enabled
+            || !self.scroll_enabled
+            || self.is_muted_by_solo(scroll_key)
+            || self.is_muted_by_fullscreen()
+            || self.is_muted_by_recording()
+        {
+            return;
+        }
+
+        if self.unloaded {
+            self.reload_requested = true;
+            return;
+        }
+
+        if !self.bypass_cooldown {
+            if let Some(last) = self.last_scroll {
+                if now.duration_since(last).as_millis() < SCROLL_COOLDOWN_MS {
+                    self.throttle_stats.throttled += 1;
+                    return;
+                }
+            }
+        }
+
+        let sound_path = match self
+            .active_pack
+            .as_ref()
+            .and_then(|pack| pack.resolve_scroll(scroll_key))
+        {
+            Some(p) => p,
+            None => return,
+        };
+
+        if self.play_sound(scroll_key, &sound_path, now, false) && !self.bypass_cooldown {
+            self.last_scroll = Some(now);
+        }
+    }
+
+    /// Whether a fullscreen app has focus and the user asked to be quiet then.
+    fn is_muted_by_fullscreen(&self) -> bool {
+        self.mute_on_fullscreen && self.fullscreen_probe.is_fullscreen_focused()
+    }
+
+    /// Whether a screen recording is running and the user asked to be quiet then.
+    fn is_muted_by_recording(&self) -> bool {
+        self.mute_on_recording && self.capture_probe.is_capturing()
+    }
+
+    /// Whether another key is soloed, silencing this one.
+    fn is_muted_by_solo(&self, key_name: &str) -> bool {
+        self.solo_key
+            .as_deref()
+            .is_some_and(|solo| solo != key_name)
+    }
+
+    /// Whether `key_name`'s last keydown sound is still playing.
+    fn is_ringing(&self, key_name: &str) -> bool {
+        self.ringing
+            .get(key_name)
+            .is_some_and(|h| !matches!(h.state(), PlaybackState::Stopping | PlaybackState::Stopped))
+    }
+
+    /// Start a decoded sound at the key's volume. Returns false if nothing was
+    /// submitted (sound not loaded, still ringing under the Ignore policy, or
+    /// dropped by the rate limiter). `keydown` applies the retrigger policy and
+    /// dynamic volume.
+    fn play_sound(
+        &mut self,
+        key_name: &str,
+        sound_path: &Path,
+        now: Instant,
+        keydown: bool,
+    ) -> bool {
+        let retrigger = keydown && self.retrigger_policy != RetriggerPolicy::Overlap;
+        if retrigger
+            && self.retrigger_policy == RetriggerPolicy::Ignore
+            && self.is_ringing(key_name)
+        {
+            self.throttle_stats.throttled += 1;
+            return false;
+        }
+
+        let pack = match &self.active_pack {
+            Some(p) => p,
+            None => return false,
+        };
+
+        // Cheap clone (shared frames) so the engine can be borrowed mutably below
+        let mut sound_data = match self.sounds.get(sound_path) {
+            Some(d) => d.clone(),
+            None => return false,
+        };
+        let delay_ms = pack.resolve_delay_ms(key_name);
+        if keydown && delay_ms > 0 {
+            sound_data = sound_data.start_time(StartTime::Delayed(Duration::from_millis(delay_ms)));
+        }
+
+        // Master volume is applied by the main track
+        let mut key_volume = pack.resolve_volume(key_name);
+        if let Some(gain) = pack
+            .category_of(key_name)
+            .and_then(|category| self.category_gains.get(category))
+        {
+            key_volume = (key_volume * gain).min(MAX_PACK_VOLUME);
+        }
+        let jitter = pack.resolve_volume_jitter(key_name);
+        if keydown && !self.performance_mode {
+            key_volume *= self.dynamic_volume_multiplier();
+            key_volume = jittered_volume(key_volume, jitter, &mut self.jitter_rng);
+        }
+        let db = amplitude_to_db(key_volume) as f32;
+
+        // Global backpressure: drop plays when too many started very recently
+        if !self.acquire_play_slot(now) {
+            self.dropped_plays += 1;
+            log::debug!("Dropped sound for {}: play rate limit reached", key_name);
+            return false;
+        }
+
+        if retrigger
+            && self.retrigger_policy == RetriggerPolicy::Restart
+            && self.is_ringing(key_name)
+        {
+            if let Some(mut previous) = self.ringing.remove(key_name) {
+                previous.stop(Tween::default());
+                self.restarted_plays += 1;
+            }
+        }
+
+        match self.player.play(&sound_data, db) {
+            Ok(handle) => {
+                self.consecutive_play_failures = 0;
+                self.throttle_stats.played += 1;
+                match handle {
+                    Some(handle) if retrigger => {
+                        self.ringing.insert(key_name.to_string(), handle);
+                    }
+                    _ => self.track_sound(handle),
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to play sound: {}", e);
+                self.record_play_failure(now);
+            }
+        }
+        true
+    }
+
+    /// Count a failed play and, once failures pile up, try rebuilding the audio
+    /// manager. Attempts are spaced by REBUILD_RETRY_MS.
+    fn record_play_failure(&mut self, now: Instant) {
+        self.consecutive_play_failures += 1;
+        if self.consecutive_play_failures < PLAY_FAILURE_THRESHOLD {
+            return;
+        }
+        if let Some(last) = self.last_rebuild_attempt {
+            if now.duration_since(last).as_millis() < REBUILD_RETRY_MS {
+                return;
+            }
+        }
+
+        self.last_rebuild_attempt = Some(now);
+        self.rebuild_attempts += 1;
+        log::warn!(
+            "{} consecutive play failures, rebuilding audio manager",
+            self.consecutive_play_failures
+        );
+        self.pending_error = Some(format!(
+            "Audio output failed {} times in a row; reconnecting to the output device",
+            self.consecutive_play_failures
+        ));
+        match self.reconnect_output(self.performance_mode) {
+            Ok(()) => {
+                self.consecutive_play_failures = 0;
+                // Keep playing the decoded sounds meanwhile, but re-decode the
+                // active pack off the input thread (see `take_reload_request`)
+                self.reload_requested = true;
+                log::info!("Audio manager rebuilt");
+            }
+            Err(e) => log::error!("Failed to rebuild audio manager: {}", e),
+        }
+    }
+
+    /// Reopen the output device, with the small buffer if `small_buffer`.
+    /// Used both to recover a lost device and to apply performance mode.
+    fn reconnect_output(&mut self, small_buffer: bool) -> Result<(), String> {
+        self.player.reconnect(small_buffer)?;
+        // Sounds on the old output are gone
+        self.ringing.clear();
+        self.active_sounds.clear();
+        self.apply_master_volume(Tween::default());
+        Ok(())
+    }
+
+    /// Take the most recent background failure (decode warning, device loss), if any.
+    pub fn take_error(&mut self) -> Option<String> {
+        self.pending_error.take()
+    }
+
+    /// Whether playback keeps failing (output device lost and not yet recovered).
+    pub fn is_degraded(&self) -> bool {
+        self.consecutive_play_failures >= PLAY_FAILURE_THRESHOLD
+    }
+
+    /// Track inter-keystroke timing for typing rhythm features.
+    fn record_keypress(&mut self, key_name: &str, now: Instant) {
+        *self.key_counts.entry(key_name.to_string()).or_insert(0) += 1;
+        if let Some(last) = self.last_keypress {
+            let interval = now.duration_since(last).as_millis();
+            self.last_interval = Some(interval);
+            if interval < TYPING_PAUSE_MS {
+                if self.recent_intervals.len() >= TYPING_SAMPLE_SIZE {
+                    self.recent_intervals.pop_front();
+                }
+                self.recent_intervals.push_back(interval);
+            }
+        }
+        self.last_keypress = Some(now);
+    }
+
+    /// Interval between the two most recent keypresses (ms).
+    pub fn last_interval_ms(&self) -> Option<u128> {
+        self.last_interval
+    }
+
+    /// Presses per key.
+    pub fn key_counts(&self) -> &HashMap<String, u64> {
+        &self.key_counts
+    }
+
+    /// Category of the active pack a key belongs to, or UNCATEGORIZED.
+    fn key_category(&self, key_name: &str) -> &str {
+        self.active_pack
+            .as_ref()
+            .and_then(|pack| pack.category_of(key_name))
+            .unwrap_or(UNCATEGORIZED)
+    }
+
+    /// Presses per category of the active pack; keys outside every category
+    /// (or with no pack loaded) count toward UNCATEGORIZED.
+    pub fn category_counts(&self) -> HashMap<String, u64> {
+        let mut counts = HashMap::new();
+        for (key, count) in &self.key_counts {
+            let category = self.key_category(key).to_string();
+            *counts.entry(category).or_insert(0) += count;
+        }
+        counts
+    }
+
+    /// Snapshot of the per-key and per-category counts, for export.
+    pub fn key_stats(&self) -> KeyStats {
+        KeyStats {
+            keys: self
+                .key_counts
+                .iter()
+                .map(|(key, &count)| {
+                    let category = self.key_category(key).to_string();
+                    (key.clone(), KeyStat { count, category })
+                })
+                .collect(),
+            categories: self.category_counts().into_iter().collect(),
+        }
+    }
+
+    pub fn reset_key_counts(&mut self) {
+        self.key_counts.clear();
+    }
+
+    /// Rolling words-per-minute estimate from recent keystroke intervals.
+    pub fn typing_speed_wpm(&self) -> f64 {
+        if self.recent_intervals.is_empty() {
+            return 0.0;
+        }
+        let total: u128 = self.recent_intervals.iter().sum();
+        let avg_ms = total as f64 / self.recent_intervals.len() as f64;
+        if avg_ms <= 0.0 {
+            return 0.0;
+        }
+        60_000.0 / avg_ms / CHARS_PER_WORD
+    }
+
+    /// Current dynamic volume multiplier (1.0 when the mode is off).
+    pub fn dynamic_volume_multiplier(&self) -> f64 {
+        self.dynamic_volume.multiplier(self.typing_speed_wpm())
+    }
+
+    /// Audition a decoded sound at an explicit pack-volume multiplier (clamped
+    /// to 0.0..=MAX_PACK_VOLUME) instead of the stored one. Plays even when sound
+    /// is disabled. Returns the final amplitude used.
+    pub fn play_preview(&mut self, data: &StaticSoundData, volume: f64) -> f64 {
+        let volume = if volume.is_nan() { 1.0 } else { volume };
+        let pack_volume = volume.clamp(0.0, MAX_PACK_VOLUME);
+        let db = amplitude_to_db(pack_volume);
+        match self.player.play(data, db as f32) {
+            Ok(handle) => self.track_sound(handle),
+            Err(e) => log::error!("Failed to play preview: {}", e),
+        }
+        self.volume * pack_volume
+    }
+
+    /// Play a short built-in click at the current master volume, for feedback
+    /// while the volume slider is dragged. Plays even when sound is disabled.
+    /// Returns false if throttled by VOLUME_FEEDBACK_COOLDOWN_MS.
+    pub fn play_volume_feedback(&mut self) -> bool {
+        self.play_volume_feedback_at(Instant::now())
+    }
+
+    fn play_volume_feedback_at(&mut self, now: Instant) -> bool {
+        if let Some(last) = self.last_play.get(VOLUME_FEEDBACK_KEY) {
+            if now.duration_since(*last).as_millis() < VOLUME_FEEDBACK_COOLDOWN_MS {
+                return false;
+            }
+        }
+        self.last_play.insert(VOLUME_FEEDBACK_KEY.to_string(), now);
+        match self.player.play(&builtin_click(), 0.0) {
+            Ok(handle) => self.track_sound(handle),
+            Err(e) => log::error!("Failed to play volume feedback: {}", e),
+        }
+        true
+    }
+
+    /// Keep a handle for `panic_stop`, dropping handles of finished sounds.
+    fn track_sound(&mut self, handle: Option<Box<dyn PlayingSound>>) {
+        self.active_sounds
+            .retain(|h| h.state() != PlaybackState::Stopped);
+        self.active_sounds.extend(handle);
+    }
+
+    /// Cut every sound that is still playing and disable sound. Unlike
+    /// `toggle`, this also silences sounds that already started.
+    pub fn panic_stop(&mut self) {
+        let stop = Tween {
+            duration: Duration::ZERO,
+            ..Default::default()
+        };
+        for mut handle in self.active_sounds.drain(..) {
+            handle.stop(stop);
+        }
+        for (_, mut handle) in self.ringing.drain() {
+            handle.stop(stop);
+        }
+        self.enabled = false;
+        log::info!("Panic stop: all sounds halted");
+    }
+
+    /// Estimated memory (bytes) held by decoded audio: every frame is stored
+    /// as a stereo pair of f32 samples, whatever the source format.
+    pub fn loaded_audio_bytes(&self) -> usize {
+        self.sounds
+            .values()
+            .map(|data| data.frames.len() * std::mem::size_of::<Frame>())
+            .sum()
+    }
+
+    /// Number of started sounds still tracked (finished ones are pruned lazily).
+    pub fn active_sound_count(&self) -> usize {
+        self.active_sounds.len() + self.ringing.len()
+    }
+
+    /// Median time (µs) for `Player::play` to accept a short click.
+    /// Covers app-side submission only, not the audio driver's output latency.
+    pub fn measure_play_latency(&mut self) -> u128 {
+        let click = builtin_click();
+        let mut samples: Vec<u128> = (0..LATENCY_SAMPLES)
+            .map(|_| {
+                let start = Instant::now();
+                if let Err(e) = self.player.play(&click, 0.0) {
+                    log::error!("Failed to play latency test click: {}", e);
+                }
+                start.elapsed().as_micros()
+            })
+            .collect();
+        samples.sort_unstable();
+        samples[samples.len() / 2]
+    }
+
+    /// Record a sound start at `now` if the global rate limit allows it.
+    fn acquire_play_slot(&mut self, now: Instant) -> bool {
+        while let Some(front) = self.recent_plays.front() {
+            if now.duration_since(*front).as_millis() >= PLAY_RATE_WINDOW_MS {
+                self.recent_plays.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let max_plays = if self.performance_mode {
+            self.max_plays_per_window
+                .min(PERFORMANCE_MAX_PLAYS_PER_WINDOW)
+        } else {
+            self.max_plays_per_window
+        };
+        if self.recent_plays.len() >= max_plays {
+            return false;
+        }
+        self.recent_plays.push_back(now);
+        true
+    }
+
+    pub fn set_max_plays_per_window(&mut self, max: usize) {
+        self.max_plays_per_window = max.max(1);
+    }
+
+    pub fn get_max_plays_per_window(&self) -> usize {
+        self.max_plays_per_window
+    }
+
+    /// Number of plays dropped by the global rate limiter since startup.
+    pub fn dropped_plays(&self) -> u64 {
+        self.dropped_plays
+    }
+
+    /// Disable the per-key repeat cooldown (e.g. for demos). Not persisted.
+    pub fn set_bypass_cooldown(&mut self, bypass: bool) {
+        self.bypass_cooldown = bypass;
+    }
+
+    /// Only play `key` (None plays every key again). Doesn't touch the pack.
+    pub fn set_solo_key(&mut self, key: Option<String>) {
+        self.solo_key = key;
+    }
+
+    /// Multiply the volume of keys in `category` by `gain` (clamped to
+    /// 0.0..=MAX_CATEGORY_GAIN); None clears it. Doesn't touch the pack.
+    pub fn set_category_gain_override(&mut self, category: &str, gain: Option<f64>) {
+        match gain {
+            Some(gain) => {
+                let gain = gain.clamp(0.0, MAX_CATEGORY_GAIN);
+                self.category_gains.insert(category.to_string(), gain);
+            }
+            None => {
+                self.category_gains.remove(category);
+            }
+        }
+    }
+
+    /// Set the dynamic volume mode. Bounds are clamped to 0.0..=MAX_PACK_VOLUME
+    /// and swapped if given in the wrong order.
+    pub fn set_dynamic_volume(&mut self, dynamic: DynamicVolume) {
+        let clamp = |v: f64| {
+            if v.is_nan() {
+                1.0
+            } else {
+                v.clamp(0.0, MAX_PACK_VOLUME)
+            }
+        };
+        let (a, b) = (clamp(dynamic.min), clamp(dynamic.max));
+        self.dynamic_volume = DynamicVolume {
+            enabled: dynamic.enabled,
+            min: a.min(b),
+            max: a.max(b),
+        };
+    }
+
+    pub fn get_dynamic_volume(&self) -> DynamicVolume {
+        self.dynamic_volume
+    }
+
+    pub fn set_layout_profile(&mut self, profile: LayoutProfile) {
+        self.layout_profile = profile;
+    }
+
+    pub fn get_layout_profile(&self) -> LayoutProfile {
+        self.layout_profile
+    }
+
+    pub fn set_retrigger_policy(&mut self, policy: RetriggerPolicy) {
+        self.retrigger_policy = policy;
+        self.ringing.clear();
+    }
+
+    pub fn get_retrigger_policy(&self) -> RetriggerPolicy {
+        self.retrigger_policy
+    }
+
+    /// Number of ringing sounds cut short by the Restart policy
+    pub fn restarted_plays(&self) -> u64 {
+        self.restarted_plays
+    }
+
+    /// Sound only the first press of a held key instead of throttled auto-repeat.
+    pub fn set_suppress_held_repeats(&mut self, suppress: bool) {
+        self.suppress_held_repeats = suppress;
+        self.held_keys.clear();
+    }
+
+    pub fn is_suppress_held_repeats(&self) -> bool {
+        self.suppress_held_repeats
+    }
+
+    pub fn set_scroll_enabled(&mut self, enabled: bool) {
+        self.scroll_enabled = enabled;
+    }
+
+    pub fn is_scroll_enabled(&self) -> bool {
+        self.scroll_enabled
+    }
+
+    /// Silence key and scroll sounds while a fullscreen app has focus
+    /// (Windows only; elsewhere this has no effect).
+    pub fn set_mute_on_fullscreen(&mut self, mute: bool) {
+        self.mute_on_fullscreen = mute;
+    }
+
+    pub fn is_mute_on_fullscreen(&self) -> bool {
+        self.mute_on_fullscreen
+    }
+
+    /// Silence key and scroll sounds while the screen is recorded or streamed
+    pub fn set_mute_on_recording(&mut self, mute: bool) {
+        self.mute_on_recording = mute;
+    }
+
+    pub fn is_mute_on_recording(&self) -> bool {
+        self.mute_on_recording
+    }
+
+    /// Play one sound for presses arriving within BURST_WINDOW_MS of each other
+    pub fn set_burst_protection(&mut self, enabled: bool) {
+        self.burst_protection = enabled;
+    }
+
+    pub fn is_burst_protection(&self) -> bool {
+        self.burst_protection
+    }
+
+    /// Turn performance mode on or off, reopening the output with the matching
+    /// buffer size. While on, keydowns skip jitter and dynamic volume and the
+    /// plays per window are capped; the user's own settings are left as they are.
+    pub fn set_performance_mode(&mut self, enabled: bool) -> Result<(), String> {
+        self.reconnect_output(enabled)?;
+        self.performance_mode = enabled;
+        Ok(())
+    }
+
+    pub fn is_performance_mode(&self) -> bool {
+        self.performance_mode
+    }
+
+    /// Key that turns sound on/off per the hotkey mode; None disables it
+    pub fn set_hotkey(&mut self, key: Option<String>) {
+        self.hotkey = key;
+        self.hotkey_held = false;
+    }
+
+    pub fn get_hotkey(&self) -> Option<String> {
+        self.hotkey.clone()
+    }
+
+    /// Switching to a push mode puts sound in that mode's resting state
+    pub fn set_hotkey_mode(&mut self, mode: HotkeyMode) {
+        self.hotkey_mode = mode;
+        self.hotkey_held = false;
+        if let Some(enabled) = mode.resting_enabled() {
+            self.enabled = enabled;
+        }
+    }
+
+    pub fn get_hotkey_mode(&self) -> HotkeyMode {
+        self.hotkey_mode
+    }
+
+    /// Apply a press (`pressed`) or release of `key_name` if it is the hotkey.
+    /// Returns whether it was, in which case it must not make a sound.
+    pub fn handle_hotkey(&mut self, key_name: &str, pressed: bool) -> bool {
+        if self.hotkey.as_deref() != Some(key_name) {
+            return false;
+        }
+        // OS auto-repeat sends more presses while held; only the first counts
+        if pressed == self.hotkey_held {
+            return true;
+        }
+        self.hotkey_held = pressed;
+        self.enabled = self.hotkey_mode.next_enabled(pressed, self.enabled);
+        true
+    }
+
+    /// Set the per-key keyup cooldown (ms). 0 disables it.
+    pub fn set_keyup_cooldown_ms(&mut self, ms: u64) {
+        self.keyup_cooldown_ms = ms as u128;
+    }
+
+    pub fn get_keyup_cooldown_ms(&self) -> u64 {
+        self.keyup_cooldown_ms as u64
+    }
+
+    pub fn throttle_stats(&self) -> ThrottleStats {
+        self.throttle_stats
+    }
+
+    pub fn reset_throttle_stats(&mut self) {
+        self.throttle_stats = ThrottleStats::default();
+    }
+
+    /// Ramp the master volume to `volume` over VOLUME_RAMP_MS.
+    pub fn set_volume(&mut self, volume: f64) {
+        self.volume = volume.clamp(0.0, 1.0);
+        self.apply_master_volume(Tween {
+            duration: Duration::from_millis(VOLUME_RAMP_MS),
+            ..Default::default()
+        });
+    }
+
+    /// Target master volume; the output may still be ramping toward it.
+    pub fn get_volume(&self) -> f64 {
+        self.volume
+    }
+
+    /// Move the main track's volume to the current master volume.
+    fn apply_master_volume(&mut self, tween: Tween) {
+        let db = amplitude_to_db(self.volume) as f32;
+        self.player.set_master_volume(db, tween);
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn toggle(&mut self) -> bool {
+        self.enabled = !self.enabled;
+        self.enabled
+    }
+
+    pub fn active_pack(&self) -> Option<&SoundPack> {
+        self.active_pack.as_ref()
+    }
+
+    pub fn active_pack_id(&self) -> Option<String> {
+        self.active_pack.as_ref().map(|p| p.id.clone())
+    }
+
+    /// Active pack id, unless the pack is sideloaded. Installed packs that
+    /// happen to share a sideloaded pack's id must not replace it.
+    pub fn managed_pack_id(&self) -> Option<String> {
+        self.active_pack
+            .as_ref()
+            .filter(|p| !p.is_sideloaded())
+            .map(|p| p.id.clone())
+    }
+
+    pub fn state(&self) -> EngineState {
+        EngineState {
+            enabled: self.enabled,
+            volume: self.volume,
+            active_pack_id: self.active_pack_id(),
+            sideloaded: self.active_pack.as_ref().is_some_and(|p| p.is_sideloaded()),
+            degraded: self.is_degraded(),
+        }
+    }
+
+    /// Load a sound pack from a directory path
+    pub fn load_pack_from_path(&mut self, pack_dir: &Path) -> Result<(), String> {
+        let pack = SoundPack::load(pack_dir)?;
+        self.load_pack(pack)
+    }
+
+    /// Check if a key is within cooldown period (would be throttled).
+    pub fn is_key_in_cooldown(&self, key_name: &str) -> bool {
+        if let Some(last) = self.last_play.get(key_name) {
+            Instant::now().duration_since(*last).as_millis() < KEY_REPEAT_COOLDOWN_MS
+        } else {
+            false
+        }
+    }
+
+    /// Check if a key's release is within the keyup cooldown period (for testing).
+    #[cfg(test)]
+    fn is_key_up_in_cooldown(&self, key_name: &str) -> bool {
+        if let Some(last) = self.last_play_up.get(key_name) {
+            Instant::now().duration_since(*last).as_millis() < self.keyup_cooldown_ms
+        } else {
+            false
         }
     }
 
@@ -224,137 +1607,1564 @@ impl SoundEngine {
         self.last_play.insert(key_name.to_string(), Instant::now());
     }
 
-    /// Record a key play at a specific instant (for testing).
-    #[cfg(test)]
-    fn record_key_play_at(&mut self, key_name: &str, at: Instant) {
-        self.last_play.insert(key_name.to_string(), at);
+    /// Record a key play at a specific instant (for testing).
+    #[cfg(test)]
+    fn record_key_play_at(&mut self, key_name: &str, at: Instant) {
+        self.last_play.insert(key_name.to_string(), at);
+    }
+
+    /// Record a keyup play at a specific instant (for testing).
+    #[cfg(test)]
+    fn record_key_up_play_at(&mut self, key_name: &str, at: Instant) {
+        self.last_play_up.insert(key_name.to_string(), at);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_pack::{change_pack_id_dir, generate_silence_wav};
+    use crate::sound_pack::{TriggerMode, MAX_KEY_DELAY_MS};
+    use std::fs;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Write `manifest` as pack.json in `pack_dir`, generating a silence wav
+    /// for every sound it references.
+    fn write_test_pack(pack_dir: &Path, manifest: &serde_json::Value) {
+        let pack: SoundPack = serde_json::from_value(manifest.clone()).unwrap();
+        for path in pack.sound_paths() {
+            let path = pack_dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            generate_silence_wav(&path, 1).unwrap();
+        }
+        fs::write(pack_dir.join("pack.json"), manifest.to_string()).unwrap();
+    }
+
+    /// Create an engine with `manifest` written to `dir/<id>` and loaded.
+    fn engine_with_manifest(dir: &Path, manifest: serde_json::Value) -> SoundEngine {
+        let pack_dir = dir.join(manifest["id"].as_str().unwrap());
+        write_test_pack(&pack_dir, &manifest);
+
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        engine.load_pack_from_path(&pack_dir).unwrap();
+        engine
+    }
+
+    /// Create an engine with a pack whose default sound is a generated silence wav.
+    fn engine_with_pack(dir: &Path) -> SoundEngine {
+        engine_with_manifest(
+            dir,
+            serde_json::json!({
+                "id": "test",
+                "name": "Test",
+                "defaults": { "keydown": "sounds/keydown.wav" }
+            }),
+        )
+    }
+
+    /// Player that records the gain and start time of every play instead of
+    /// making a sound
+    #[derive(Clone, Default)]
+    struct RecordingPlayer {
+        plays: Arc<Mutex<Vec<(f32, StartTime)>>>,
+        reconnects: Arc<AtomicUsize>,
+        /// Fail every play, as if the output device was unplugged
+        failing: Arc<AtomicBool>,
+        /// Fail every reconnect, as if no output device could be opened
+        reconnect_failing: Arc<AtomicBool>,
+        /// Whether each play's sound has been stopped, in play order
+        stopped: Arc<Mutex<Vec<bool>>>,
+    }
+
+    /// Handle to a recorded play; it rings until stopped or `finish_all`
+    struct RecordedSound {
+        index: usize,
+        stopped: Arc<Mutex<Vec<bool>>>,
+    }
+
+    impl PlayingSound for RecordedSound {
+        fn state(&self) -> PlaybackState {
+            if self.stopped.lock().unwrap()[self.index] {
+                PlaybackState::Stopped
+            } else {
+                PlaybackState::Playing
+            }
+        }
+
+        fn stop(&mut self, _tween: Tween) {
+            self.stopped.lock().unwrap()[self.index] = true;
+        }
+    }
+
+    impl RecordingPlayer {
+        fn plays(&self) -> Vec<f32> {
+            self.plays
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(db, _)| *db)
+                .collect()
+        }
+
+        fn start_times(&self) -> Vec<StartTime> {
+            self.plays
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(_, at)| *at)
+                .collect()
+        }
+
+        fn reconnects(&self) -> usize {
+            self.reconnects.load(Ordering::Relaxed)
+        }
+
+        fn set_failing(&self, failing: bool) {
+            self.failing.store(failing, Ordering::Relaxed);
+        }
+
+        fn set_reconnect_failing(&self, failing: bool) {
+            self.reconnect_failing.store(failing, Ordering::Relaxed);
+        }
+
+        fn stopped(&self) -> Vec<bool> {
+            self.stopped.lock().unwrap().clone()
+        }
+
+        /// Let every recorded sound run to its end.
+        fn finish_all(&self) {
+            self.stopped.lock().unwrap().fill(true);
+        }
+    }
+
+    impl Player for RecordingPlayer {
+        fn play(
+            &mut self,
+            data: &StaticSoundData,
+            db: f32,
+        ) -> Result<Option<Box<dyn PlayingSound>>, String> {
+            if self.failing.load(Ordering::Relaxed) {
+                return Err("Output device lost".into());
+            }
+            self.plays
+                .lock()
+                .unwrap()
+                .push((db, data.settings.start_time));
+            let mut stopped = self.stopped.lock().unwrap();
+            stopped.push(false);
+            Ok(Some(Box::new(RecordedSound {
+                index: stopped.len() - 1,
+                stopped: self.stopped.clone(),
+            })))
+        }
+
+        fn set_master_volume(&mut self, _db: f32, _tween: Tween) {}
+
+        fn reconnect(&mut self, _low_latency: bool) -> Result<(), String> {
+            self.reconnects.fetch_add(1, Ordering::Relaxed);
+            if self.reconnect_failing.load(Ordering::Relaxed) {
+                return Err("no output device".into());
+            }
+            Ok(())
+        }
+    }
+
+    /// Engine with a pack that has keydown and keyup sounds, playing into a recorder.
+    fn recording_engine(dir: &Path) -> (SoundEngine, RecordingPlayer) {
+        let pack_dir = dir.join("rec");
+        let manifest = serde_json::json!({
+            "id": "rec",
+            "name": "Recorded",
+            "defaults": { "keydown": "sounds/down.wav", "keyup": "sounds/up.wav", "volume": 0.5 },
+            "key_overrides": { "KeyD": { "keydown": null, "keyup": null, "volume": null, "delay_ms": 500 } },
+            "category_overrides": { "modifiers": { "keys": ["ShiftLeft", "ShiftRight"] } }
+        });
+        write_test_pack(&pack_dir, &manifest);
+
+        let player = RecordingPlayer::default();
+        let mut engine = SoundEngine::with_player(Box::new(player.clone()));
+        engine.load_pack_from_path(&pack_dir).unwrap();
+        (engine, player)
+    }
+
+    #[test]
+    fn test_player_sees_keydown_cooldown() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        let now = Instant::now();
+
+        engine.play_key_at("KeyA", now);
+        engine.play_key_at("KeyA", now + Duration::from_millis(20));
+        assert_eq!(player.plays().len(), 1);
+
+        let after = now + Duration::from_millis(KEY_REPEAT_COOLDOWN_MS as u64);
+        engine.play_key_at("KeyA", after);
+        assert_eq!(player.plays().len(), 2);
+        // Pack volume 0.5 reaches the player as about -6 dB
+        assert!(player.plays().iter().all(|db| (db + 6.02).abs() < 0.01));
+    }
+
+    #[test]
+    fn test_player_keydown_and_keyup_cool_down_separately() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        let now = Instant::now();
+
+        engine.play_key_at("KeyA", now);
+        engine.play_key_up_at("KeyA", now);
+        assert_eq!(player.plays().len(), 2);
+
+        // A second release inside the keyup cooldown is dropped, keydown is unaffected
+        let soon = now + Duration::from_millis(10);
+        engine.play_key_up_at("KeyA", soon);
+        engine.play_key_at("KeyB", soon);
+        assert_eq!(player.plays().len(), 3);
+    }
+
+    #[test]
+    fn test_layout_profile_mutes_keys_outside_it() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        engine.set_layout_profile(LayoutProfile::LettersOnly);
+        let now = Instant::now();
+
+        engine.play_key_at("Num0", now);
+        engine.play_key_up_at("Num0", now);
+        assert!(player.plays().is_empty());
+
+        engine.play_key_at("KeyA", now);
+        assert_eq!(player.plays().len(), 1);
+    }
+
+    #[test]
+    fn test_category_gain_override_boosts_only_that_category() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        engine.set_category_gain_override("modifiers", Some(2.0));
+        let now = Instant::now();
+
+        engine.play_key_at("ShiftLeft", now);
+        engine.play_key_at("KeyA", now);
+        let plays = player.plays();
+        // 0.5 doubled is full volume (0 dB); the letter stays at about -6 dB
+        assert!(plays[0].abs() < 0.01);
+        assert!((plays[1] + 6.02).abs() < 0.01);
+
+        engine.set_category_gain_override("modifiers", None);
+        engine.play_key_at("ShiftRight", now);
+        assert!((player.plays()[2] + 6.02).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_key_delay_is_clamped_and_reaches_player() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        let now = Instant::now();
+
+        engine.play_key_at("KeyD", now);
+        engine.play_key_up_at("KeyD", now);
+        engine.play_key_at("KeyA", now);
+        // 500ms in pack.json is clamped to MAX_KEY_DELAY_MS; keyup isn't delayed
+        assert_eq!(
+            player.start_times(),
+            vec![
+                StartTime::Delayed(Duration::from_millis(MAX_KEY_DELAY_MS)),
+                StartTime::Immediate,
+                StartTime::Immediate,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repoint_renamed_active_pack() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        let pack =
+            change_pack_id_dir(dir.path(), &dir.path().join("none"), "rec", "Renamed").unwrap();
+
+        assert!(!engine.repoint_pack("other", pack.clone()));
+        assert!(engine.repoint_pack("rec", pack));
+        assert_eq!(engine.active_pack_id().as_deref(), Some("renamed"));
+        // The decoded sounds followed the folder, so keys still play
+        engine.play_key_at("KeyA", Instant::now());
+        assert_eq!(player.plays().len(), 1);
+    }
+
+    /// Reinstall the active pack with `slot` set to `mode` and keyup sounds off.
+    fn set_trigger(engine: &mut SoundEngine, slot: &str, mode: TriggerMode) {
+        let mut pack = engine.active_pack().unwrap().clone();
+        pack.keyup_enabled = false;
+        pack.slot_triggers.insert(slot.into(), mode);
+        engine.load_pack(pack).unwrap();
+    }
+
+    #[test]
+    fn test_trigger_press_plays_on_press_only() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        set_trigger(&mut engine, "default", TriggerMode::Press);
+        let now = Instant::now();
+
+        engine.play_key_at("KeyA", now);
+        assert_eq!(player.plays().len(), 1);
+        engine.play_key_up_at("KeyA", now);
+        assert_eq!(player.plays().len(), 1);
+    }
+
+    #[test]
+    fn test_trigger_release_plays_on_release_only() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        set_trigger(&mut engine, "default", TriggerMode::Release);
+        let now = Instant::now();
+
+        engine.play_key_at("KeyA", now);
+        assert!(player.plays().is_empty());
+        engine.play_key_up_at("KeyA", now);
+        assert_eq!(player.plays().len(), 1);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::thread;
-    use std::time::Duration;
+    #[test]
+    fn test_trigger_both_plays_on_press_and_release() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        set_trigger(&mut engine, "default", TriggerMode::Both);
+        let now = Instant::now();
+
+        engine.play_key_at("KeyA", now);
+        assert_eq!(player.plays().len(), 1);
+        engine.play_key_up_at("KeyA", now);
+        assert_eq!(player.plays().len(), 2);
+    }
+
+    #[test]
+    fn test_player_gets_nothing_while_disabled() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        engine.set_enabled(false);
+        let now = Instant::now();
+        engine.play_key_at("KeyA", now);
+        engine.play_key_up_at("KeyA", now);
+        assert!(player.plays().is_empty());
+
+        engine.set_enabled(true);
+        engine.play_key_at("KeyA", now + Duration::from_secs(1));
+        assert_eq!(player.plays().len(), 1);
+    }
+
+    #[test]
+    fn test_amplitude_to_db_full_volume() {
+        let db = amplitude_to_db(1.0);
+        assert!((db - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_amplitude_to_db_half_volume() {
+        let db = amplitude_to_db(0.5);
+        assert!((db - (-6.0206)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_amplitude_to_db_zero() {
+        assert_eq!(amplitude_to_db(0.0), -100.0);
+    }
+
+    #[test]
+    fn test_amplitude_to_db_negative() {
+        assert_eq!(amplitude_to_db(-0.5), -100.0);
+    }
+
+    #[test]
+    fn test_key_cooldown_constant() {
+        assert_eq!(KEY_REPEAT_COOLDOWN_MS, 80);
+    }
+
+    #[test]
+    fn test_key_not_in_cooldown_initially() {
+        let engine = SoundEngine::new().expect("Failed to create engine");
+        assert!(!engine.is_key_in_cooldown("KeyA"));
+        assert!(!engine.is_key_in_cooldown("Space"));
+    }
+
+    #[test]
+    fn test_key_in_cooldown_after_play() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        engine.record_key_play("KeyA");
+        assert!(engine.is_key_in_cooldown("KeyA"));
+        assert!(!engine.is_key_in_cooldown("KeyB"));
+    }
+
+    #[test]
+    fn test_key_cooldown_expires() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        let past = Instant::now() - Duration::from_millis(100);
+        engine.record_key_play_at("KeyA", past);
+        assert!(!engine.is_key_in_cooldown("KeyA"));
+    }
+
+    #[test]
+    fn test_key_cooldown_not_expired() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        let past = Instant::now() - Duration::from_millis(30);
+        engine.record_key_play_at("KeyA", past);
+        assert!(engine.is_key_in_cooldown("KeyA"));
+    }
+
+    #[test]
+    fn test_key_cooldown_boundary() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        let past = Instant::now() - Duration::from_millis(80);
+        engine.record_key_play_at("KeyA", past);
+        assert!(!engine.is_key_in_cooldown("KeyA"));
+    }
+
+    #[test]
+    fn test_key_cooldown_independent_keys() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        engine.record_key_play("KeyA");
+        engine.record_key_play("KeyB");
+        assert!(engine.is_key_in_cooldown("KeyA"));
+        assert!(engine.is_key_in_cooldown("KeyB"));
+        assert!(!engine.is_key_in_cooldown("KeyC"));
+    }
+
+    #[test]
+    fn test_keyup_cooldown_defaults_to_keydown() {
+        let engine = SoundEngine::new().expect("Failed to create engine");
+        assert_eq!(
+            engine.get_keyup_cooldown_ms() as u128,
+            KEY_REPEAT_COOLDOWN_MS
+        );
+        assert!(!engine.is_key_up_in_cooldown("KeyA"));
+    }
+
+    #[test]
+    fn test_keyup_cooldown_expires() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        engine.record_key_up_play_at("KeyA", Instant::now() - Duration::from_millis(100));
+        assert!(!engine.is_key_up_in_cooldown("KeyA"));
+    }
+
+    #[test]
+    fn test_keyup_cooldown_not_expired() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        engine.record_key_up_play_at("KeyA", Instant::now() - Duration::from_millis(30));
+        assert!(engine.is_key_up_in_cooldown("KeyA"));
+        // Keydown and keyup are tracked separately
+        assert!(!engine.is_key_in_cooldown("KeyA"));
+        assert!(!engine.is_key_up_in_cooldown("KeyB"));
+    }
+
+    #[test]
+    fn test_keyup_cooldown_is_configurable() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        engine.set_keyup_cooldown_ms(20);
+        engine.record_key_up_play_at("KeyA", Instant::now() - Duration::from_millis(30));
+        assert!(!engine.is_key_up_in_cooldown("KeyA"));
+        engine.set_keyup_cooldown_ms(0);
+        engine.record_key_up_play_at("KeyA", Instant::now());
+        assert!(!engine.is_key_up_in_cooldown("KeyA"));
+    }
+
+    #[test]
+    fn test_volume_clamp() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        engine.set_volume(0.5);
+        assert!((engine.get_volume() - 0.5).abs() < 0.001);
+        engine.set_volume(1.5);
+        assert!((engine.get_volume() - 1.0).abs() < 0.001);
+        engine.set_volume(-0.5);
+        assert!((engine.get_volume() - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_get_volume_reports_ramp_target() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        // A fast slider drag: each change starts a new ramp on the main track
+        for step in 1..=10 {
+            engine.set_volume(1.0 - step as f64 * 0.05);
+        }
+        // The target is reported right away, not the mid-ramp output level
+        assert!((engine.get_volume() - 0.5).abs() < 0.001);
+        assert!((engine.state().volume - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_toggle_sound() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        assert!(engine.is_enabled());
+        assert!(!engine.toggle());
+        assert!(!engine.is_enabled());
+        assert!(engine.toggle());
+        assert!(engine.is_enabled());
+    }
+
+    #[test]
+    fn test_set_enabled() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        engine.set_enabled(false);
+        assert!(!engine.is_enabled());
+        engine.set_enabled(true);
+        assert!(engine.is_enabled());
+    }
+
+    #[test]
+    fn test_engine_state_snapshot() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        engine.set_volume(0.4);
+        engine.toggle();
+
+        let state = engine.state();
+        assert!(!state.enabled);
+        assert!((state.volume - 0.4).abs() < 0.001);
+        assert_eq!(state.active_pack_id.as_deref(), Some("test"));
+        assert!(!state.degraded);
+    }
+
+    #[test]
+    fn test_play_failures_rebuild_at_most_once_per_window() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        player.set_failing(true);
+        let start = Instant::now();
+        let mut now = start;
+        // Distinct keys spaced out, so only the failures stop them
+        let press = |engine: &mut SoundEngine, at: Instant| {
+            engine.play_key_at(&format!("Key{}", at.duration_since(start).as_millis()), at);
+        };
+
+        for _ in 0..PLAY_FAILURE_THRESHOLD - 1 {
+            now += Duration::from_millis(50);
+            press(&mut engine, now);
+        }
+        assert_eq!(player.reconnects(), 0);
+
+        // Threshold reached: one reconnect, and the pack is queued for a re-decode
+        now += Duration::from_millis(50);
+        press(&mut engine, now);
+        let rebuilt_at = now;
+        assert_eq!(player.reconnects(), 1);
+        assert!(!engine.is_degraded());
+        let (_, pack) = engine.take_reload_request().unwrap();
+        assert_eq!(pack.id, "rec");
+
+        // Still failing within the retry window: degraded, but no new reconnect
+        for _ in 0..10 {
+            now += Duration::from_millis(50);
+            press(&mut engine, now);
+        }
+        assert_eq!(player.reconnects(), 1);
+        assert!(engine.state().degraded);
+
+        press(
+            &mut engine,
+            rebuilt_at + Duration::from_millis(REBUILD_RETRY_MS as u64),
+        );
+        assert_eq!(player.reconnects(), 2);
+
+        // Device loss is reported once, then cleared
+        assert!(engine.take_error().unwrap().contains("Audio output failed"));
+        assert!(engine.take_error().is_none());
+
+        player.set_failing(false);
+        press(&mut engine, now + Duration::from_secs(10));
+        assert_eq!(player.plays().len(), 1);
+        assert!(!engine.is_degraded());
+    }
+
+    #[test]
+    fn test_rebuild_keeps_the_injected_player() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        let now = Instant::now();
+        for _ in 0..PLAY_FAILURE_THRESHOLD {
+            engine.record_play_failure(now);
+        }
+        assert_eq!(player.reconnects(), 1);
+
+        // Still playing into the same recorder, not a real device
+        engine.play_key_at("KeyA", now);
+        assert_eq!(player.plays().len(), 1);
+    }
+
+    #[test]
+    fn test_install_pack_reports_missing_sounds() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        assert!(engine.take_error().is_none());
+
+        let mut pack = SoundPack::load(&dir.path().join("test")).unwrap();
+        pack.defaults.keyup = Some("sounds/missing.wav".into());
+        engine.load_pack(pack).unwrap();
+        let error = engine.take_error().unwrap();
+        assert!(error.contains("Sound file not found"));
+        assert!(error.contains("missing.wav"));
+    }
+
+    #[test]
+    fn test_active_pack_id_none() {
+        let engine = SoundEngine::new().expect("Failed to create engine");
+        assert!(engine.active_pack_id().is_none());
+    }
+
+    #[test]
+    fn test_cooldown_real_wait() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        engine.record_key_play("KeyA");
+        assert!(engine.is_key_in_cooldown("KeyA"));
+        thread::sleep(Duration::from_millis(90));
+        assert!(!engine.is_key_in_cooldown("KeyA"));
+    }
+
+    #[test]
+    fn test_decode_reports_progress_per_file() {
+        let dir = TempDir::new().unwrap();
+        let pack_dir = dir.path().join("big");
+        let mut key_overrides = serde_json::Map::new();
+        for key in ["KeyA", "KeyB", "KeyC", "KeyD"] {
+            let file = format!("sounds/{}.wav", key);
+            key_overrides.insert(key.into(), serde_json::json!({ "keydown": file }));
+        }
+        let manifest = serde_json::json!({
+            "id": "big",
+            "name": "Big",
+            "defaults": { "keydown": "sounds/keydown.wav" },
+            "key_overrides": key_overrides
+        });
+        write_test_pack(&pack_dir, &manifest);
+
+        let events = std::sync::Mutex::new(Vec::new());
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        let loaded = LoadedPack::decode_with_progress(pack, |progress| {
+            events.lock().unwrap().push(progress);
+        });
+        assert_eq!(loaded.sounds.len(), 5);
+
+        let mut events = events.into_inner().unwrap();
+        assert_eq!(events.len(), 5);
+        events.sort_by_key(|p| p.loaded);
+        assert!(events.iter().all(|p| p.total == 5 && p.pack_id == "big"));
+        assert_eq!(
+            events.iter().map(|p| p.loaded).collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn test_stale_pack_load_is_dropped() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, _player) = recording_engine(dir.path());
+        let test_dir = dir.path().join("test");
+        write_test_pack(
+            &test_dir,
+            &serde_json::json!({
+                "id": "test",
+                "name": "Test",
+                "defaults": { "keydown": "sounds/keydown.wav" }
+            }),
+        );
+        let decode = |pack_dir: &Path| LoadedPack::decode(SoundPack::load(pack_dir).unwrap());
+
+        // Two quick switches that finish out of order: the newer choice wins
+        let to_test = engine.begin_pack_load(Some("test".into()));
+        let to_rec = engine.begin_pack_load(Some("rec".into()));
+        assert_eq!(engine.target_pack_id().as_deref(), Some("rec"));
+        assert!(engine.install_pack_if_current(to_rec, decode(&dir.path().join("rec"))));
+        assert!(!engine.install_pack_if_current(to_test, decode(&test_dir)));
+        assert_eq!(engine.active_pack_id().as_deref(), Some("rec"));
+
+        // A reload that started before a switch loses to it
+        let reload = engine.begin_pack_load(Some("rec".into()));
+        let switch = engine.begin_pack_load(Some("test".into()));
+        assert!(engine.install_pack_if_current(switch, decode(&test_dir)));
+        assert!(!engine.install_pack_if_current(reload, decode(&dir.path().join("rec"))));
+        assert_eq!(engine.active_pack_id().as_deref(), Some("test"));
+
+        // An abandoned load leaves the active pack as the target
+        let failed = engine.begin_pack_load(Some("rec".into()));
+        engine.abandon_pack_load(failed);
+        assert_eq!(engine.target_pack_id().as_deref(), Some("test"));
+
+        // Installing resets the cooldowns of the previous pack
+        engine.record_key_play("KeyA");
+        let ticket = engine.begin_pack_load(Some("test".into()));
+        assert!(engine.install_pack_if_current(ticket, decode(&test_dir)));
+        assert!(!engine.is_key_in_cooldown("KeyA"));
+    }
+
+    #[test]
+    fn test_active_reload_yields_to_a_newer_switch() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, _player) = recording_engine(dir.path());
+        let (reload, pack) = engine.begin_active_reload().unwrap();
+        assert_eq!(pack.id, "rec");
+
+        // A pack picked while the reload decodes wins over it
+        let switch = engine.begin_pack_load(Some("test".into()));
+        assert!(!engine.install_pack_if_current(reload, LoadedPack::decode(pack)));
+        assert_eq!(engine.target_pack_id().as_deref(), Some("test"));
+        engine.abandon_pack_load(switch);
+
+        // A sideloaded pack reloads without claiming a managed id
+        let sideloaded = SoundPack::load_sideloaded(&dir.path().join("rec")).unwrap();
+        engine.load_pack(sideloaded).unwrap();
+        let (reload, pack) = engine.begin_active_reload().unwrap();
+        assert_eq!(engine.target_pack_id(), None);
+        assert!(engine.install_pack_if_current(reload, LoadedPack::decode(pack)));
+        assert!(engine.state().sideloaded);
+    }
+
+    #[test]
+    fn test_rate_limit_drops_excess_plays() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        engine.set_max_plays_per_window(4);
+
+        let now = Instant::now();
+        for i in 0..10 {
+            engine.play_key_at(&format!("Key{}", i), now);
+        }
+        assert_eq!(engine.dropped_plays(), 6);
+        // Dropped keys are not put into cooldown
+        assert!(engine.is_key_in_cooldown("Key0"));
+        assert!(!engine.is_key_in_cooldown("Key9"));
+    }
+
+    #[test]
+    fn test_rate_limit_window_expires() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        engine.set_max_plays_per_window(2);
+
+        let start = Instant::now();
+        engine.play_key_at("KeyA", start);
+        engine.play_key_at("KeyB", start);
+        engine.play_key_at("KeyC", start);
+        assert_eq!(engine.dropped_plays(), 1);
+
+        let later = start + Duration::from_millis(PLAY_RATE_WINDOW_MS as u64);
+        engine.play_key_at("KeyD", later);
+        assert_eq!(engine.dropped_plays(), 1);
+    }
+
+    #[test]
+    fn test_max_plays_per_window_minimum() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        assert_eq!(
+            engine.get_max_plays_per_window(),
+            DEFAULT_MAX_PLAYS_PER_WINDOW
+        );
+        engine.set_max_plays_per_window(0);
+        assert_eq!(engine.get_max_plays_per_window(), 1);
+    }
+
+    #[test]
+    fn test_play_key_up_silent_when_keyup_disabled() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_manifest(
+            dir.path(),
+            serde_json::json!({
+                "id": "test",
+                "name": "Test",
+                "keyup_enabled": false,
+                "defaults": { "keydown": "sounds/keydown.wav", "keyup": "sounds/keydown.wav" }
+            }),
+        );
+
+        let now = Instant::now();
+        engine.play_key_up_at("KeyA", now);
+        assert_eq!(engine.throttle_stats().played, 0);
+        // Keydown is unaffected
+        engine.play_key_at("KeyA", now);
+        assert_eq!(engine.throttle_stats().played, 1);
+    }
+
+    #[test]
+    fn test_play_key_up_uses_keyup_sound() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        let now = Instant::now();
+
+        // Pack has no keyup sounds: releases are silent
+        engine.play_key_up_at("ShiftLeft", now);
+        assert_eq!(engine.throttle_stats().played, 0);
+
+        let pack_dir = dir.path().join("test");
+        let manifest = serde_json::json!({
+            "id": "test",
+            "name": "Test",
+            "defaults": { "keydown": "sounds/keydown.wav" },
+            "category_overrides": {
+                "modifiers": {
+                    "keys": ["ShiftLeft"],
+                    "keydown": null,
+                    "keyup": "sounds/keydown.wav",
+                    "volume": null
+                }
+            }
+        });
+        write_test_pack(&pack_dir, &manifest);
+        engine.load_pack_from_path(&pack_dir).unwrap();
+
+        engine.play_key_up_at("ShiftLeft", now);
+        engine.play_key_up_at("ShiftLeft", now);
+        assert_eq!(engine.throttle_stats().played, 1);
+        assert_eq!(engine.throttle_stats().throttled, 1);
+        engine.play_key_up_at("KeyA", now);
+        assert_eq!(engine.throttle_stats().played, 1);
+        let later = now + Duration::from_millis(KEY_REPEAT_COOLDOWN_MS as u64);
+        engine.play_key_up_at("ShiftLeft", later);
+        assert_eq!(engine.throttle_stats().played, 2);
+    }
+
+    #[test]
+    fn test_held_key_repeats_suppressed_until_release() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        engine.set_suppress_held_repeats(true);
+
+        let now = Instant::now();
+        let ms = |n: u64| now + Duration::from_millis(n);
+        engine.play_key_at("KeyA", now);
+        // Auto-repeat well past the cooldown is still suppressed while held
+        engine.play_key_at("KeyA", ms(300));
+        engine.play_key_at("KeyA", ms(600));
+        assert_eq!(engine.throttle_stats().played, 1);
+
+        engine.play_key_up_at("KeyA", ms(650));
+        engine.play_key_at("KeyA", ms(700));
+        assert_eq!(engine.throttle_stats().played, 2);
+    }
+
+    #[test]
+    fn test_held_key_watchdog_recovers_from_missing_release() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        engine.set_suppress_held_repeats(true);
+
+        // No release events ever arrive
+        let now = Instant::now();
+        engine.play_key_at("KeyA", now);
+        engine.play_key_at("KeyA", now + Duration::from_millis(500));
+        assert_eq!(engine.throttle_stats().played, 1);
+
+        let stale = now + Duration::from_millis(HELD_KEY_WATCHDOG_MS as u64);
+        engine.play_key_at("KeyA", stale);
+        assert_eq!(engine.throttle_stats().played, 2);
+        // The watchdog restarts the held window from the press that sounded
+        engine.play_key_at("KeyA", stale + Duration::from_millis(500));
+        assert_eq!(engine.throttle_stats().played, 2);
+    }
 
     #[test]
-    fn test_amplitude_to_db_full_volume() {
-        let db = amplitude_to_db(1.0);
-        assert!((db - 0.0).abs() < 0.001);
+    fn test_cooldown_still_applies_with_held_tracking() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        engine.set_suppress_held_repeats(true);
+
+        // Press/release faster than the cooldown still only sounds once
+        let now = Instant::now();
+        engine.play_key_at("KeyA", now);
+        engine.play_key_up_at("KeyA", now + Duration::from_millis(10));
+        engine.play_key_at("KeyA", now + Duration::from_millis(20));
+        assert_eq!(engine.throttle_stats().played, 1);
     }
 
     #[test]
-    fn test_amplitude_to_db_half_volume() {
-        let db = amplitude_to_db(0.5);
-        assert!((db - (-6.0206)).abs() < 0.01);
+    fn test_retrigger_restart_stops_previous_sound() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        engine.set_retrigger_policy(RetriggerPolicy::Restart);
+
+        let now = Instant::now();
+        engine.play_key_at("KeyA", now);
+        engine.play_key_at("KeyA", now + Duration::from_millis(200));
+        assert_eq!(engine.restarted_plays(), 1);
+        // The new sound replaced the stopped one
+        assert_eq!(player.stopped(), [true, false]);
+
+        // Other keys don't cut each other off
+        engine.play_key_at("KeyB", now + Duration::from_millis(200));
+        assert_eq!(engine.restarted_plays(), 1);
+        assert_eq!(player.stopped(), [true, false, false]);
+
+        // A sound that already ended isn't counted as restarted
+        player.finish_all();
+        engine.play_key_at("KeyA", now + Duration::from_millis(400));
+        assert_eq!(engine.restarted_plays(), 1);
+        assert_eq!(player.plays().len(), 4);
     }
 
     #[test]
-    fn test_amplitude_to_db_zero() {
-        assert_eq!(amplitude_to_db(0.0), -100.0);
+    fn test_retrigger_ignore_and_overlap() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        let now = Instant::now();
+
+        engine.set_retrigger_policy(RetriggerPolicy::Ignore);
+        engine.play_key_at("KeyA", now);
+        engine.play_key_at("KeyA", now + Duration::from_millis(200));
+        assert_eq!(player.plays().len(), 1);
+        // Once the sound ends the key plays again
+        player.finish_all();
+        engine.play_key_at("KeyA", now + Duration::from_millis(400));
+        assert_eq!(player.plays().len(), 2);
+
+        // Overlap (the default) neither tracks nor stops sounds
+        engine.set_retrigger_policy(RetriggerPolicy::Overlap);
+        engine.play_key_at("KeyA", now + Duration::from_millis(600));
+        engine.play_key_at("KeyA", now + Duration::from_millis(800));
+        assert_eq!(player.plays().len(), 4);
+        assert_eq!(player.stopped()[2..], [false, false]);
+        assert!(!engine.is_ringing("KeyA"));
+        assert_eq!(engine.restarted_plays(), 0);
     }
 
     #[test]
-    fn test_amplitude_to_db_negative() {
-        assert_eq!(amplitude_to_db(-0.5), -100.0);
+    fn test_dynamic_volume_rises_with_typing_speed() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        assert_eq!(engine.dynamic_volume_multiplier(), 1.0);
+        engine.set_dynamic_volume(DynamicVolume {
+            enabled: true,
+            min: 1.3,
+            max: 0.7,
+        });
+        assert_eq!(engine.get_dynamic_volume().min, 0.7);
+
+        // Slow typing: 600ms between keys (20 WPM)
+        let start = Instant::now();
+        for i in 0..10 {
+            engine.record_keypress("KeyA", start + Duration::from_millis(i * 600));
+        }
+        let slow = engine.dynamic_volume_multiplier();
+
+        // Fast typing: 100ms between keys (120 WPM)
+        let start = start + Duration::from_secs(10);
+        for i in 0..=TYPING_SAMPLE_SIZE as u64 {
+            engine.record_keypress("KeyA", start + Duration::from_millis(i * 100));
+        }
+        let fast = engine.dynamic_volume_multiplier();
+
+        assert!(fast > slow);
+        assert!((slow - 0.7).abs() < 0.001);
+        assert!((fast - 1.3).abs() < 0.001);
     }
 
     #[test]
-    fn test_key_cooldown_constant() {
-        assert_eq!(KEY_REPEAT_COOLDOWN_MS, 80);
+    fn test_sideloaded_pack_is_not_managed() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        assert_eq!(engine.managed_pack_id().as_deref(), Some("test"));
+        assert!(!engine.state().sideloaded);
+
+        let pack = SoundPack::load_sideloaded(&dir.path().join("test")).unwrap();
+        engine.load_pack(pack).unwrap();
+        assert_eq!(engine.active_pack_id().as_deref(), Some("test"));
+        assert_eq!(engine.managed_pack_id(), None);
+        assert!(engine.state().sideloaded);
     }
 
     #[test]
-    fn test_key_not_in_cooldown_initially() {
-        let engine = SoundEngine::new().expect("Failed to create engine");
-        assert!(!engine.is_key_in_cooldown("KeyA"));
-        assert!(!engine.is_key_in_cooldown("Space"));
+    fn test_volume_feedback_is_throttled() {
+        let player = RecordingPlayer::default();
+        let mut engine = SoundEngine::with_player(Box::new(player.clone()));
+        let now = Instant::now();
+        assert!(engine.play_volume_feedback_at(now));
+        assert!(!engine.play_volume_feedback_at(now + Duration::from_millis(30)));
+        assert!(!engine.play_volume_feedback_at(now + Duration::from_millis(90)));
+        assert_eq!(player.plays().len(), 1);
+
+        let later = now + Duration::from_millis(VOLUME_FEEDBACK_COOLDOWN_MS as u64);
+        assert!(engine.play_volume_feedback_at(later));
+        assert_eq!(player.plays().len(), 2);
     }
 
     #[test]
-    fn test_key_in_cooldown_after_play() {
-        let mut engine = SoundEngine::new().expect("Failed to create engine");
-        engine.record_key_play("KeyA");
-        assert!(engine.is_key_in_cooldown("KeyA"));
-        assert!(!engine.is_key_in_cooldown("KeyB"));
+    fn test_panic_stop_halts_tracked_sounds() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        engine.set_retrigger_policy(RetriggerPolicy::Restart);
+        let now = Instant::now();
+        engine.play_key_at("KeyA", now);
+        engine.play_key_at("KeyB", now);
+        let click = builtin_click();
+        engine.play_preview(&click, 1.0);
+        assert_eq!(engine.active_sound_count(), 3);
+
+        engine.panic_stop();
+        assert_eq!(engine.active_sound_count(), 0);
+        assert_eq!(player.stopped(), [true, true, true]);
+        assert!(!engine.is_enabled());
+
+        // Nothing plays until sound is turned back on
+        engine.play_key_at("KeyC", now);
+        assert_eq!(player.plays().len(), 3);
+    }
+
+    /// Fullscreen probe the test can flip at will
+    struct FakeFullscreen(Arc<AtomicBool>);
+
+    impl FullscreenProbe for FakeFullscreen {
+        fn is_fullscreen_focused(&self) -> bool {
+            self.0.load(Ordering::Relaxed)
+        }
     }
 
     #[test]
-    fn test_key_cooldown_expires() {
-        let mut engine = SoundEngine::new().expect("Failed to create engine");
-        let past = Instant::now() - Duration::from_millis(100);
-        engine.record_key_play_at("KeyA", past);
-        assert!(!engine.is_key_in_cooldown("KeyA"));
+    fn test_mute_on_fullscreen_suppresses_keys() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        let fullscreen = Arc::new(AtomicBool::new(true));
+        engine.fullscreen_probe = Box::new(FakeFullscreen(Arc::clone(&fullscreen)));
+
+        // Off by default, so fullscreen apps don't matter
+        let now = Instant::now();
+        engine.play_key_at("KeyA", now);
+        assert_eq!(engine.throttle_stats().played, 1);
+
+        engine.set_mute_on_fullscreen(true);
+        engine.play_key_at("KeyB", now);
+        assert_eq!(engine.throttle_stats().played, 1);
+
+        fullscreen.store(false, Ordering::Relaxed);
+        engine.play_key_at("KeyC", now);
+        assert_eq!(engine.throttle_stats().played, 2);
+    }
+
+    struct FakeCapture(Arc<AtomicBool>);
+
+    impl CaptureProbe for FakeCapture {
+        fn is_capturing(&self) -> bool {
+            self.0.load(Ordering::Relaxed)
+        }
     }
 
     #[test]
-    fn test_key_cooldown_not_expired() {
-        let mut engine = SoundEngine::new().expect("Failed to create engine");
-        let past = Instant::now() - Duration::from_millis(30);
-        engine.record_key_play_at("KeyA", past);
-        assert!(engine.is_key_in_cooldown("KeyA"));
+    fn test_mute_on_recording_suppresses_keys() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        let capturing = Arc::new(AtomicBool::new(true));
+        engine.capture_probe = Box::new(FakeCapture(Arc::clone(&capturing)));
+
+        // Off by default, so recorders don't matter
+        let now = Instant::now();
+        engine.play_key_at("KeyA", now);
+        assert_eq!(player.plays().len(), 1);
+
+        engine.set_mute_on_recording(true);
+        engine.play_key_at("KeyB", now);
+        engine.play_key_up_at("KeyB", now);
+        assert_eq!(player.plays().len(), 1);
+
+        capturing.store(false, Ordering::Relaxed);
+        engine.play_key_at("KeyC", now);
+        assert_eq!(player.plays().len(), 2);
     }
 
     #[test]
-    fn test_key_cooldown_boundary() {
-        let mut engine = SoundEngine::new().expect("Failed to create engine");
-        let past = Instant::now() - Duration::from_millis(80);
-        engine.record_key_play_at("KeyA", past);
-        assert!(!engine.is_key_in_cooldown("KeyA"));
+    fn test_burst_protection_collapses_simultaneous_keys() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        let now = Instant::now();
+        let at = |ms| now + Duration::from_millis(ms);
+
+        // Off by default: a five-key chord plays five sounds
+        for key in ["KeyA", "KeyB", "KeyC", "KeyD", "KeyE"] {
+            engine.play_key_at(key, now);
+        }
+        assert_eq!(player.plays().len(), 5);
+
+        engine.set_burst_protection(true);
+        for (i, key) in ["KeyF", "KeyG", "KeyH", "KeyI", "KeyJ"].iter().enumerate() {
+            engine.play_key_at(key, at(100 + i as u64));
+        }
+        assert_eq!(player.plays().len(), 6);
+
+        // Ordinary typing speed is untouched
+        engine.play_key_at("KeyK", at(200));
+        engine.play_key_at("KeyL", at(260));
+        assert_eq!(player.plays().len(), 8);
     }
 
     #[test]
-    fn test_key_cooldown_independent_keys() {
-        let mut engine = SoundEngine::new().expect("Failed to create engine");
-        engine.record_key_play("KeyA");
-        engine.record_key_play("KeyB");
-        assert!(engine.is_key_in_cooldown("KeyA"));
-        assert!(engine.is_key_in_cooldown("KeyB"));
-        assert!(!engine.is_key_in_cooldown("KeyC"));
+    fn test_performance_mode_skips_effects_and_caps_plays() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        let dynamic = DynamicVolume {
+            enabled: true,
+            min: 0.5,
+            max: 1.5,
+        };
+        engine.set_dynamic_volume(dynamic);
+        engine.set_max_plays_per_window(16);
+
+        engine.set_performance_mode(true).unwrap();
+        assert!(engine.is_performance_mode());
+
+        // Keydowns play at the plain pack volume (0.5), and only a few at once
+        let now = Instant::now();
+        for key in ["KeyA", "KeyB", "KeyC", "KeyE", "KeyF", "KeyG"] {
+            engine.play_key_at(key, now);
+        }
+        assert_eq!(
+            player.plays(),
+            vec![amplitude_to_db(0.5) as f32; PERFORMANCE_MAX_PLAYS_PER_WINDOW]
+        );
+
+        // The user's settings come back untouched
+        engine.set_performance_mode(false).unwrap();
+        assert!(!engine.is_performance_mode());
+        assert_eq!(engine.get_dynamic_volume(), dynamic);
+        assert_eq!(engine.get_max_plays_per_window(), 16);
     }
 
     #[test]
-    fn test_volume_clamp() {
-        let mut engine = SoundEngine::new().expect("Failed to create engine");
-        engine.set_volume(0.5);
-        assert!((engine.get_volume() - 0.5).abs() < 0.001);
-        engine.set_volume(1.5);
-        assert!((engine.get_volume() - 1.0).abs() < 0.001);
-        engine.set_volume(-0.5);
-        assert!((engine.get_volume() - 0.0).abs() < 0.001);
+    fn test_performance_mode_stays_off_when_reconnect_fails() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        player.set_reconnect_failing(true);
+
+        assert!(engine.set_performance_mode(true).is_err());
+        assert!(!engine.is_performance_mode());
     }
 
     #[test]
-    fn test_toggle_sound() {
-        let mut engine = SoundEngine::new().expect("Failed to create engine");
+    fn test_hotkey_toggle_flips_on_press_and_ignores_repeats() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        assert!(!engine.handle_hotkey("F9", true));
+
+        engine.set_hotkey(Some("F9".into()));
+        assert!(engine.handle_hotkey("F9", true));
+        assert!(!engine.is_enabled());
+        // Auto-repeat while held doesn't flip it back
+        assert!(engine.handle_hotkey("F9", true));
+        assert!(!engine.is_enabled());
+        assert!(engine.handle_hotkey("F9", false));
+        assert!(!engine.is_enabled());
+
+        engine.handle_hotkey("F9", true);
+        engine.handle_hotkey("F9", false);
         assert!(engine.is_enabled());
-        assert!(!engine.toggle());
+        assert!(!engine.handle_hotkey("KeyA", true));
+        assert!(player.plays().is_empty());
+    }
+
+    #[test]
+    fn test_hotkey_push_to_play_sounds_only_while_held() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        engine.set_hotkey(Some("AltGr".into()));
+        engine.set_hotkey_mode(HotkeyMode::PushToPlay);
         assert!(!engine.is_enabled());
-        assert!(engine.toggle());
+
+        let now = Instant::now();
+        engine.play_key_at("KeyA", now);
+        assert!(player.plays().is_empty());
+
+        engine.handle_hotkey("AltGr", true);
         assert!(engine.is_enabled());
+        engine.play_key_at("KeyB", now);
+        assert_eq!(player.plays().len(), 1);
+
+        engine.handle_hotkey("AltGr", false);
+        assert!(!engine.is_enabled());
     }
 
     #[test]
-    fn test_set_enabled() {
-        let mut engine = SoundEngine::new().expect("Failed to create engine");
+    fn test_hotkey_push_to_mute_silences_only_while_held() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, _player) = recording_engine(dir.path());
         engine.set_enabled(false);
+        engine.set_hotkey(Some("F9".into()));
+        engine.set_hotkey_mode(HotkeyMode::PushToMute);
+        assert!(engine.is_enabled());
+
+        engine.handle_hotkey("F9", true);
         assert!(!engine.is_enabled());
-        engine.set_enabled(true);
+        engine.handle_hotkey("F9", true);
+        assert!(!engine.is_enabled());
+        engine.handle_hotkey("F9", false);
         assert!(engine.is_enabled());
     }
 
     #[test]
-    fn test_active_pack_id_none() {
+    fn test_solo_key_silences_other_keys() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        engine.set_solo_key(Some("KeyA".into()));
+
+        let now = Instant::now();
+        engine.play_key_at("KeyB", now);
+        assert_eq!(engine.throttle_stats().played, 0);
+        engine.play_key_at("KeyA", now);
+        assert_eq!(engine.throttle_stats().played, 1);
+        // Silenced keys still count toward typing stats
+        assert_eq!(engine.key_counts()["KeyB"], 1);
+
+        engine.set_solo_key(None);
+        engine.play_key_at("KeyB", now);
+        assert_eq!(engine.throttle_stats().played, 2);
+    }
+
+    #[test]
+    fn test_export_stats_csv_has_row_per_key() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        let now = Instant::now();
+        for (i, key) in ["KeyA", "KeyB", "KeyA", "Space"].iter().enumerate() {
+            engine.play_key_at(key, now + Duration::from_millis(i as u64 * 100));
+        }
+
+        let dst = dir.path().join("stats.csv");
+        let format = StatsFormat::parse("CSV").unwrap();
+        engine.key_stats().export(&dst, format).unwrap();
+        let csv = fs::read_to_string(&dst).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "key,count,category",
+                "KeyA,2,other",
+                "KeyB,1,other",
+                "Space,1,other"
+            ]
+        );
+
+        let json_dst = dir.path().join("stats.json");
+        engine
+            .key_stats()
+            .export(&json_dst, StatsFormat::Json)
+            .unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_dst).unwrap()).unwrap();
+        assert_eq!(json["keys"]["KeyA"]["count"], 2);
+        assert_eq!(json["categories"]["other"], 4);
+    }
+
+    #[test]
+    fn test_stats_csv_quotes_special_fields() {
+        let mut stats = KeyStats::default();
+        for (key, category) in [
+            ("KeyA", "a,b"),
+            ("KeyB", "say \"hi\""),
+            ("KeyC", "two\nlines"),
+        ] {
+            stats.keys.insert(
+                key.into(),
+                KeyStat {
+                    count: 1,
+                    category: category.into(),
+                },
+            );
+        }
+
+        assert_eq!(
+            stats.to_csv(),
+            "key,count,category\n\
+             KeyA,1,\"a,b\"\n\
+             KeyB,1,\"say \"\"hi\"\"\"\n\
+             KeyC,1,\"two\nlines\"\n"
+        );
+    }
+
+    #[test]
+    fn test_export_stats_validates_format_and_destination() {
+        assert!(StatsFormat::parse("xml")
+            .unwrap_err()
+            .contains("Unsupported"));
+        let dir = TempDir::new().unwrap();
+        let err = KeyStats::default()
+            .export(
+                &dir.path().join("missing").join("stats.csv"),
+                StatsFormat::Csv,
+            )
+            .unwrap_err();
+        assert!(err.contains("does not exist"));
+    }
+
+    #[test]
+    fn test_category_counts_bucket_by_pack_category() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        let pack_dir = dir.path().join("test");
+        let manifest = serde_json::json!({
+            "id": "test",
+            "name": "Test",
+            "defaults": { "keydown": "sounds/keydown.wav" },
+            "category_overrides": {
+                "modifiers": {
+                    "keys": ["ShiftLeft", "ShiftRight"],
+                    "keydown": null,
+                    "keyup": null,
+                    "volume": 0.6
+                }
+            }
+        });
+        write_test_pack(&pack_dir, &manifest);
+        engine.load_pack_from_path(&pack_dir).unwrap();
+
+        let now = Instant::now();
+        engine.play_key_at("ShiftLeft", now);
+        engine.play_key_at("KeyA", now);
+        engine.play_key_at("KeyA", now + Duration::from_millis(200));
+        engine.set_enabled(false);
+        engine.play_key_at("ShiftRight", now + Duration::from_millis(300));
+
+        assert_eq!(engine.key_counts()["KeyA"], 2);
+        let categories = engine.category_counts();
+        assert_eq!(categories["modifiers"], 2);
+        assert_eq!(categories[UNCATEGORIZED], 2);
+        assert_eq!(categories.len(), 2);
+
+        engine.reset_key_counts();
+        assert!(engine.category_counts().is_empty());
+    }
+
+    /// Give the test pack a "scroll" category using its keydown sound.
+    fn add_scroll_category(engine: &mut SoundEngine, dir: &Path) {
+        let pack_dir = dir.join("test");
+        let manifest = serde_json::json!({
+            "id": "test",
+            "name": "Test",
+            "defaults": { "keydown": "sounds/keydown.wav" },
+            "category_overrides": {
+                "scroll": {
+                    "keys": ["ScrollUp", "ScrollDown"],
+                    "keydown": "sounds/keydown.wav",
+                    "keyup": null,
+                    "volume": null
+                }
+            }
+        });
+        write_test_pack(&pack_dir, &manifest);
+        engine.load_pack_from_path(&pack_dir).unwrap();
+    }
+
+    #[test]
+    fn test_scroll_is_off_by_default() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        add_scroll_category(&mut engine, dir.path());
+
+        engine.play_scroll_at("ScrollUp", Instant::now());
+        assert_eq!(engine.throttle_stats().played, 0);
+    }
+
+    #[test]
+    fn test_scroll_burst_is_throttled_across_directions() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        add_scroll_category(&mut engine, dir.path());
+        engine.set_scroll_enabled(true);
+
+        // A burst of notches within the scroll cooldown plays once
+        let now = Instant::now();
+        for i in 0..6u64 {
+            let key = if i % 2 == 0 { "ScrollUp" } else { "ScrollDown" };
+            engine.play_scroll_at(key, now + Duration::from_millis(i * 20));
+        }
+        assert_eq!(engine.throttle_stats().played, 1);
+        assert_eq!(engine.throttle_stats().throttled, 5);
+
+        // Longer than the key cooldown, still within the scroll cooldown
+        let mid = now + Duration::from_millis(KEY_REPEAT_COOLDOWN_MS as u64 + 20);
+        engine.play_scroll_at("ScrollDown", mid);
+        assert_eq!(engine.throttle_stats().played, 1);
+
+        let later = now + Duration::from_millis(SCROLL_COOLDOWN_MS as u64);
+        engine.play_scroll_at("ScrollDown", later);
+        assert_eq!(engine.throttle_stats().played, 2);
+    }
+
+    #[test]
+    fn test_scroll_without_pack_category_is_silent() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        engine.set_scroll_enabled(true);
+
+        engine.play_scroll_at("ScrollUp", Instant::now());
+        assert_eq!(engine.throttle_stats(), ThrottleStats::default());
+    }
+
+    #[test]
+    fn test_throttle_stats_count_cooldown_skips() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+
+        let now = Instant::now();
+        for i in 0..5u64 {
+            engine.play_key_at("KeyA", now + Duration::from_millis(i * 10));
+        }
+        let stats = engine.throttle_stats();
+        assert_eq!(stats.played, 1);
+        assert_eq!(stats.throttled, 4);
+
+        engine.reset_throttle_stats();
+        assert_eq!(engine.throttle_stats(), ThrottleStats::default());
+    }
+
+    #[test]
+    fn test_bypass_cooldown_plays_every_repeat() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        // With one play per window, a second attempt shows up as a dropped play
+        engine.set_max_plays_per_window(1);
+
+        let now = Instant::now();
+        engine.play_key_at("KeyA", now);
+        engine.play_key_at("KeyA", now);
+        assert_eq!(engine.dropped_plays(), 0);
+
+        engine.set_bypass_cooldown(true);
+        let later = now + Duration::from_millis(PLAY_RATE_WINDOW_MS as u64);
+        engine.play_key_at("KeyB", later);
+        engine.play_key_at("KeyB", later);
+        assert_eq!(engine.dropped_plays(), 1);
+        assert!(!engine.is_key_in_cooldown("KeyB"));
+    }
+
+    #[test]
+    fn test_jittered_volume_stays_in_bounds() {
+        let mut rng = JitterRng(0x9E37_79B9_7F4A_7C15);
+        assert_eq!(jittered_volume(0.8, 0.0, &mut rng), 0.8);
+
+        let samples: Vec<f64> = (0..1000)
+            .map(|_| jittered_volume(0.8, 0.25, &mut rng))
+            .collect();
+        assert!(samples.iter().all(|v| (0.6..=1.0).contains(v)));
+        // Presses actually vary, on both sides of the base volume
+        assert!(samples.iter().any(|v| *v < 0.7));
+        assert!(samples.iter().any(|v| *v > 0.9));
+
+        // Never louder than a pack may be
+        assert!(
+            (0..1000).all(|_| jittered_volume(MAX_PACK_VOLUME, 0.5, &mut rng) <= MAX_PACK_VOLUME)
+        );
+    }
+
+    #[test]
+    fn test_play_preview_uses_volume_override() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        engine.set_volume(0.5);
+        let pack = engine.active_pack().unwrap().clone();
+        assert_eq!(pack.resolve_volume("KeyA"), 1.0);
+        let data = StaticSoundData::from_file(pack.resolve_keydown("KeyA").unwrap()).unwrap();
+
+        assert!((engine.play_preview(&data, 1.5) - 0.75).abs() < 1e-9);
+        assert!((engine.play_preview(&data, 0.2) - 0.1).abs() < 1e-9);
+        // Clamped to 0.0..=MAX_PACK_VOLUME
+        assert!((engine.play_preview(&data, 9.0) - 1.0).abs() < 1e-9);
+        assert_eq!(engine.play_preview(&data, -1.0), 0.0);
+    }
+
+    #[test]
+    fn test_measure_play_latency_is_small() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        let latency = engine.measure_play_latency();
+        assert!(latency < 1_000_000, "latency {}µs", latency);
+        assert!(builtin_click().num_frames() > 0);
+    }
+
+    #[test]
+    fn test_last_interval_none_initially() {
         let engine = SoundEngine::new().expect("Failed to create engine");
-        assert!(engine.active_pack_id().is_none());
+        assert!(engine.last_interval_ms().is_none());
+        assert_eq!(engine.typing_speed_wpm(), 0.0);
     }
 
     #[test]
-    fn test_cooldown_real_wait() {
+    fn test_last_interval_tracks_keypresses() {
         let mut engine = SoundEngine::new().expect("Failed to create engine");
-        engine.record_key_play("KeyA");
-        assert!(engine.is_key_in_cooldown("KeyA"));
-        thread::sleep(Duration::from_millis(90));
-        assert!(!engine.is_key_in_cooldown("KeyA"));
+        let start = Instant::now();
+        engine.play_key_at("KeyA", start);
+        assert!(engine.last_interval_ms().is_none());
+
+        engine.play_key_at("KeyB", start + Duration::from_millis(150));
+        assert_eq!(engine.last_interval_ms(), Some(150));
+    }
+
+    #[test]
+    fn test_typing_speed_wpm() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        let start = Instant::now();
+        // 200ms per keystroke = 300 chars/min = 60 WPM
+        for i in 0..10 {
+            engine.play_key_at("KeyA", start + Duration::from_millis(200 * i));
+        }
+        assert!((engine.typing_speed_wpm() - 60.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_typing_speed_ignores_pauses() {
+        let mut engine = SoundEngine::new().expect("Failed to create engine");
+        let start = Instant::now();
+        engine.play_key_at("KeyA", start);
+        engine.play_key_at("KeyB", start + Duration::from_millis(100));
+        engine.play_key_at("KeyC", start + Duration::from_millis(5100));
+        assert_eq!(engine.last_interval_ms(), Some(5000));
+        // 100ms per keystroke = 120 WPM; the 5s pause is left out
+        assert!((engine.typing_speed_wpm() - 120.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_unload_and_reload() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        assert_eq!(engine.sounds.len(), 1);
+
+        engine.unload();
+        assert!(engine.sounds.is_empty());
+        assert!(engine.is_unloaded());
+        assert_eq!(engine.active_pack_id().as_deref(), Some("test"));
+
+        engine.reload();
+        assert_eq!(engine.sounds.len(), 1);
+        assert!(!engine.is_unloaded());
+    }
+
+    #[test]
+    fn test_loaded_audio_bytes_tracks_decoded_sounds() {
+        let dir = TempDir::new().unwrap();
+        let mut engine = engine_with_pack(dir.path());
+        // 10ms of silence at 44.1kHz, 8 bytes per stereo f32 frame
+        assert_eq!(engine.loaded_audio_bytes(), 441 * 8);
+
+        let (mut rec, _) = recording_engine(dir.path());
+        assert_eq!(rec.loaded_audio_bytes(), 2 * 441 * 8);
+        rec.unload();
+        assert_eq!(rec.loaded_audio_bytes(), 0);
+
+        engine.unload();
+        assert_eq!(engine.loaded_audio_bytes(), 0);
+    }
+
+    #[test]
+    fn test_play_after_unload_requests_background_reload() {
+        let dir = TempDir::new().unwrap();
+        let (mut engine, player) = recording_engine(dir.path());
+        engine.unload();
+        assert!(engine.take_reload_request().is_none());
+
+        // The key is skipped instead of decoding on the input thread
+        engine.play_key_at("KeyA", Instant::now());
+        assert!(player.plays().is_empty());
+        let (ticket, pack) = engine.take_reload_request().unwrap();
+        assert_eq!(pack.id, "rec");
+
+        // Keys during the decode don't start a second one
+        engine.play_key_at("KeyB", Instant::now());
+        assert!(engine.take_reload_request().is_none());
+
+        assert!(engine.install_pack_if_current(ticket, LoadedPack::decode(pack)));
+        assert!(!engine.is_unloaded());
+        engine.play_key_at("KeyC", Instant::now());
+        assert_eq!(player.plays().len(), 1);
     }
 }
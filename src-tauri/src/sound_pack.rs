@@ -1,9 +1,22 @@
+use crate::error::KeysoundError;
+use crate::keyboard::is_known_key_name;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::{Component, Path, PathBuf};
+
+/// Newest pack.json schema this build understands.
+/// Bump when pack.json changes in a way older builds can't read.
+pub const MANIFEST_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SoundPack {
+    /// pack.json schema version (distinct from the author's `version`)
+    #[serde(default = "default_manifest_version")]
+    pub manifest_version: u32,
+
     pub id: String,
     pub name: String,
     #[serde(default)]
@@ -13,54 +26,249 @@ pub struct SoundPack {
     #[serde(default)]
     pub description: String,
 
+    /// Freeform author notes; only in the detailed view, not SoundPackInfo
+    #[serde(default)]
+    pub notes: String,
+
     /// "user" for user-created packs, None for bundled
     #[serde(default)]
     pub source: Option<String>,
 
+    /// Theme color for the pack card, as "#RRGGBB"
+    #[serde(default)]
+    pub color: Option<String>,
+
+    /// Play keyup sounds. Off silences release clicks without editing slots.
+    #[serde(default = "default_true")]
+    pub keyup_enabled: bool,
+
     pub defaults: SoundDefaults,
 
+    // BTreeMaps keep pack.json key order stable across writes
     #[serde(default)]
-    pub key_overrides: HashMap<String, KeySound>,
+    pub key_overrides: BTreeMap<String, KeySound>,
 
     #[serde(default)]
-    pub category_overrides: HashMap<String, CategoryOverride>,
+    pub category_overrides: BTreeMap<String, CategoryOverride>,
 
     /// Maps slot name -> original file name (for display in UI)
     #[serde(default)]
-    pub original_names: HashMap<String, String>,
+    pub original_names: BTreeMap<String, String>,
+
+    /// Maps slot name -> custom display label (for the pack editor)
+    #[serde(default)]
+    pub slot_labels: BTreeMap<String, String>,
+
+    /// Maps slot name -> when its sound plays (slots not listed play on press)
+    #[serde(default)]
+    pub slot_triggers: BTreeMap<String, TriggerMode>,
+
+    /// Maps sound path -> content hash, for spotting corrupted or altered files
+    #[serde(default)]
+    pub checksums: BTreeMap<String, String>,
+
+    /// Unix time (seconds) the pack was created in the app; None for bundled packs
+    #[serde(default)]
+    pub created_at: Option<u64>,
+
+    /// Unix time (seconds) pack.json was last written by the app
+    #[serde(default)]
+    pub modified_at: Option<u64>,
 
     /// Base directory of the sound pack (not serialized from JSON)
     #[serde(skip)]
     pub base_path: PathBuf,
+
+    /// Loaded straight from an arbitrary folder rather than installed. Such
+    /// packs aren't managed by the app: they're never copied, renamed, or
+    /// deleted. Set at load time only, never read from or written to pack.json.
+    #[serde(skip)]
+    pub sideloaded: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SoundDefaults {
+    /// Some minimal manifests omit it; they get the conventional path
+    #[serde(default = "default_keydown")]
     pub keydown: String,
     pub keyup: Option<String>,
     #[serde(default = "default_volume")]
     pub volume: f64,
+    /// Random per-press volume variation, as a fraction (0.1 = ±10%)
+    #[serde(default)]
+    pub volume_jitter: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct KeySound {
     pub keydown: Option<String>,
     pub keyup: Option<String>,
     pub volume: Option<f64>,
+    #[serde(default)]
+    pub volume_jitter: Option<f64>,
+    /// Wait this long (ms) before the keydown sound starts, for rhythm effects
+    #[serde(default)]
+    pub delay_ms: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CategoryOverride {
     pub keys: Vec<String>,
     pub keydown: Option<String>,
     pub keyup: Option<String>,
     pub volume: Option<f64>,
+    #[serde(default)]
+    pub volume_jitter: Option<f64>,
+    /// Some(false) silences every key in this category (None = enabled)
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+impl KeySound {
+    /// Whether this override sets nothing, leaving the key at its fallback.
+    pub fn is_noop(&self) -> bool {
+        self.keydown.is_none()
+            && self.keyup.is_none()
+            && self.volume.is_none()
+            && self.volume_jitter.is_none()
+            && self.delay_ms.is_none()
+    }
+}
+
+impl CategoryOverride {
+    /// Whether `key_name` belongs to this category
+    pub fn contains(&self, key_name: &str) -> bool {
+        self.keys.iter().any(|k| k == key_name)
+    }
+
+    /// Whether this category has no keys, or neither sets a sound or volume
+    /// nor silences its keys.
+    pub fn is_noop(&self) -> bool {
+        self.keys.is_empty()
+            || (self.keydown.is_none()
+                && self.keyup.is_none()
+                && self.volume.is_none()
+                && self.volume_jitter.is_none()
+                && self.enabled != Some(false))
+    }
 }
 
 fn default_volume() -> f64 {
     1.0
 }
 
+fn default_keydown() -> String {
+    "sounds/keydown.wav".into()
+}
+
+fn default_manifest_version() -> u32 {
+    1
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Every sound path referenced by a mapping, defaults first.
+pub(crate) fn mapping_paths<'a>(
+    defaults: &'a SoundDefaults,
+    key_overrides: &'a BTreeMap<String, KeySound>,
+    category_overrides: &'a BTreeMap<String, CategoryOverride>,
+) -> Vec<&'a str> {
+    let mut paths = vec![defaults.keydown.as_str()];
+    paths.extend(defaults.keyup.as_deref());
+    for ks in key_overrides.values() {
+        paths.extend(ks.keydown.as_deref());
+        paths.extend(ks.keyup.as_deref());
+    }
+    for cat in category_overrides.values() {
+        paths.extend(cat.keydown.as_deref());
+        paths.extend(cat.keyup.as_deref());
+    }
+    paths
+}
+
+/// Whether a manifest sound path stays inside the pack directory once `.` and
+/// `..` are resolved. Absolute paths never do.
+pub fn is_contained_path(path: &str) -> bool {
+    let mut depth = 0usize;
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    depth > 0
+}
+
+/// Id of the built-in pack used when the user hasn't chosen a default.
+pub const DEFAULT_PACK_ID: &str = "default";
+
+/// Highest volume multiplier a pack may specify. Anything louder is clamped on load.
+pub const MAX_PACK_VOLUME: f64 = 2.0;
+
+/// Clamp a pack volume into 0.0..=MAX_PACK_VOLUME, logging when it was out of range.
+fn clamp_pack_volume(volume: f64, pack_id: &str, field: &str) -> f64 {
+    let clamped = if volume.is_nan() {
+        default_volume()
+    } else {
+        volume.clamp(0.0, MAX_PACK_VOLUME)
+    };
+    if clamped != volume {
+        log::warn!(
+            "Pack '{}': {} volume {} out of range, clamped to {}",
+            pack_id,
+            field,
+            volume,
+            clamped
+        );
+    }
+    clamped
+}
+
+/// Largest volume jitter fraction a pack may specify.
+pub const MAX_VOLUME_JITTER: f64 = 0.5;
+
+/// Clamp a jitter fraction into 0.0..=MAX_VOLUME_JITTER (NaN means none).
+pub fn clamp_volume_jitter(jitter: f64) -> f64 {
+    if jitter.is_nan() {
+        0.0
+    } else {
+        jitter.clamp(0.0, MAX_VOLUME_JITTER)
+    }
+}
+
+/// When a slot's sound plays
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerMode {
+    /// On key press (keyup sounds still play on release)
+    #[default]
+    Press,
+    /// Only on key release, in place of the keyup sound
+    Release,
+    /// On press and again on release, in place of the keyup sound
+    Both,
+}
+
+impl TriggerMode {
+    pub fn on_press(self) -> bool {
+        self != Self::Release
+    }
+
+    pub fn on_release(self) -> bool {
+        self != Self::Press
+    }
+}
+
+/// Longest per-key delay before a keydown sound (ms)
+pub const MAX_KEY_DELAY_MS: u64 = 200;
+
 /// Info returned to the frontend for pack selection
 #[derive(Debug, Clone, Serialize)]
 pub struct SoundPackInfo {
@@ -70,6 +278,175 @@ pub struct SoundPackInfo {
     pub description: String,
     /// "user" for user-created packs, None for bundled
     pub source: Option<String>,
+    /// Theme color as "#RRGGBB"
+    pub color: Option<String>,
+    pub keyup_enabled: bool,
+    /// Loaded from an arbitrary folder, not managed by the app
+    pub sideloaded: bool,
+    /// Unix time (seconds), None if unknown
+    pub created_at: Option<u64>,
+    pub modified_at: Option<u64>,
+}
+
+/// Order for `sort_packs`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    /// Default pack, then user packs, then other bundled packs
+    #[default]
+    Default,
+    /// Name, A to Z
+    Name,
+    /// Newest first
+    Created,
+    /// Most recently edited first
+    Modified,
+    /// Largest on disk first
+    Size,
+}
+
+/// A problem found by `validate_pack`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PackIssue {
+    /// A sound file referenced by pack.json doesn't exist
+    MissingFile { path: String },
+    /// An override that changes nothing ("category:<name>" or "key:<KeyName>")
+    NoOpOverride { name: String },
+    /// A key name no keyboard event produces, so it never matches (likely a typo).
+    /// `name` is the override it appears in.
+    UnknownKey { name: String, key: String },
+}
+
+/// How much a `PackIssue` matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// Part of the pack won't play as written
+    Error,
+    /// Harmless, but probably not what the author meant
+    Warning,
+}
+
+impl PackIssue {
+    pub fn severity(&self) -> IssueSeverity {
+        match self {
+            PackIssue::MissingFile { .. } => IssueSeverity::Error,
+            PackIssue::NoOpOverride { .. } | PackIssue::UnknownKey { .. } => IssueSeverity::Warning,
+        }
+    }
+}
+
+/// Which layer of a pack decided a key's keydown sound
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "category", rename_all = "snake_case")]
+pub enum ResolveLayer {
+    /// Exact entry in `key_overrides`
+    KeyOverride,
+    /// Entry in `category_overrides` (category name)
+    Category(String),
+    /// Category that silences its keys (category name)
+    DisabledCategory(String),
+    /// Pack-wide `defaults`
+    Default,
+}
+
+/// Result of a dry-run key resolution
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolveTrace {
+    pub key: String,
+    /// Absolute path of the keydown sound, None if the key is silent
+    pub keydown: Option<PathBuf>,
+    pub layer: ResolveLayer,
+    pub volume: f64,
+}
+
+/// Which keys have a sound of their own vs. fall through to the pack default
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Coverage {
+    /// Resolved by a key or category override
+    pub covered: Vec<String>,
+    /// Only the pack-wide default applies
+    pub default_only: Vec<String>,
+    /// Silenced by a disabled category
+    pub silenced: Vec<String>,
+}
+
+/// What one key plays once every fallback is applied, for `SoundPack::flatten`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FlatKey {
+    pub key: String,
+    /// Keydown sound relative to the pack folder, None if the key is silent
+    pub keydown_path: Option<String>,
+    pub volume: f64,
+    pub source_layer: ResolveLayer,
+}
+
+/// A pack's sound configuration at one point in time (no file bytes), for
+/// an "unsaved changes" indicator or reviewing edits with `PackSnapshot::diff`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackSnapshot {
+    pub defaults: SoundDefaults,
+    pub key_overrides: BTreeMap<String, KeySound>,
+    pub category_overrides: BTreeMap<String, CategoryOverride>,
+}
+
+/// One field that differs between two snapshots. `None` means unset.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlotDiff {
+    /// "defaults", "key:<KeyName>" or "category:<name>"
+    pub slot: String,
+    /// e.g. "keydown", "volume", "keys"
+    pub field: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+impl PackSnapshot {
+    /// Every set field as (slot, field) -> value.
+    fn fields(&self) -> BTreeMap<(String, String), serde_json::Value> {
+        let mut fields = BTreeMap::new();
+        let mut add = |slot: String, value: serde_json::Value| {
+            if let serde_json::Value::Object(map) = value {
+                for (field, v) in map.into_iter().filter(|(_, v)| !v.is_null()) {
+                    fields.insert((slot.clone(), field), v);
+                }
+            }
+        };
+        add("defaults".into(), serde_json::json!(self.defaults));
+        for (key, sound) in &self.key_overrides {
+            add(format!("key:{}", key), serde_json::json!(sound));
+        }
+        for (name, cat) in &self.category_overrides {
+            add(format!("category:{}", name), serde_json::json!(cat));
+        }
+        fields
+    }
+
+    /// What changed going from this snapshot to `current`, ordered by slot.
+    pub fn diff(&self, current: &PackSnapshot) -> Vec<SlotDiff> {
+        let mut before = self.fields();
+        let mut diffs = Vec::new();
+        for ((slot, field), after) in current.fields() {
+            let old = before.remove(&(slot.clone(), field.clone()));
+            if old.as_ref() != Some(&after) {
+                diffs.push(SlotDiff {
+                    slot,
+                    field,
+                    before: old,
+                    after: Some(after),
+                });
+            }
+        }
+        diffs.extend(before.into_iter().map(|((slot, field), old)| SlotDiff {
+            slot,
+            field,
+            before: Some(old),
+            after: None,
+        }));
+        diffs.sort_by(|a, b| (&a.slot, &a.field).cmp(&(&b.slot, &b.field)));
+        diffs
+    }
 }
 
 impl SoundPack {
@@ -79,37 +456,297 @@ impl SoundPack {
         if !manifest_path.exists() {
             return Err(format!("No pack.json found in {}", dir.display()));
         }
+        if !manifest_path.is_file() {
+            return Err(format!("pack.json is not a file in {}", dir.display()));
+        }
 
         let contents = std::fs::read_to_string(&manifest_path)
             .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+        Self::parse(&contents, dir, &manifest_path.display().to_string())
+    }
+
+    /// Load a pack from any folder without installing it, marked as sideloaded.
+    pub fn load_sideloaded(dir: &Path) -> Result<Self, String> {
+        let mut pack = Self::load(dir)?;
+        pack.sideloaded = true;
+        Ok(pack)
+    }
+
+    pub fn is_sideloaded(&self) -> bool {
+        self.sideloaded
+    }
 
-        let mut pack: SoundPack = serde_json::from_str(&contents)
-            .map_err(|e| format!("Failed to parse {}: {}", manifest_path.display(), e))?;
+    /// Parse pack.json `contents` for a pack in `dir`. `source` names the
+    /// manifest in error messages, which include serde's line and column.
+    pub fn parse(contents: &str, dir: &Path, source: &str) -> Result<Self, String> {
+        let value: serde_json::Value = serde_json::from_str(contents)
+            .map_err(|e| format!("Failed to parse {}: {}", source, e))?;
+
+        // Check the schema version before parsing the rest, which may not match ours
+        let manifest_version = value
+            .get("manifest_version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(u64::from(default_manifest_version()));
+        if manifest_version > u64::from(MANIFEST_VERSION) {
+            return Err("This pack requires a newer version of KeySound".into());
+        }
+        if value
+            .get("defaults")
+            .is_some_and(|d| d.get("keydown").is_none())
+        {
+            log::warn!(
+                "{} has no defaults.keydown, using {}",
+                source,
+                default_keydown()
+            );
+        }
+
+        let mut pack: SoundPack = serde_json::from_value(value)
+            .map_err(|e| format!("Failed to parse {}: {}", source, e))?;
 
         pack.base_path = dir.to_path_buf();
+        pack.check_sound_paths()?;
+        pack.clamp_volumes();
+        for (name, key) in pack.unknown_keys() {
+            log::warn!("{}: unknown key '{}' in {}", source, key, name);
+        }
         Ok(pack)
     }
 
+    /// Every sound path referenced by the manifest (relative to the pack dir).
+    pub fn sound_paths(&self) -> Vec<&str> {
+        mapping_paths(
+            &self.defaults,
+            &self.key_overrides,
+            &self.category_overrides,
+        )
+    }
+
+    /// Reject sound paths that point outside the pack directory.
+    pub fn check_sound_paths(&self) -> Result<(), String> {
+        for path in self.sound_paths() {
+            if !is_contained_path(path) {
+                return Err(format!(
+                    "Pack '{}': sound path '{}' points outside the pack directory",
+                    self.id, path
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Clamp every volume field so a hand-edited pack can't produce huge gains.
+    pub(crate) fn clamp_volumes(&mut self) {
+        let id = self.id.clone();
+        self.defaults.volume = clamp_pack_volume(self.defaults.volume, &id, "default");
+        self.defaults.volume_jitter = clamp_volume_jitter(self.defaults.volume_jitter);
+        for (key, key_sound) in self.key_overrides.iter_mut() {
+            if let Some(vol) = key_sound.volume {
+                key_sound.volume = Some(clamp_pack_volume(vol, &id, key));
+            }
+            key_sound.volume_jitter = key_sound.volume_jitter.map(clamp_volume_jitter);
+            key_sound.delay_ms = key_sound.delay_ms.map(|ms| ms.min(MAX_KEY_DELAY_MS));
+        }
+        for (name, cat) in self.category_overrides.iter_mut() {
+            if let Some(vol) = cat.volume {
+                cat.volume = Some(clamp_pack_volume(vol, &id, name));
+            }
+            cat.volume_jitter = cat.volume_jitter.map(clamp_volume_jitter);
+        }
+    }
+
     /// Get the absolute path to the sound file for a keydown event
     pub fn resolve_keydown(&self, key_name: &str) -> Option<PathBuf> {
+        self.resolve_keydown_layer(key_name).0
+    }
+
+    /// Resolve a keydown sound along with the layer that decided it.
+    fn resolve_keydown_layer(&self, key_name: &str) -> (Option<PathBuf>, ResolveLayer) {
         // 1. Check exact key override
         if let Some(key_sound) = self.key_overrides.get(key_name) {
             if let Some(ref path) = key_sound.keydown {
+                return (Some(self.base_path.join(path)), ResolveLayer::KeyOverride);
+            }
+        }
+
+        // 2. Check category overrides (a disabled category silences its keys)
+        for (name, cat) in &self.category_overrides {
+            if cat.contains(key_name) {
+                if cat.enabled == Some(false) {
+                    return (None, ResolveLayer::DisabledCategory(name.clone()));
+                }
+                if let Some(ref path) = cat.keydown {
+                    return (
+                        Some(self.base_path.join(path)),
+                        ResolveLayer::Category(name.clone()),
+                    );
+                }
+            }
+        }
+
+        // 3. Fall back to default
+        (
+            Some(self.base_path.join(&self.defaults.keydown)),
+            ResolveLayer::Default,
+        )
+    }
+
+    /// Editor slot ("default", "space", "key:KeyA", ...) whose keydown sound
+    /// `key_name` plays. None for categories without a slot or disabled ones.
+    pub fn slot_of(&self, key_name: &str) -> Option<String> {
+        match self.resolve_keydown_layer(key_name).1 {
+            ResolveLayer::KeyOverride => Some(match key_name {
+                "Space" => "space".into(),
+                "Return" => "enter".into(),
+                _ => format!("key:{}", key_name),
+            }),
+            ResolveLayer::Category(name) => match name.as_str() {
+                "modifiers" => Some("modifier".into()),
+                "delete" => Some("backspace".into()),
+                _ => None,
+            },
+            ResolveLayer::DisabledCategory(_) => None,
+            ResolveLayer::Default => Some("default".into()),
+        }
+    }
+
+    /// When `key_name`'s keydown sound plays, per its slot's trigger mode.
+    pub fn resolve_trigger(&self, key_name: &str) -> TriggerMode {
+        self.slot_of(key_name)
+            .and_then(|slot| self.slot_triggers.get(&slot).copied())
+            .unwrap_or_default()
+    }
+
+    /// Sound for a synthetic scroll key ("ScrollUp"/"ScrollDown"). Only an explicit
+    /// key or category override counts, so packs without one stay silent on scroll.
+    pub fn resolve_scroll(&self, scroll_key: &str) -> Option<PathBuf> {
+        match self.resolve_keydown_layer(scroll_key) {
+            (_, ResolveLayer::Default) => None,
+            (path, _) => path,
+        }
+    }
+
+    /// Get the absolute path to the sound file for a keyup event.
+    /// Same layering as keydown; None when no layer has a keyup sound.
+    pub fn resolve_keyup(&self, key_name: &str) -> Option<PathBuf> {
+        // 1. Check exact key override
+        if let Some(key_sound) = self.key_overrides.get(key_name) {
+            if let Some(ref path) = key_sound.keyup {
                 return Some(self.base_path.join(path));
             }
         }
 
-        // 2. Check category overrides
+        // 2. Check category overrides (a disabled category silences its keys)
         for cat in self.category_overrides.values() {
-            if cat.keys.iter().any(|k| k == key_name) {
-                if let Some(ref path) = cat.keydown {
+            if cat.contains(key_name) {
+                if cat.enabled == Some(false) {
+                    return None;
+                }
+                if let Some(ref path) = cat.keyup {
                     return Some(self.base_path.join(path));
                 }
             }
         }
 
         // 3. Fall back to default
-        Some(self.base_path.join(&self.defaults.keydown))
+        self.defaults
+            .keyup
+            .as_ref()
+            .map(|path| self.base_path.join(path))
+    }
+
+    /// Name of the first category `key_name` belongs to, if any.
+    pub fn category_of(&self, key_name: &str) -> Option<&str> {
+        self.category_overrides
+            .iter()
+            .find(|(_, cat)| cat.contains(key_name))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Explain how a key resolves without playing anything (for pack authors).
+    pub fn resolve_trace(&self, key_name: &str) -> ResolveTrace {
+        let (keydown, layer) = self.resolve_keydown_layer(key_name);
+        ResolveTrace {
+            key: key_name.to_string(),
+            keydown,
+            layer,
+            volume: self.resolve_volume(key_name),
+        }
+    }
+
+    /// Names of overrides that change nothing, as "key:<KeyName>" / "category:<name>".
+    pub fn noop_overrides(&self) -> Vec<String> {
+        let keys = self
+            .key_overrides
+            .iter()
+            .filter(|(_, ks)| ks.is_noop())
+            .map(|(key, _)| format!("key:{}", key));
+        let categories = self
+            .category_overrides
+            .iter()
+            .filter(|(_, cat)| cat.is_noop())
+            .map(|(name, _)| format!("category:{}", name));
+        keys.chain(categories).collect()
+    }
+
+    /// Keys in overrides that no key event is named, as (override, key) pairs;
+    /// the override is "key:<KeyName>" or "category:<name>".
+    pub fn unknown_keys(&self) -> Vec<(String, String)> {
+        let keys = self
+            .key_overrides
+            .keys()
+            .filter(|key| !is_known_key_name(key))
+            .map(|key| (format!("key:{}", key), key.clone()));
+        let categories = self.category_overrides.iter().flat_map(|(name, cat)| {
+            cat.keys
+                .iter()
+                .filter(|key| !is_known_key_name(key))
+                .map(move |key| (format!("category:{}", name), key.clone()))
+        });
+        keys.chain(categories).collect()
+    }
+
+    /// Current defaults and overrides, to diff against later.
+    pub fn snapshot(&self) -> PackSnapshot {
+        PackSnapshot {
+            defaults: self.defaults.clone(),
+            key_overrides: self.key_overrides.clone(),
+            category_overrides: self.category_overrides.clone(),
+        }
+    }
+
+    /// Sort `keys` by the layer their keydown sound resolves from.
+    pub fn coverage(&self, keys: &[&str]) -> Coverage {
+        let mut report = Coverage::default();
+        for key in keys {
+            let bucket = match self.resolve_keydown_layer(key).1 {
+                ResolveLayer::KeyOverride | ResolveLayer::Category(_) => &mut report.covered,
+                ResolveLayer::DisabledCategory(_) => &mut report.silenced,
+                ResolveLayer::Default => &mut report.default_only,
+            };
+            bucket.push(key.to_string());
+        }
+        report
+    }
+
+    /// The sound and volume each of `keys` resolves to, with the layer that
+    /// decided it (for documenting or exporting a pack).
+    pub fn flatten(&self, keys: &[&str]) -> Vec<FlatKey> {
+        keys.iter()
+            .map(|key| {
+                let (keydown, source_layer) = self.resolve_keydown_layer(key);
+                let keydown_path = keydown.map(|path| {
+                    let rel = path.strip_prefix(&self.base_path).unwrap_or(&path);
+                    rel.to_string_lossy().replace('\\', "/")
+                });
+                FlatKey {
+                    key: key.to_string(),
+                    keydown_path,
+                    volume: self.resolve_volume(key),
+                    source_layer,
+                }
+            })
+            .collect()
     }
 
     /// Get the volume for a specific key
@@ -123,7 +760,7 @@ impl SoundPack {
 
         // 2. Check category overrides
         for cat in self.category_overrides.values() {
-            if cat.keys.iter().any(|k| k == key_name) {
+            if cat.contains(key_name) {
                 if let Some(vol) = cat.volume {
                     return vol;
                 }
@@ -134,6 +771,30 @@ impl SoundPack {
         self.defaults.volume
     }
 
+    /// Get the volume jitter fraction for a specific key (same precedence as volume)
+    pub fn resolve_volume_jitter(&self, key_name: &str) -> f64 {
+        if let Some(jitter) = self
+            .key_overrides
+            .get(key_name)
+            .and_then(|k| k.volume_jitter)
+        {
+            return jitter;
+        }
+        self.category_overrides
+            .values()
+            .filter(|cat| cat.contains(key_name))
+            .find_map(|cat| cat.volume_jitter)
+            .unwrap_or(self.defaults.volume_jitter)
+    }
+
+    /// Delay (ms) before a key's keydown sound starts; only key overrides set one.
+    pub fn resolve_delay_ms(&self, key_name: &str) -> u64 {
+        self.key_overrides
+            .get(key_name)
+            .and_then(|k| k.delay_ms)
+            .unwrap_or(0)
+    }
+
     pub fn info(&self) -> SoundPackInfo {
         SoundPackInfo {
             id: self.id.clone(),
@@ -141,26 +802,88 @@ impl SoundPack {
             author: self.author.clone(),
             description: self.description.clone(),
             source: self.source.clone(),
+            color: self.color.clone(),
+            keyup_enabled: self.keyup_enabled,
+            sideloaded: self.sideloaded,
+            created_at: self.created_at,
+            modified_at: self.modified_at,
         }
     }
 }
 
+/// Check a loaded pack for problems that don't stop it from loading.
+pub fn validate_pack(pack: &SoundPack) -> Vec<PackIssue> {
+    let mut paths = pack.sound_paths();
+    paths.sort_unstable();
+    paths.dedup();
+    let missing = paths
+        .into_iter()
+        .filter(|path| !pack.base_path.join(path).is_file())
+        .map(|path| PackIssue::MissingFile {
+            path: path.to_string(),
+        });
+    let noop = pack
+        .noop_overrides()
+        .into_iter()
+        .map(|name| PackIssue::NoOpOverride { name });
+    let unknown = pack
+        .unknown_keys()
+        .into_iter()
+        .map(|(name, key)| PackIssue::UnknownKey { name, key });
+    missing.chain(noop).chain(unknown).collect()
+}
+
+/// Check an edited pack.json for the pack in `pack_dir` without writing it.
+pub fn validate_manifest_json(json: &str, pack_dir: &Path) -> Result<Vec<PackIssue>, String> {
+    let pack = SoundPack::parse(json, pack_dir, "pack.json")?;
+    Ok(validate_pack(&pack))
+}
+
+/// Subfolder of the user packs directory that broken packs are moved into.
+/// Discovery skips it.
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// A pack folder that discovery skipped because it failed to load
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnloadablePack {
+    pub dir: PathBuf,
+    pub error: String,
+}
+
 /// Discover all sound packs in a directory
 pub fn discover_packs(dir: &Path) -> Vec<SoundPack> {
+    scan_packs(dir).0
+}
+
+/// Every pack folder under `dirs` that failed to load, sorted by path.
+pub fn find_unloadable_packs(dirs: &[&Path]) -> Vec<UnloadablePack> {
+    let mut unloadable: Vec<UnloadablePack> =
+        dirs.iter().flat_map(|dir| scan_packs(dir).1).collect();
+    unloadable.sort_by(|a, b| a.dir.cmp(&b.dir));
+    unloadable
+}
+
+/// Load every pack folder in `dir`, keeping the ones that failed separately.
+fn scan_packs(dir: &Path) -> (Vec<SoundPack>, Vec<UnloadablePack>) {
     let mut packs = Vec::new();
+    let mut unloadable = Vec::new();
 
     if !dir.exists() {
-        return packs;
+        return (packs, unloadable);
     }
 
     if let Ok(entries) = std::fs::read_dir(dir) {
         for entry in entries.flatten() {
             let path = entry.path();
-            if path.is_dir() {
+            if path.is_dir() && entry.file_name() != QUARANTINE_DIR {
                 match SoundPack::load(&path) {
                     Ok(pack) => packs.push(pack),
                     Err(e) => {
                         log::warn!("Failed to load sound pack from {}: {}", path.display(), e);
+                        unloadable.push(UnloadablePack {
+                            dir: path,
+                            error: e,
+                        });
                     }
                 }
             }
@@ -170,26 +893,37 @@ pub fn discover_packs(dir: &Path) -> Vec<SoundPack> {
     packs.sort_by(|a, b| {
         // "default" pack always comes first, then alphabetical by id
         match (a.id.as_str(), b.id.as_str()) {
-            ("default", "default") => std::cmp::Ordering::Equal,
-            ("default", _) => std::cmp::Ordering::Less,
-            (_, "default") => std::cmp::Ordering::Greater,
+            (DEFAULT_PACK_ID, DEFAULT_PACK_ID) => std::cmp::Ordering::Equal,
+            (DEFAULT_PACK_ID, _) => std::cmp::Ordering::Less,
+            (_, DEFAULT_PACK_ID) => std::cmp::Ordering::Greater,
             _ => a.id.cmp(&b.id),
         }
     });
-    packs
+    (packs, unloadable)
 }
 
 /// Discover packs from both bundled and user directories.
 /// Ordering: default first, then user/custom packs (alphabetical), then other bundled (alphabetical).
 pub fn discover_all_packs(bundled_dir: &Path, user_dir: &Path) -> Vec<SoundPack> {
-    let bundled = discover_packs(bundled_dir);
-    let user = discover_packs(user_dir);
+    scan_all_packs(bundled_dir, user_dir).0
+}
+
+/// `discover_all_packs` and `find_unloadable_packs` from a single scan of
+/// both directories.
+pub fn scan_all_packs(
+    bundled_dir: &Path,
+    user_dir: &Path,
+) -> (Vec<SoundPack>, Vec<UnloadablePack>) {
+    let (bundled, mut unloadable) = scan_packs(bundled_dir);
+    let (user, user_unloadable) = scan_packs(user_dir);
+    unloadable.extend(user_unloadable);
+    unloadable.sort_by(|a, b| a.dir.cmp(&b.dir));
 
     let mut all = Vec::with_capacity(bundled.len() + user.len());
 
     // Default pack first (from bundled)
     for pack in &bundled {
-        if pack.id == "default" {
+        if pack.id == DEFAULT_PACK_ID {
             all.push(pack.clone());
         }
     }
@@ -199,12 +933,217 @@ pub fn discover_all_packs(bundled_dir: &Path, user_dir: &Path) -> Vec<SoundPack>
 
     // Then other bundled packs (alphabetical, already sorted)
     for pack in &bundled {
-        if pack.id != "default" {
+        if pack.id != DEFAULT_PACK_ID {
             all.push(pack.clone());
         }
     }
 
-    all
+    (all, unloadable)
+}
+
+/// Pack to fall back to at startup or after the active pack is deleted: the
+/// user's chosen default if it still exists, then the built-in "default", then
+/// the first pack in `packs`.
+pub fn choose_default_pack(packs: Vec<SoundPack>, preferred: Option<&str>) -> Option<SoundPack> {
+    let position = |id: &str| packs.iter().position(|p| p.id == id);
+    let index = preferred
+        .and_then(position)
+        .or_else(|| position(DEFAULT_PACK_ID))
+        .unwrap_or(0);
+    packs.into_iter().nth(index)
+}
+
+/// Reorder packs from `discover_all_packs` by `sort`. The sort is stable, so
+/// ties (and packs without a timestamp, which go last) keep the default order.
+pub fn sort_packs(packs: &mut [SoundPack], sort: SortKey) {
+    // Newest first, unknown last
+    let newest = |a: Option<u64>, b: Option<u64>| b.cmp(&a);
+    match sort {
+        SortKey::Default => {}
+        SortKey::Name => packs.sort_by_cached_key(|p| p.name.to_lowercase()),
+        SortKey::Created => packs.sort_by(|a, b| newest(a.created_at, b.created_at)),
+        SortKey::Modified => packs.sort_by(|a, b| newest(a.modified_at, b.modified_at)),
+        SortKey::Size => packs.sort_by_cached_key(|p| std::cmp::Reverse(dir_size(&p.base_path))),
+    }
+}
+
+/// Total size in bytes of the files under `dir`.
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .sum()
+}
+
+/// Find a pack's directory by id: bundled packs first, then user packs.
+pub fn resolve_pack_dir(bundled_dir: &Path, user_dir: &Path, pack_id: &str) -> Option<PathBuf> {
+    [bundled_dir, user_dir]
+        .into_iter()
+        .map(|root| root.join(pack_id))
+        .find(|dir| dir.join("pack.json").exists())
+}
+
+/// Find and load a pack by id, telling a missing pack (`NotFound`) apart from
+/// one whose pack.json is present but unreadable (`LoadFailed`).
+pub fn load_pack_by_id(
+    bundled_dir: &Path,
+    user_dir: &Path,
+    pack_id: &str,
+) -> Result<SoundPack, KeysoundError> {
+    let dir = resolve_pack_dir(bundled_dir, user_dir, pack_id)
+        .ok_or_else(|| KeysoundError::NotFound(format!("Sound pack '{}' not found", pack_id)))?;
+    SoundPack::load(&dir).map_err(KeysoundError::LoadFailed)
+}
+
+/// Directory bundled packs are read from. With `in_place`, packs are read
+/// straight from the (read-only) resource dir instead of the app-data copy.
+pub fn bundled_packs_root(resource_packs: &Path, app_data_copy: &Path, in_place: bool) -> PathBuf {
+    if in_place && resource_packs.is_dir() {
+        resource_packs.to_path_buf()
+    } else {
+        app_data_copy.to_path_buf()
+    }
+}
+
+/// Collect every file under `dir` (recursively) as paths relative to `prefix`.
+fn collect_files(dir: &Path, prefix: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let rel = prefix.join(entry.file_name());
+        if path.is_dir() {
+            collect_files(&path, &rel, out)?;
+        } else {
+            out.push(rel);
+        }
+    }
+    Ok(())
+}
+
+/// Hash the contents of a pack directory: pack.json plus every other file in
+/// the pack. Fields that differ between copies of the same pack (`id`, `name`,
+/// `source`, timestamps and stored checksums) are left out.
+pub fn hash_pack_contents(dir: &Path) -> std::io::Result<u64> {
+    let mut hasher = DefaultHasher::new();
+
+    let manifest = std::fs::read_to_string(dir.join("pack.json"))?;
+    let mut manifest: serde_json::Value = serde_json::from_str(&manifest)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    if let Some(obj) = manifest.as_object_mut() {
+        for field in [
+            "id",
+            "name",
+            "source",
+            "created_at",
+            "modified_at",
+            "checksums",
+        ] {
+            obj.remove(field);
+        }
+    }
+    manifest.to_string().hash(&mut hasher);
+
+    let mut files = Vec::new();
+    collect_files(dir, Path::new(""), &mut files)?;
+    files.retain(|f| f != Path::new("pack.json"));
+    files.sort();
+
+    for rel in files {
+        rel.to_string_lossy().replace('\\', "/").hash(&mut hasher);
+        std::fs::read(dir.join(&rel))?.hash(&mut hasher);
+    }
+
+    Ok(hasher.finish())
+}
+
+/// Fingerprint of every file under `dir` that stays the same across runs and
+/// builds: pack.json files by content, other files by size and modification
+/// time, so audio is never read. Hex-encoded FNV-1a.
+pub fn fingerprint_dir(dir: &Path) -> std::io::Result<String> {
+    let mut files = Vec::new();
+    collect_files(dir, Path::new(""), &mut files)?;
+    files.sort();
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for rel in files {
+        fnv1a(
+            &mut hash,
+            rel.to_string_lossy().replace('\\', "/").as_bytes(),
+        );
+        fnv1a(&mut hash, &[0]);
+        let path = dir.join(&rel);
+        if rel.file_name().is_some_and(|name| name == "pack.json") {
+            fnv1a(&mut hash, &std::fs::read(&path)?);
+        } else {
+            let meta = std::fs::metadata(&path)?;
+            let mtime = meta
+                .modified()?
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default();
+            fnv1a(&mut hash, &meta.len().to_le_bytes());
+            fnv1a(&mut hash, &mtime.as_nanos().to_le_bytes());
+        }
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Fold `bytes` into a 64-bit FNV-1a hash.
+fn fnv1a(hash: &mut u64, bytes: &[u8]) {
+    for &byte in bytes {
+        *hash ^= u64::from(byte);
+        *hash = hash.wrapping_mul(FNV_PRIME);
+    }
+}
+
+/// SHA-256 of one file as lowercase hex, as stored in a pack's `checksums`.
+pub fn hash_file(path: &Path) -> std::io::Result<String> {
+    let digest = Sha256::digest(std::fs::read(path)?);
+    Ok(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Whether `hash` has the shape `hash_file` produces. Older packs stored a
+/// shorter, toolchain-dependent hash that can't be checked.
+pub fn is_current_checksum(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Group packs from both directories whose contents are identical.
+/// Only groups containing more than one pack are returned.
+pub fn group_duplicate_packs(bundled_dir: &Path, user_dir: &Path) -> Vec<Vec<String>> {
+    let mut groups: HashMap<u64, Vec<String>> = HashMap::new();
+
+    for pack in discover_all_packs(bundled_dir, user_dir) {
+        match hash_pack_contents(&pack.base_path) {
+            Ok(hash) => groups.entry(hash).or_default().push(pack.id),
+            Err(e) => {
+                log::warn!(
+                    "Failed to hash sound pack {}: {}",
+                    pack.base_path.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    let mut duplicates: Vec<Vec<String>> = groups
+        .into_values()
+        .filter(|ids| ids.len() > 1)
+        .map(|mut ids| {
+            ids.sort();
+            ids
+        })
+        .collect();
+    duplicates.sort();
+    duplicates
 }
 
 #[cfg(test)]
@@ -230,6 +1169,16 @@ mod tests {
         fs::write(pack_dir.join("pack.json"), serde_json::to_string(&manifest).unwrap()).unwrap();
     }
 
+    #[test]
+    fn test_bundled_packs_validate_cleanly() {
+        let bundled = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/soundpacks");
+        let packs = discover_packs(&bundled);
+        assert!(!packs.is_empty());
+        for pack in packs {
+            assert_eq!(validate_pack(&pack), vec![], "pack {}", pack.id);
+        }
+    }
+
     #[test]
     fn test_discover_packs_empty() {
         let dir = TempDir::new().unwrap();
@@ -243,6 +1192,46 @@ mod tests {
         assert!(packs.is_empty());
     }
 
+    #[test]
+    fn test_find_unloadable_packs_lists_malformed_pack() {
+        let dir = TempDir::new().unwrap();
+        create_pack(dir.path(), "good", Some("user"));
+        create_pack(dir.path(), "broken", Some("user"));
+        fs::write(dir.path().join("broken").join("pack.json"), "{ \"id\": ").unwrap();
+        // Already-quarantined packs aren't reported again
+        create_pack(&dir.path().join(QUARANTINE_DIR), "old", Some("user"));
+        fs::write(
+            dir.path()
+                .join(QUARANTINE_DIR)
+                .join("old")
+                .join("pack.json"),
+            "",
+        )
+        .unwrap();
+
+        let ids: Vec<String> = discover_packs(dir.path())
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(ids, ["good"]);
+
+        let unloadable = find_unloadable_packs(&[dir.path()]);
+        assert_eq!(unloadable.len(), 1);
+        assert_eq!(unloadable[0].dir, dir.path().join("broken"));
+        assert!(unloadable[0].error.contains("pack.json"));
+
+        // One scan over both roots gives the same packs and failures
+        let bundled = TempDir::new().unwrap();
+        create_pack(bundled.path(), DEFAULT_PACK_ID, None);
+        let (packs, unloadable) = scan_all_packs(bundled.path(), dir.path());
+        let ids: Vec<&str> = packs.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, [DEFAULT_PACK_ID, "good"]);
+        assert_eq!(
+            unloadable,
+            find_unloadable_packs(&[bundled.path(), dir.path()])
+        );
+    }
+
     #[test]
     fn test_discover_packs_default_first() {
         let dir = TempDir::new().unwrap();
@@ -255,6 +1244,88 @@ mod tests {
         assert_eq!(packs[0].id, "default");
     }
 
+    #[test]
+    fn test_resolve_pack_dir_from_resource_path() {
+        let resources = TempDir::new().unwrap();
+        let app_data = TempDir::new().unwrap();
+        let user = TempDir::new().unwrap();
+        let resource_packs = resources.path().join("resources").join("soundpacks");
+        create_pack(&resource_packs, "default", None);
+        create_pack(user.path(), "mine", Some("user"));
+
+        let bundled = bundled_packs_root(&resource_packs, app_data.path(), true);
+        assert_eq!(bundled, resource_packs);
+        assert_eq!(
+            resolve_pack_dir(&bundled, user.path(), "default"),
+            Some(resource_packs.join("default"))
+        );
+        assert_eq!(
+            resolve_pack_dir(&bundled, user.path(), "mine"),
+            Some(user.path().join("mine"))
+        );
+        assert_eq!(resolve_pack_dir(&bundled, user.path(), "missing"), None);
+
+        let ids: Vec<String> = discover_all_packs(&bundled, user.path())
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(ids, vec!["default", "mine"]);
+    }
+
+    #[test]
+    fn test_load_pack_by_id_not_found() {
+        let bundled = TempDir::new().unwrap();
+        let user = TempDir::new().unwrap();
+        // A folder without pack.json counts as missing
+        fs::create_dir_all(user.path().join("empty")).unwrap();
+
+        for id in ["missing", "empty"] {
+            match load_pack_by_id(bundled.path(), user.path(), id) {
+                Err(KeysoundError::NotFound(msg)) => assert!(msg.contains(id)),
+                other => panic!("expected NotFound, got {:?}", other.map(|p| p.id)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_pack_by_id_parse_failure() {
+        let bundled = TempDir::new().unwrap();
+        let user = TempDir::new().unwrap();
+        create_pack(user.path(), "mine", Some("user"));
+        assert_eq!(
+            load_pack_by_id(bundled.path(), user.path(), "mine")
+                .unwrap()
+                .id,
+            "mine"
+        );
+
+        fs::write(user.path().join("mine").join("pack.json"), "{ not json").unwrap();
+        match load_pack_by_id(bundled.path(), user.path(), "mine") {
+            Err(KeysoundError::LoadFailed(msg)) => assert!(msg.contains("pack.json")),
+            other => panic!("expected LoadFailed, got {:?}", other.map(|p| p.id)),
+        }
+    }
+
+    #[test]
+    fn test_bundled_packs_root_falls_back_to_copy() {
+        let resources = TempDir::new().unwrap();
+        let app_data = TempDir::new().unwrap();
+        let resource_packs = resources.path().join("soundpacks");
+
+        // Not in place: always the app-data copy
+        fs::create_dir_all(&resource_packs).unwrap();
+        assert_eq!(
+            bundled_packs_root(&resource_packs, app_data.path(), false),
+            app_data.path()
+        );
+        // In place but resources missing: the copy
+        let missing = resources.path().join("nope");
+        assert_eq!(
+            bundled_packs_root(&missing, app_data.path(), true),
+            app_data.path()
+        );
+    }
+
     #[test]
     fn test_discover_all_packs_ordering() {
         let bundled = TempDir::new().unwrap();
@@ -277,6 +1348,69 @@ mod tests {
         assert_eq!(all[4].id, "beta");
     }
 
+    #[test]
+    fn test_choose_default_pack_fallback_chain() {
+        let bundled = TempDir::new().unwrap();
+        let user = TempDir::new().unwrap();
+        create_pack(bundled.path(), "default", None);
+        create_pack(bundled.path(), "piano", None);
+        create_pack(user.path(), "mine", Some("user"));
+        let packs = || discover_all_packs(bundled.path(), user.path());
+        let chosen = |packs, preferred| choose_default_pack(packs, preferred).map(|p| p.id);
+
+        // The user's choice wins while it exists
+        assert_eq!(chosen(packs(), Some("mine")).as_deref(), Some("mine"));
+        // A missing choice falls back to "default"
+        assert_eq!(chosen(packs(), Some("deleted")).as_deref(), Some("default"));
+        assert_eq!(chosen(packs(), None).as_deref(), Some("default"));
+
+        // Without "default", the first discovered pack
+        fs::remove_dir_all(bundled.path().join("default")).unwrap();
+        assert_eq!(chosen(packs(), Some("deleted")).as_deref(), Some("mine"));
+        assert_eq!(chosen(Vec::new(), Some("mine")), None);
+    }
+
+    #[test]
+    fn test_sort_packs_by_created_and_modified() {
+        let dir = TempDir::new().unwrap();
+        for (id, created, modified) in [
+            ("old", Some(100), Some(900)),
+            ("new", Some(300), Some(300)),
+            ("bundled", None, None),
+            ("mid", Some(200), Some(500)),
+        ] {
+            create_pack(dir.path(), id, None);
+            let path = dir.path().join(id).join("pack.json");
+            let mut manifest: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+            manifest["created_at"] = serde_json::json!(created);
+            manifest["modified_at"] = serde_json::json!(modified);
+            fs::write(&path, manifest.to_string()).unwrap();
+        }
+        let ids = |sort| {
+            let mut packs = discover_packs(dir.path());
+            sort_packs(&mut packs, sort);
+            packs.into_iter().map(|p| p.id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(SortKey::Created), ["new", "mid", "old", "bundled"]);
+        assert_eq!(ids(SortKey::Modified), ["old", "mid", "new", "bundled"]);
+        assert_eq!(ids(SortKey::Name), ["bundled", "mid", "new", "old"]);
+        assert_eq!(ids(SortKey::Default), ["bundled", "mid", "new", "old"]);
+    }
+
+    #[test]
+    fn test_sort_packs_by_size() {
+        let dir = TempDir::new().unwrap();
+        create_pack(dir.path(), "small", None);
+        create_pack(dir.path(), "large", None);
+        fs::write(dir.path().join("large/sounds/extra.wav"), vec![0u8; 4096]).unwrap();
+
+        let mut packs = discover_packs(dir.path());
+        sort_packs(&mut packs, SortKey::Size);
+        assert_eq!(packs[0].id, "large");
+    }
+
     #[test]
     fn test_discover_all_packs_no_user_packs() {
         let bundled = TempDir::new().unwrap();
@@ -344,4 +1478,607 @@ mod tests {
         let vol = pack.resolve_volume("KeyA");
         assert!((vol - 1.0).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn test_group_duplicate_packs_groups_identical() {
+        let bundled = TempDir::new().unwrap();
+        let user = TempDir::new().unwrap();
+
+        create_pack(bundled.path(), "default", None);
+        create_pack(bundled.path(), "default-copy", None);
+        create_pack(user.path(), "mine", Some("user"));
+
+        let dupes = group_duplicate_packs(bundled.path(), user.path());
+        assert_eq!(dupes, vec![vec!["default", "default-copy", "mine"]]);
+    }
+
+    #[test]
+    fn test_group_duplicate_packs_different_sounds() {
+        let bundled = TempDir::new().unwrap();
+        let user = TempDir::new().unwrap();
+
+        create_pack(bundled.path(), "alpha", None);
+        create_pack(bundled.path(), "beta", None);
+        fs::write(
+            bundled
+                .path()
+                .join("beta")
+                .join("sounds")
+                .join("keydown.wav"),
+            b"RIFF other",
+        )
+        .unwrap();
+
+        assert!(group_duplicate_packs(bundled.path(), user.path()).is_empty());
+    }
+
+    #[test]
+    fn test_load_clamps_absurd_volumes() {
+        let dir = TempDir::new().unwrap();
+        let pack_dir = dir.path().join("loud");
+        fs::create_dir_all(&pack_dir).unwrap();
+        let manifest = serde_json::json!({
+            "id": "loud",
+            "name": "Loud",
+            "defaults": { "keydown": "sounds/keydown.wav", "volume": 50.0 },
+            "key_overrides": {
+                "Space": { "keydown": null, "keyup": null, "volume": -3.0 }
+            },
+            "category_overrides": {
+                "modifiers": { "keys": ["ShiftLeft"], "keydown": null, "keyup": null, "volume": 1.5 }
+            }
+        });
+        fs::write(pack_dir.join("pack.json"), manifest.to_string()).unwrap();
+
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(pack.resolve_volume("KeyA"), MAX_PACK_VOLUME);
+        assert_eq!(pack.resolve_volume("Space"), 0.0);
+        // In-range values are left untouched
+        assert_eq!(pack.resolve_volume("ShiftLeft"), 1.5);
+    }
+
+    #[test]
+    fn test_resolve_keydown_disabled_category() {
+        let dir = TempDir::new().unwrap();
+        create_pack(dir.path(), "test", None);
+        let mut pack = SoundPack::load(&dir.path().join("test")).unwrap();
+        pack.category_overrides.insert(
+            "modifiers".into(),
+            CategoryOverride {
+                keys: vec!["ShiftLeft".into(), "ShiftRight".into()],
+                keydown: Some("sounds/mod.wav".into()),
+                keyup: None,
+                volume: None,
+                volume_jitter: None,
+                enabled: Some(false),
+            },
+        );
+
+        assert!(pack.resolve_keydown("ShiftLeft").is_none());
+        assert!(pack.resolve_keydown("KeyA").is_some());
+
+        pack.category_overrides
+            .get_mut("modifiers")
+            .unwrap()
+            .enabled = None;
+        let path = pack.resolve_keydown("ShiftLeft").unwrap();
+        assert!(path.ends_with("sounds/mod.wav"));
+    }
+
+    #[test]
+    fn test_coverage_splits_custom_and_default_keys() {
+        let dir = TempDir::new().unwrap();
+        create_pack(dir.path(), "test", None);
+        let mut pack = SoundPack::load(&dir.path().join("test")).unwrap();
+        pack.key_overrides.insert(
+            "KeyQ".into(),
+            KeySound {
+                keydown: Some("sounds/q.wav".into()),
+                keyup: None,
+                volume: None,
+                ..Default::default()
+            },
+        );
+        // A volume-only override still uses the default sound
+        pack.key_overrides.insert(
+            "KeyW".into(),
+            KeySound {
+                keydown: None,
+                keyup: None,
+                volume: Some(0.5),
+                ..Default::default()
+            },
+        );
+
+        let report = pack.coverage(&["KeyQ", "KeyW", "KeyE", "Space"]);
+        assert_eq!(report.covered, ["KeyQ"]);
+        assert_eq!(report.default_only, ["KeyW", "KeyE", "Space"]);
+        assert!(report.silenced.is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_diff_after_slot_change() {
+        let dir = TempDir::new().unwrap();
+        create_pack(dir.path(), "test", None);
+        let mut pack = SoundPack::load(&dir.path().join("test")).unwrap();
+        let snapshot = pack.snapshot();
+        assert!(snapshot.diff(&pack.snapshot()).is_empty());
+
+        pack.defaults.keydown = "sounds/new.wav".into();
+        pack.defaults.volume = 0.5;
+        pack.key_overrides.insert(
+            "Space".into(),
+            KeySound {
+                keydown: Some("sounds/space.wav".into()),
+                keyup: None,
+                volume: None,
+                ..Default::default()
+            },
+        );
+
+        let diffs = snapshot.diff(&pack.snapshot());
+        let changed: Vec<(&str, &str)> = diffs
+            .iter()
+            .map(|d| (d.slot.as_str(), d.field.as_str()))
+            .collect();
+        assert_eq!(
+            changed,
+            [
+                ("defaults", "keydown"),
+                ("defaults", "volume"),
+                ("key:Space", "keydown")
+            ]
+        );
+        assert_eq!(
+            diffs[0].before,
+            Some(serde_json::json!("sounds/keydown.wav"))
+        );
+        assert_eq!(diffs[0].after, Some(serde_json::json!("sounds/new.wav")));
+        assert_eq!(diffs[2].before, None);
+
+        // Removing the override shows up as unset on the other side
+        let edited = pack.snapshot();
+        pack.key_overrides.remove("Space");
+        let diffs = edited.diff(&pack.snapshot());
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].slot, "key:Space");
+        assert_eq!(diffs[0].after, None);
+    }
+
+    #[test]
+    fn test_resolve_trace_layers() {
+        let dir = TempDir::new().unwrap();
+        create_pack(dir.path(), "test", None);
+        let mut pack = SoundPack::load(&dir.path().join("test")).unwrap();
+        pack.key_overrides.insert(
+            "Space".into(),
+            KeySound {
+                keydown: Some("sounds/space.wav".into()),
+                keyup: None,
+                volume: Some(0.5),
+                ..Default::default()
+            },
+        );
+        pack.category_overrides.insert(
+            "modifiers".into(),
+            CategoryOverride {
+                keys: vec!["ShiftLeft".into()],
+                keydown: Some("sounds/mod.wav".into()),
+                keyup: None,
+                volume: Some(0.6),
+                volume_jitter: None,
+                enabled: None,
+            },
+        );
+        pack.category_overrides.insert(
+            "delete".into(),
+            CategoryOverride {
+                keys: vec!["Backspace".into()],
+                keydown: Some("sounds/bs.wav".into()),
+                keyup: None,
+                volume: None,
+                volume_jitter: None,
+                enabled: Some(false),
+            },
+        );
+
+        let trace = pack.resolve_trace("Space");
+        assert_eq!(trace.layer, ResolveLayer::KeyOverride);
+        assert!(trace.keydown.unwrap().ends_with("sounds/space.wav"));
+        assert_eq!(trace.volume, 0.5);
+
+        let trace = pack.resolve_trace("ShiftLeft");
+        assert_eq!(trace.layer, ResolveLayer::Category("modifiers".into()));
+        assert!(trace.keydown.unwrap().ends_with("sounds/mod.wav"));
+        assert_eq!(trace.volume, 0.6);
+
+        let trace = pack.resolve_trace("Backspace");
+        assert_eq!(trace.layer, ResolveLayer::DisabledCategory("delete".into()));
+        assert!(trace.keydown.is_none());
+
+        let trace = pack.resolve_trace("KeyA");
+        assert_eq!(trace.layer, ResolveLayer::Default);
+        assert!(trace.keydown.unwrap().ends_with("sounds/keydown.wav"));
+        assert_eq!(trace.volume, 1.0);
+
+        // Each key maps to the editor slot that decided its sound
+        assert_eq!(pack.slot_of("Space").as_deref(), Some("space"));
+        assert_eq!(pack.slot_of("ShiftLeft").as_deref(), Some("modifier"));
+        assert_eq!(pack.slot_of("Backspace"), None);
+        assert_eq!(pack.slot_of("KeyA").as_deref(), Some("default"));
+        pack.slot_triggers
+            .insert("space".into(), TriggerMode::Release);
+        assert_eq!(pack.resolve_trigger("Space"), TriggerMode::Release);
+        assert_eq!(pack.resolve_trigger("KeyA"), TriggerMode::Press);
+    }
+
+    #[test]
+    fn test_flatten_resolves_every_key() {
+        let dir = TempDir::new().unwrap();
+        let pack_dir = write_manifest(
+            dir.path(),
+            "flat",
+            serde_json::json!({
+                "id": "flat",
+                "name": "Flat",
+                "defaults": { "keydown": "sounds/keydown.wav", "volume": 0.8 },
+                "key_overrides": {
+                    "Space": { "keydown": "sounds/space.wav", "keyup": null, "volume": 0.5 }
+                },
+                "category_overrides": {
+                    "modifiers": { "keys": ["ShiftLeft"], "keydown": "sounds/mod.wav", "keyup": null, "volume": null },
+                    "delete": { "keys": ["Backspace"], "keydown": null, "keyup": null, "volume": null, "enabled": false }
+                }
+            }),
+        );
+        let pack = SoundPack::load(&pack_dir).unwrap();
+
+        let flat = pack.flatten(&["Space", "ShiftLeft", "Backspace", "KeyA"]);
+        let expected = [
+            (
+                "Space",
+                Some("sounds/space.wav"),
+                0.5,
+                ResolveLayer::KeyOverride,
+            ),
+            (
+                "ShiftLeft",
+                Some("sounds/mod.wav"),
+                0.8,
+                ResolveLayer::Category("modifiers".into()),
+            ),
+            (
+                "Backspace",
+                None,
+                0.8,
+                ResolveLayer::DisabledCategory("delete".into()),
+            ),
+            (
+                "KeyA",
+                Some("sounds/keydown.wav"),
+                0.8,
+                ResolveLayer::Default,
+            ),
+        ];
+        assert_eq!(flat.len(), expected.len());
+        for (got, (key, path, volume, layer)) in flat.iter().zip(expected) {
+            assert_eq!(got.key, key);
+            assert_eq!(got.keydown_path.as_deref(), path);
+            assert_eq!(got.volume, volume);
+            assert_eq!(got.source_layer, layer);
+        }
+    }
+
+    #[test]
+    fn test_load_rejects_newer_manifest_version() {
+        let dir = TempDir::new().unwrap();
+        let pack_dir = dir.path().join("future");
+        fs::create_dir_all(&pack_dir).unwrap();
+        let manifest = serde_json::json!({
+            "manifest_version": MANIFEST_VERSION + 1,
+            "id": "future",
+            "name": "Future",
+            "defaults": { "keydown": "sounds/keydown.wav" },
+            "some_new_field": { "unknown": true }
+        });
+        fs::write(pack_dir.join("pack.json"), manifest.to_string()).unwrap();
+
+        let err = SoundPack::load(&pack_dir).unwrap_err();
+        assert_eq!(err, "This pack requires a newer version of KeySound");
+    }
+
+    #[test]
+    fn test_load_defaults_manifest_version() {
+        let dir = TempDir::new().unwrap();
+        create_pack(dir.path(), "test", None);
+        let pack = SoundPack::load(&dir.path().join("test")).unwrap();
+        assert_eq!(pack.manifest_version, 1);
+    }
+
+    fn write_manifest(dir: &Path, id: &str, manifest: serde_json::Value) -> PathBuf {
+        let pack_dir = dir.join(id);
+        fs::create_dir_all(&pack_dir).unwrap();
+        fs::write(pack_dir.join("pack.json"), manifest.to_string()).unwrap();
+        pack_dir
+    }
+
+    #[test]
+    fn test_load_rejects_parent_dir_escape() {
+        let dir = TempDir::new().unwrap();
+        let pack_dir = write_manifest(
+            dir.path(),
+            "evil",
+            serde_json::json!({
+                "id": "evil",
+                "name": "Evil",
+                "defaults": { "keydown": "../../etc/passwd" }
+            }),
+        );
+        let err = SoundPack::load(&pack_dir).unwrap_err();
+        assert!(err.contains("outside the pack directory"), "{}", err);
+
+        let pack_dir = write_manifest(
+            dir.path(),
+            "sneaky",
+            serde_json::json!({
+                "id": "sneaky",
+                "name": "Sneaky",
+                "defaults": { "keydown": "sounds/keydown.wav" },
+                "key_overrides": {
+                    "KeyA": { "keydown": "sounds/../../other/a.wav", "keyup": null, "volume": null }
+                }
+            }),
+        );
+        assert!(SoundPack::load(&pack_dir).is_err());
+    }
+
+    #[test]
+    fn test_load_rejects_absolute_path() {
+        let dir = TempDir::new().unwrap();
+        let abs = dir.path().join("outside.wav");
+        let pack_dir = write_manifest(
+            dir.path(),
+            "abs",
+            serde_json::json!({
+                "id": "abs",
+                "name": "Abs",
+                "defaults": { "keydown": "sounds/keydown.wav", "keyup": abs.to_str().unwrap() }
+            }),
+        );
+        let err = SoundPack::load(&pack_dir).unwrap_err();
+        assert!(err.contains("outside the pack directory"), "{}", err);
+    }
+
+    #[test]
+    fn test_is_contained_path() {
+        assert!(is_contained_path("sounds/keydown.wav"));
+        assert!(is_contained_path("./sounds/a/../b.wav"));
+        assert!(!is_contained_path("../x.wav"));
+        assert!(!is_contained_path("sounds/../../x.wav"));
+        assert!(!is_contained_path("/etc/passwd"));
+        assert!(!is_contained_path(""));
+        assert!(!is_contained_path("sounds/.."));
+    }
+
+    #[test]
+    fn test_validate_pack_reports_missing_files() {
+        let dir = TempDir::new().unwrap();
+        create_pack(dir.path(), "test", None);
+        let mut pack = SoundPack::load(&dir.path().join("test")).unwrap();
+        assert!(validate_pack(&pack).is_empty());
+
+        pack.defaults.keyup = Some("sounds/up.wav".into());
+        assert_eq!(
+            validate_pack(&pack),
+            vec![PackIssue::MissingFile {
+                path: "sounds/up.wav".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_pack_reports_unknown_keys() {
+        let dir = TempDir::new().unwrap();
+        let pack_dir = write_manifest(
+            dir.path(),
+            "typo",
+            serde_json::json!({
+                "id": "typo",
+                "name": "Typo",
+                "defaults": { "keydown": "sounds/keydown.wav" },
+                "key_overrides": { "Retrun": { "keydown": null, "keyup": null, "volume": 0.5 } },
+                "category_overrides": {
+                    "modifiers": { "keys": ["ShiftLeft", "Shfit"], "keydown": null, "keyup": null, "volume": 0.5 }
+                }
+            }),
+        );
+        fs::create_dir_all(pack_dir.join("sounds")).unwrap();
+        fs::write(pack_dir.join("sounds").join("keydown.wav"), b"").unwrap();
+
+        // Still loads; the typos are only reported
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(
+            validate_pack(&pack),
+            vec![
+                PackIssue::UnknownKey {
+                    name: "key:Retrun".into(),
+                    key: "Retrun".into()
+                },
+                PackIssue::UnknownKey {
+                    name: "category:modifiers".into(),
+                    key: "Shfit".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_pack_reports_noop_overrides() {
+        let dir = TempDir::new().unwrap();
+        create_pack(dir.path(), "test", None);
+        let mut pack = SoundPack::load(&dir.path().join("test")).unwrap();
+        let category = |keys: Vec<String>, enabled| CategoryOverride {
+            keys,
+            keydown: None,
+            keyup: None,
+            volume: None,
+            volume_jitter: None,
+            enabled,
+        };
+        pack.category_overrides
+            .insert("empty".into(), category(Vec::new(), None));
+        pack.category_overrides
+            .insert("soundless".into(), category(vec!["KeyA".into()], None));
+        // Silencing keys is an effect, even without sounds
+        pack.category_overrides
+            .insert("muted".into(), category(vec!["KeyB".into()], Some(false)));
+        pack.key_overrides.insert(
+            "KeyC".into(),
+            KeySound {
+                keydown: None,
+                keyup: None,
+                volume: None,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            validate_pack(&pack),
+            vec![
+                PackIssue::NoOpOverride {
+                    name: "key:KeyC".into()
+                },
+                PackIssue::NoOpOverride {
+                    name: "category:empty".into()
+                },
+                PackIssue::NoOpOverride {
+                    name: "category:soundless".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_sideloaded_pack_outside_managed_roots() {
+        let elsewhere = TempDir::new().unwrap();
+        create_pack(elsewhere.path(), "wip", Some("user"));
+        let pack = SoundPack::load_sideloaded(&elsewhere.path().join("wip")).unwrap();
+        assert!(pack.is_sideloaded());
+        assert!(pack.info().sideloaded);
+        assert_eq!(pack.source.as_deref(), Some("user"));
+        assert_eq!(pack.base_path, elsewhere.path().join("wip"));
+        assert!(!serde_json::to_string(&pack).unwrap().contains("sideloaded"));
+
+        // A manifest can't mark itself sideloaded
+        let on_disk = SoundPack::load(&elsewhere.path().join("wip")).unwrap();
+        assert!(!on_disk.is_sideloaded());
+        let manifest = r#"{ "id": "x", "name": "X", "source": "sideloaded", "sideloaded": true,
+            "defaults": { "keydown": "sounds/keydown.wav" } }"#;
+        assert!(!SoundPack::parse(manifest, elsewhere.path(), "test")
+            .unwrap()
+            .is_sideloaded());
+
+        let err = SoundPack::load_sideloaded(elsewhere.path()).unwrap_err();
+        assert!(err.contains("No pack.json"));
+    }
+
+    #[test]
+    fn test_load_manifest_without_default_keydown() {
+        let dir = TempDir::new().unwrap();
+        let pack_dir = write_manifest(
+            dir.path(),
+            "minimal",
+            serde_json::json!({
+                "id": "minimal",
+                "name": "Minimal",
+                "defaults": { "volume": 0.8 }
+            }),
+        );
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(pack.defaults.keydown, "sounds/keydown.wav");
+        assert_eq!(pack.defaults.volume, 0.8);
+    }
+
+    #[test]
+    fn test_pack_json_directory_is_reported_clearly() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("odd").join("pack.json")).unwrap();
+
+        let err = SoundPack::load(&dir.path().join("odd")).unwrap_err();
+        assert!(err.contains("pack.json is not a file"), "{}", err);
+        // Discovery skips it and reports it as unloadable
+        assert!(discover_packs(dir.path()).is_empty());
+        let unloadable = find_unloadable_packs(&[dir.path()]);
+        assert_eq!(unloadable.len(), 1);
+        assert!(unloadable[0].error.contains("pack.json is not a file"));
+    }
+
+    #[test]
+    fn test_validate_manifest_json_reports_parse_position() {
+        let dir = TempDir::new().unwrap();
+        let err =
+            validate_manifest_json("{\n  \"id\": \"x\",\n  \"name\": }", dir.path()).unwrap_err();
+        assert!(err.contains("line 3"), "{}", err);
+        // Missing required fields are reported too
+        let err = validate_manifest_json(r#"{"id": "x"}"#, dir.path()).unwrap_err();
+        assert!(err.contains("missing field"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_manifest_json_reports_issues_without_writing() {
+        let dir = TempDir::new().unwrap();
+        create_pack(dir.path(), "test", None);
+        let pack_dir = dir.path().join("test");
+        let before = fs::read_to_string(pack_dir.join("pack.json")).unwrap();
+
+        let edited = serde_json::json!({
+            "id": "test",
+            "name": "Edited",
+            "defaults": { "keydown": "sounds/keydown.wav", "keyup": "sounds/gone.wav" }
+        })
+        .to_string();
+        assert_eq!(
+            validate_manifest_json(&edited, &pack_dir).unwrap(),
+            vec![PackIssue::MissingFile {
+                path: "sounds/gone.wav".into()
+            }]
+        );
+        assert_eq!(
+            fs::read_to_string(pack_dir.join("pack.json")).unwrap(),
+            before
+        );
+    }
+
+    #[test]
+    fn test_resolve_scroll_needs_explicit_override() {
+        let dir = TempDir::new().unwrap();
+        let pack_dir = write_manifest(
+            dir.path(),
+            "scroll",
+            serde_json::json!({
+                "id": "scroll",
+                "name": "Scroll",
+                "defaults": { "keydown": "sounds/keydown.wav" },
+                "category_overrides": {
+                    "scroll": {
+                        "keys": ["ScrollUp", "ScrollDown"],
+                        "keydown": "sounds/tick.wav",
+                        "keyup": null,
+                        "volume": 0.3
+                    }
+                }
+            }),
+        );
+        let pack = SoundPack::load(&pack_dir).unwrap();
+        assert_eq!(
+            pack.resolve_scroll("ScrollUp"),
+            Some(pack_dir.join("sounds/tick.wav"))
+        );
+
+        // Without a scroll category the default key sound is not used
+        let mut plain = pack.clone();
+        plain.category_overrides.clear();
+        assert!(plain.resolve_scroll("ScrollDown").is_none());
+        assert!(plain.resolve_keydown("ScrollDown").is_some());
+    }
 }